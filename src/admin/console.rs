@@ -0,0 +1,322 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    io,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use east_online_core::model::Vector3;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+    sync::{mpsc, oneshot},
+};
+
+use crate::map::{self, Command, ProfileChange, Status};
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+/**
+ * Operator commands, run against the map workers through their channels.
+ *
+ * Every command is a line of words, answered with the output lines
+ * followed by `ok` or by a single `error: ...` line.
+ */
+#[derive(Clone)]
+pub struct Console {
+    maps: Arc<Mutex<BTreeMap<String, mpsc::Sender<Command>>>>,
+    loader: Arc<dyn map::Loader>,
+}
+
+impl Console {
+    pub fn new(loader: Arc<dyn map::Loader>) -> Self {
+        Console {
+            maps: Arc::new(Mutex::new(BTreeMap::new())),
+            loader,
+        }
+    }
+
+    /**
+     * Add a map to every clone of the console.
+     */
+    pub fn add_map(&self, id: &str, control: mpsc::Sender<Command>) {
+        self.maps.lock().unwrap().insert(id.to_string(), control);
+    }
+
+    pub fn remove_map(&self, id: &str) {
+        self.maps.lock().unwrap().remove(id);
+    }
+
+    /**
+     * Accept operators on a Unix socket, replacing a stale one.
+     */
+    pub async fn listen(self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+
+        let listener = UnixListener::bind(path)?;
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+
+            let console = self.clone();
+
+            tokio::spawn(async move {
+                if let Err(e) = console.serve(stream).await {
+                    eprintln!("admin session failed for {e}");
+                }
+            });
+        }
+    }
+
+    async fn serve(&self, stream: UnixStream) -> io::Result<()> {
+        let (reader, mut writer) = stream.into_split();
+
+        let mut lines = BufReader::new(reader).lines();
+
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let response = match self.execute(&line).await {
+                Ok(output) if output.is_empty() => String::from("ok\n"),
+                Ok(output) => format!("{output}\nok\n"),
+                Err(e) => format!("error: {e}\n"),
+            };
+
+            writer.write_all(response.as_bytes()).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn execute(&self, line: &str) -> Result<String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words.as_slice() {
+            ["maps"] => {
+                let mut lines = Vec::new();
+
+                for status in self.statuses().await {
+                    lines.push(format!(
+                        "{} {} {}",
+                        status.id,
+                        status.name,
+                        status.users.len()
+                    ));
+                }
+
+                Ok(lines.join("\n"))
+            }
+            ["autosave"] => {
+                let mut lines = Vec::new();
+
+                for status in self.statuses().await {
+                    let report = status.autosave;
+
+                    let latency = report.last_latency.map_or(String::from("-"), |latency| {
+                        format!("{}ms", latency.as_millis())
+                    });
+
+                    lines.push(format!(
+                        "{} dirty {} pending {} saves {} failures {} last {} in {}",
+                        status.id,
+                        status.dirty,
+                        report.pending,
+                        report.saves,
+                        report.failures,
+                        report.last_count,
+                        latency
+                    ));
+                }
+
+                Ok(lines.join("\n"))
+            }
+            ["users"] => {
+                let mut lines = Vec::new();
+
+                for status in self.statuses().await {
+                    lines.extend(users(&status));
+                }
+
+                Ok(lines.join("\n"))
+            }
+            ["users", map_id] => Ok(users(&self.status(map_id).await?).join("\n")),
+            ["kick", user_id] => {
+                let map_id = self.locate(user_id).await?;
+
+                let (reply, response) = oneshot::channel();
+
+                self.send(&map_id, Command::Kick(user_id.to_string(), reply))
+                    .await?;
+
+                match response.await? {
+                    true => Ok(format!("kicked {} from {}", user_id, map_id)),
+                    false => Err(format!("{} already left", user_id).into()),
+                }
+            }
+            ["teleport", user_id, x, y, z] => {
+                let position = Vector3 {
+                    x: x.parse()?,
+                    y: y.parse()?,
+                    z: z.parse()?,
+                };
+
+                let map_id = self.locate(user_id).await?;
+
+                let (reply, response) = oneshot::channel();
+
+                let command = Command::Teleport(user_id.to_string(), position, reply);
+
+                self.send(&map_id, command).await?;
+
+                response.await??;
+
+                Ok(format!("teleported {} to {} {} {}", user_id, x, y, z))
+            }
+            ["profile", user_id, field, value] => {
+                let change = match *field {
+                    "name" => ProfileChange::Name(value.to_string()),
+                    "appearance" => ProfileChange::Appearance(value.to_string()),
+                    "level" => ProfileChange::Level(value.parse()?),
+                    _ => return Err(format!("unknown profile field, {}", field).into()),
+                };
+
+                let map_id = self.locate(user_id).await?;
+
+                let (reply, response) = oneshot::channel();
+
+                self.send(
+                    &map_id,
+                    Command::Profile(user_id.to_string(), change, reply),
+                )
+                .await?;
+
+                response.await??;
+
+                Ok(format!("set {} of {} to {}", field, user_id, value))
+            }
+            ["notice", ..] => {
+                let message = line.trim_start()["notice".len()..].trim().to_string();
+
+                let map_ids = self.map_ids();
+
+                for map_id in &map_ids {
+                    self.send(map_id, Command::Notice(message.to_owned()))
+                        .await?;
+                }
+
+                Ok(format!("noticed {} maps", map_ids.len()))
+            }
+            ["reload", map_id] => {
+                let tiles = self.loader.load(map_id).await?;
+
+                let (reply, response) = oneshot::channel();
+
+                self.send(map_id, Command::Reload(tiles, reply)).await?;
+
+                let displaced = response.await??;
+
+                Ok(format!(
+                    "reloaded {}, {} actors displaced",
+                    map_id, displaced
+                ))
+            }
+            ["transfer" | "instance", destination, user_ids @ ..] if !user_ids.is_empty() => {
+                let map_id = self.locate(user_ids[0]).await?;
+
+                let user_ids = user_ids.iter().map(|id| id.to_string()).collect();
+
+                let (reply, response) = oneshot::channel();
+
+                let command = Command::Transfer(user_ids, destination.to_string(), reply);
+
+                self.send(&map_id, command).await?;
+
+                let count = response.await??;
+
+                Ok(format!(
+                    "sent {} users from {} to {}",
+                    count, map_id, destination
+                ))
+            }
+            _ => Err(format!("unknown command, {}", line.trim()).into()),
+        }
+    }
+
+    async fn send(&self, map_id: &str, command: Command) -> Result<()> {
+        let control = self
+            .maps
+            .lock()
+            .unwrap()
+            .get(map_id)
+            .ok_or("map not found")?
+            .clone();
+
+        control
+            .send(command)
+            .await
+            .map_err(|_| format!("{} is not running", map_id))?;
+
+        Ok(())
+    }
+
+    fn map_ids(&self) -> Vec<String> {
+        self.maps.lock().unwrap().keys().cloned().collect()
+    }
+
+    async fn status(&self, map_id: &str) -> Result<Status> {
+        let (reply, response) = oneshot::channel();
+
+        self.send(map_id, Command::Status(reply)).await?;
+
+        Ok(response.await?)
+    }
+
+    /**
+     * Ask every running map for its status, skipping the ones that close
+     * in the meantime.
+     */
+    async fn statuses(&self) -> Vec<Status> {
+        let mut statuses = Vec::new();
+
+        for id in &self.map_ids() {
+            if let Ok(status) = self.status(id).await {
+                statuses.push(status);
+            }
+        }
+
+        statuses
+    }
+
+    /**
+     * Find the map a user is on.
+     */
+    async fn locate(&self, user_id: &str) -> Result<String> {
+        for status in self.statuses().await {
+            if status.users.iter().any(|(key, _)| key == user_id) {
+                return Ok(status.id);
+            }
+        }
+
+        Err(format!("{} is not connected", user_id).into())
+    }
+}
+
+fn users(status: &Status) -> Vec<String> {
+    status
+        .users
+        .iter()
+        .map(|(user_id, position)| {
+            format!(
+                "{} {} {} {} {}",
+                user_id, status.id, position.x, position.y, position.z
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,3 @@
+mod console;
+
+pub use console::Console;
@@ -0,0 +1,47 @@
+use std::error::Error;
+
+use east_online_core::model;
+use reqwest::{header::AUTHORIZATION, StatusCode};
+
+use crate::env::{url, API_ORIGIN};
+
+use super::Authenticator;
+
+pub struct Api {
+    client: reqwest::Client,
+}
+
+impl Api {
+    pub fn new() -> Self {
+        Api {
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+impl Default for Api {
+    fn default() -> Self {
+        Api::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl Authenticator for Api {
+    async fn authenticate(&self, token: &str) -> Result<String, Box<dyn Error>> {
+        let response = self
+            .client
+            .get(url(API_ORIGIN, "auth"))
+            .header(AUTHORIZATION, format!("Bearer {}", token))
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::CREATED => {
+                let token = response.json::<model::Token>().await?;
+
+                Ok(token.id)
+            }
+            _ => Err(response.text().await?.into()),
+        }
+    }
+}
@@ -0,0 +1,19 @@
+mod api;
+
+pub use api::Api;
+
+mod stub;
+
+pub use stub::Stub;
+
+use std::error::Error;
+
+#[async_trait::async_trait]
+pub trait Authenticator: Send + Sync {
+    /**
+     * Resolve a bearer token into the id of its user.
+     *
+     * Throw an error if the token is rejected.
+     */
+    async fn authenticate(&self, token: &str) -> Result<String, Box<dyn Error>>;
+}
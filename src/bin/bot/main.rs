@@ -0,0 +1,232 @@
+//! Headless bots to put load on a server.
+//!
+//! Run with `bot [address] [count] [seconds]`. The server has to authenticate
+//! with stub tokens (`AUTH_PROVIDER=stub`), and the users from `get_user_id`
+//! have to exist in its database.
+
+mod report;
+
+use std::{error::Error, sync::Mutex};
+
+use east_online_core::model::Direction;
+use east_online_server::{
+    auth,
+    net::{
+        client::{read_packet, write_packet},
+        packet::{Incoming, Outgoing},
+        protocol,
+    },
+};
+use tokio::{
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpStream,
+    },
+    sync::{mpsc, Notify},
+    time,
+};
+
+use report::{Event, Report};
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+const STEP_DURATION: time::Duration = time::Duration::from_millis(300);
+
+const REST_DURATION: time::Duration = time::Duration::from_millis(500);
+
+struct Config {
+    address: String,
+    count: usize,
+    duration: time::Duration,
+}
+
+impl Config {
+    fn from_args() -> Result<Self> {
+        let mut args = std::env::args().skip(1);
+
+        let address = args.next().unwrap_or(String::from("127.0.0.1:3000"));
+
+        let count = match args.next() {
+            Some(count) => count.parse()?,
+            None => 10,
+        };
+
+        let duration = match args.next() {
+            Some(seconds) => time::Duration::from_secs(seconds.parse()?),
+            None => time::Duration::from_secs(60),
+        };
+
+        Ok(Config {
+            address,
+            count,
+            duration,
+        })
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let config = Config::from_args()?;
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    println!("start {} bots against {}", config.count, config.address);
+
+    for index in 0..config.count {
+        let tx = tx.clone();
+
+        let address = config.address.clone();
+
+        tokio::spawn(async move {
+            let reason = match run_bot(index, &address, &tx).await {
+                Ok(()) => String::from("closed"),
+                Err(e) => format!("{e}"),
+            };
+
+            tx.send(Event::Disconnected(reason)).ok();
+        });
+    }
+
+    drop(tx);
+
+    let started_at = time::Instant::now();
+
+    let deadline = time::sleep(config.duration);
+
+    tokio::pin!(deadline);
+
+    let mut report = Report::default();
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => match event {
+                Some(event) => report.record(event),
+                None => break,
+            },
+            _ = &mut deadline => break,
+        }
+    }
+
+    report.print(started_at.elapsed());
+
+    Ok(())
+}
+
+fn get_user_id(index: usize) -> String {
+    format!("00000000-0000-4000-8000-{:012}", index)
+}
+
+async fn run_bot(index: usize, address: &str, tx: &mpsc::UnboundedSender<Event>) -> Result<()> {
+    let stream = TcpStream::connect(address).await?;
+
+    let (mut reader, mut writer) = stream.into_split();
+
+    let user_id = get_user_id(index);
+
+    let hello = Incoming::Hello {
+        version: protocol::PROTOCOL_VERSION,
+        features: protocol::FEATURES.iter().map(|f| f.to_string()).collect(),
+        token: auth::Stub::token(&user_id),
+    };
+
+    tx.send(Event::Sent(write_packet(&mut writer, &hello).await?))?;
+
+    let entered = Notify::new();
+
+    let pending = Mutex::new(None);
+
+    tokio::select! {
+        result = read(&mut reader, &user_id, &entered, &pending, tx) => result,
+        result = walk(&mut writer, index as u64, &entered, &pending, tx) => result,
+    }
+}
+
+/**
+ * Read every packet and measure how long the server took to answer a move.
+ */
+async fn read(
+    reader: &mut OwnedReadHalf,
+    user_id: &str,
+    entered: &Notify,
+    pending: &Mutex<Option<time::Instant>>,
+    tx: &mpsc::UnboundedSender<Event>,
+) -> Result<()> {
+    loop {
+        let (packet, size) = read_packet(reader).await?;
+
+        tx.send(Event::Received(size))?;
+
+        match packet {
+            Outgoing::Hello { id, .. } if id == user_id => entered.notify_one(),
+            Outgoing::Move { id, .. } | Outgoing::Stop { id, .. } if id == user_id => {
+                if let Some(sent_at) = pending.lock().unwrap().take() {
+                    tx.send(Event::Latency(sent_at.elapsed()))?;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/**
+ * Walk a few steps to a random direction, rest, and repeat.
+ */
+async fn walk(
+    writer: &mut OwnedWriteHalf,
+    seed: u64,
+    entered: &Notify,
+    pending: &Mutex<Option<time::Instant>>,
+    tx: &mpsc::UnboundedSender<Event>,
+) -> Result<()> {
+    entered.notified().await;
+
+    let mut random = Random::new(seed);
+
+    loop {
+        let direction = match random.next() % 4 {
+            0 => Direction::Up,
+            1 => Direction::Right,
+            2 => Direction::Down,
+            _ => Direction::Left,
+        };
+
+        *pending.lock().unwrap() = Some(time::Instant::now());
+
+        let packet = Incoming::Move { direction };
+
+        tx.send(Event::Sent(write_packet(writer, &packet).await?))?;
+
+        let steps = 1 + (random.next() % 4) as u32;
+
+        time::sleep(STEP_DURATION * steps).await;
+
+        let packet = Incoming::Move {
+            direction: Direction::Idle,
+        };
+
+        tx.send(Event::Sent(write_packet(writer, &packet).await?))?;
+
+        time::sleep(REST_DURATION).await;
+    }
+}
+
+/**
+ * Xorshift, good enough to scatter the bots.
+ */
+struct Random(u64);
+
+impl Random {
+    fn new(seed: u64) -> Self {
+        Random(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+
+        self.0 ^= self.0 >> 7;
+
+        self.0 ^= self.0 << 17;
+
+        self.0
+    }
+}
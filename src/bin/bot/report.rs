@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use tokio::time;
+
+#[derive(Debug)]
+pub enum Event {
+    Sent(usize),
+    Received(usize),
+    Latency(time::Duration),
+    Disconnected(String),
+}
+
+#[derive(Default)]
+pub struct Report {
+    sent_packets: usize,
+    sent_bytes: usize,
+    received_packets: usize,
+    received_bytes: usize,
+    latencies: Vec<time::Duration>,
+    disconnections: HashMap<String, usize>,
+}
+
+impl Report {
+    pub fn record(&mut self, event: Event) {
+        match event {
+            Event::Sent(bytes) => {
+                self.sent_packets += 1;
+
+                self.sent_bytes += bytes;
+            }
+            Event::Received(bytes) => {
+                self.received_packets += 1;
+
+                self.received_bytes += bytes;
+            }
+            Event::Latency(latency) => self.latencies.push(latency),
+            Event::Disconnected(reason) => {
+                *self.disconnections.entry(reason).or_insert(0) += 1;
+            }
+        }
+    }
+
+    pub fn print(&mut self, elapsed: time::Duration) {
+        let seconds = elapsed.as_secs_f64().max(f64::EPSILON);
+
+        self.latencies.sort();
+
+        println!("elapsed {:.1}s", seconds);
+
+        println!(
+            "sent {} packets, {:.1} packets/s, {:.1} bytes/s",
+            self.sent_packets,
+            self.sent_packets as f64 / seconds,
+            self.sent_bytes as f64 / seconds,
+        );
+
+        println!(
+            "received {} packets, {:.1} packets/s, {:.1} bytes/s",
+            self.received_packets,
+            self.received_packets as f64 / seconds,
+            self.received_bytes as f64 / seconds,
+        );
+
+        println!("latency over {} samples", self.latencies.len());
+
+        for percentile in [50, 90, 99] {
+            match self.percentile(percentile) {
+                Some(latency) => println!("  p{} {:?}", percentile, latency),
+                None => println!("  p{} -", percentile),
+            }
+        }
+
+        println!("disconnections");
+
+        for (reason, count) in &self.disconnections {
+            println!("  {} {}", count, reason);
+        }
+    }
+
+    fn percentile(&self, percentile: usize) -> Option<time::Duration> {
+        if self.latencies.is_empty() {
+            return None;
+        }
+
+        let index = (self.latencies.len() * percentile / 100).min(self.latencies.len() - 1);
+
+        Some(self.latencies[index])
+    }
+}
@@ -0,0 +1,158 @@
+use std::{collections::HashMap, error::Error};
+
+use east_online_core::model::{self, Vector3};
+
+use crate::{
+    env::{url, CDN_ORIGIN},
+    item,
+    map::{self, Tile},
+    net::packet::ITEM_ID_LENGTH,
+    quest, shop,
+};
+
+pub async fn fetch_map_manifest() -> Result<model::MapManifest, Box<dyn Error>> {
+    let response = reqwest::get(url(CDN_ORIGIN, "maps/manifest.yml")).await?;
+
+    let bytes = response.bytes().await?;
+
+    let result: model::MapManifest = serde_yaml::from_slice(&bytes)?;
+
+    Ok(result)
+}
+
+pub async fn fetch_map(id: &str) -> Result<model::Map, Box<dyn Error>> {
+    let response = reqwest::get(url(CDN_ORIGIN, &format!("maps/{}.yml", id))).await?;
+
+    let bytes = response.bytes().await?;
+
+    let result: model::Map = serde_yaml::from_slice(&bytes)?;
+
+    Ok(result)
+}
+
+/**
+ * Fetch the shops placed on a map.
+ *
+ * Most maps have none, so a missing file means no shops.
+ */
+pub async fn fetch_shops(map_id: &str) -> Result<Vec<shop::Definition>, Box<dyn Error>> {
+    let response = reqwest::get(url(CDN_ORIGIN, &format!("maps/{}.shops.yml", map_id))).await?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(vec![]);
+    }
+
+    let bytes = response.error_for_status()?.bytes().await?;
+
+    let result: shop::Manifest = serde_yaml::from_slice(&bytes)?;
+
+    Ok(result.shops)
+}
+
+pub async fn fetch_item_manifest() -> Result<item::Manifest, Box<dyn Error>> {
+    let response = reqwest::get(url(CDN_ORIGIN, "items/manifest.yml")).await?;
+
+    let bytes = response.bytes().await?;
+
+    let result: item::Manifest = serde_yaml::from_slice(&bytes)?;
+
+    Ok(result)
+}
+
+/**
+ * Fetch an item definition.
+ *
+ * Item ids go on the wire without a length, so one of another length
+ * would break every stream it's sent to.
+ */
+pub async fn fetch_item(id: &str) -> Result<item::Definition, Box<dyn Error>> {
+    let response = reqwest::get(url(CDN_ORIGIN, &format!("items/{}.yml", id))).await?;
+
+    let bytes = response.bytes().await?;
+
+    let result: item::Definition = serde_yaml::from_slice(&bytes)?;
+
+    if result.id.len() != ITEM_ID_LENGTH {
+        return Err(format!("item id {} is malformed", result.id).into());
+    }
+
+    Ok(result)
+}
+
+/**
+ * Fetch the objects placed on a map.
+ *
+ * A missing file means the map has none, as with shops.
+ */
+pub async fn fetch_objects(map_id: &str) -> Result<Vec<map::ObjectDefinition>, Box<dyn Error>> {
+    let response = reqwest::get(url(CDN_ORIGIN, &format!("maps/{}.objects.yml", map_id))).await?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(vec![]);
+    }
+
+    let bytes = response.error_for_status()?.bytes().await?;
+
+    let result: map::ObjectManifest = serde_yaml::from_slice(&bytes)?;
+
+    Ok(result.objects)
+}
+
+/**
+ * Fetch the spawn point and the NPCs of a map.
+ *
+ * A missing file means players enter at the origin and no NPCs fight there.
+ */
+pub async fn fetch_npcs(map_id: &str) -> Result<map::NpcManifest, Box<dyn Error>> {
+    let response = reqwest::get(url(CDN_ORIGIN, &format!("maps/{}.npcs.yml", map_id))).await?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(map::NpcManifest::default());
+    }
+
+    let bytes = response.error_for_status()?.bytes().await?;
+
+    let result: map::NpcManifest = serde_yaml::from_slice(&bytes)?;
+
+    Ok(result)
+}
+
+pub async fn fetch_quest_manifest() -> Result<quest::Manifest, Box<dyn Error>> {
+    let response = reqwest::get(url(CDN_ORIGIN, "quests/manifest.yml")).await?;
+
+    let bytes = response.bytes().await?;
+
+    let result: quest::Manifest = serde_yaml::from_slice(&bytes)?;
+
+    Ok(result)
+}
+
+pub async fn fetch_quest(id: &str) -> Result<quest::Definition, Box<dyn Error>> {
+    let response = reqwest::get(url(CDN_ORIGIN, &format!("quests/{}.yml", id))).await?;
+
+    let bytes = response.bytes().await?;
+
+    let result: quest::Definition = serde_yaml::from_slice(&bytes)?;
+
+    Ok(result)
+}
+
+/**
+ * Load maps from the CDN, as they were fetched at startup.
+ */
+pub struct Cdn;
+
+#[async_trait::async_trait]
+impl map::Loader for Cdn {
+    async fn load(&self, id: &str) -> Result<HashMap<Vector3, Tile>, Box<dyn Error + Send + Sync>> {
+        let map = fetch_map(id).await.map_err(|e| e.to_string())?;
+
+        let tiles = map
+            .tiles
+            .into_iter()
+            .map(|(position, placable)| (position, Tile::from_placable(placable)))
+            .collect();
+
+        Ok(tiles)
+    }
+}
@@ -0,0 +1,28 @@
+use tokio::time;
+
+use crate::map::Component;
+
+pub struct Attack {
+    pub power: u32,
+    pub cooldown: time::Duration,
+    pub attacked_at: Option<time::Instant>,
+}
+
+impl Attack {
+    pub fn new(power: u32, cooldown: time::Duration) -> Self {
+        Attack {
+            power,
+            cooldown,
+            attacked_at: None,
+        }
+    }
+
+    pub fn is_ready(&self, now: time::Instant) -> bool {
+        match self.attacked_at {
+            Some(attacked_at) => attacked_at + self.cooldown <= now,
+            None => true,
+        }
+    }
+}
+
+impl Component for Attack {}
@@ -0,0 +1,38 @@
+use crate::map::Component;
+
+pub struct Health {
+    pub current: u32,
+    pub max: u32,
+    pub defense: u32,
+}
+
+impl Health {
+    pub fn new(max: u32, defense: u32) -> Self {
+        Health {
+            current: max,
+            max,
+            defense,
+        }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current == 0
+    }
+
+    /**
+     * Apply damage and return the amount actually taken.
+     */
+    pub fn hurt(&mut self, amount: u32) -> u32 {
+        let taken = amount.min(self.current);
+
+        self.current -= taken;
+
+        taken
+    }
+
+    pub fn restore(&mut self) {
+        self.current = self.max;
+    }
+}
+
+impl Component for Health {}
@@ -0,0 +1,31 @@
+mod health;
+
+pub use health::Health;
+
+mod attack;
+
+pub use attack::Attack;
+
+use crate::map::Actor;
+
+pub const RESPAWN_DELAY: tokio::time::Duration = tokio::time::Duration::from_secs(5);
+
+pub const ATTACK_COOLDOWN: tokio::time::Duration = tokio::time::Duration::from_millis(800);
+
+/**
+ * Calculate the damage an attack deals.
+ *
+ * Every attack deals at least one point, so armor can't make an actor immortal.
+ */
+pub fn damage(power: u32, defense: u32) -> u32 {
+    power.saturating_sub(defense).max(1)
+}
+
+/**
+ * Check if an actor is dead.
+ *
+ * Actors without health can't die.
+ */
+pub fn is_dead(actor: &Actor) -> bool {
+    actor.get::<Health>().is_some_and(Health::is_dead)
+}
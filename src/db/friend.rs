@@ -0,0 +1,85 @@
+use std::error::Error;
+
+use mysql::{params, prelude::*};
+
+pub trait FriendStore {
+    fn find_friends(&self, user_id: &str) -> Result<Vec<String>, Box<dyn Error>>;
+
+    /**
+     * Find who asked to be friends with a user.
+     */
+    fn find_friend_requests(&self, user_id: &str) -> Result<Vec<String>, Box<dyn Error>>;
+
+    fn add_friend_request(&self, from: &str, to: &str) -> Result<(), Box<dyn Error>>;
+
+    /**
+     * Turn a request into a friendship both ways.
+     *
+     * Return false if there was no such request.
+     */
+    fn accept_friend_request(&self, user_id: &str, from: &str) -> Result<bool, Box<dyn Error>>;
+}
+
+impl FriendStore for mysql::Pool {
+    fn find_friends(&self, user_id: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut conn = self.get_conn()?;
+
+        let friends = conn.exec(
+            "SELECT friend_id FROM friends WHERE user_id = :user_id",
+            params! { "user_id" => user_id },
+        )?;
+
+        Ok(friends)
+    }
+
+    fn find_friend_requests(&self, user_id: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut conn = self.get_conn()?;
+
+        let requests = conn.exec(
+            "SELECT from_id FROM friend_requests WHERE to_id = :to_id",
+            params! { "to_id" => user_id },
+        )?;
+
+        Ok(requests)
+    }
+
+    fn add_friend_request(&self, from: &str, to: &str) -> Result<(), Box<dyn Error>> {
+        let mut conn = self.get_conn()?;
+
+        conn.exec_drop(
+            "INSERT IGNORE INTO friend_requests (from_id, to_id) VALUES (:from_id, :to_id)",
+            params! { "from_id" => from, "to_id" => to },
+        )?;
+
+        Ok(())
+    }
+
+    fn accept_friend_request(&self, user_id: &str, from: &str) -> Result<bool, Box<dyn Error>> {
+        let mut conn = self.get_conn()?;
+
+        let mut tx = conn.start_transaction(mysql::TxOpts::default())?;
+
+        tx.exec_drop(
+            "DELETE FROM friend_requests WHERE from_id = :from_id AND to_id = :to_id",
+            params! { "from_id" => from, "to_id" => user_id },
+        )?;
+
+        if tx.affected_rows() == 0 {
+            tx.rollback()?;
+
+            return Ok(false);
+        }
+
+        tx.exec_batch(
+            "INSERT IGNORE INTO friends (user_id, friend_id) VALUES (:user_id, :friend_id)",
+            [
+                params! { "user_id" => user_id, "friend_id" => from },
+                params! { "user_id" => from, "friend_id" => user_id },
+            ],
+        )?;
+
+        tx.commit()?;
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,75 @@
+use std::error::Error;
+
+use mysql::{params, prelude::*};
+
+use crate::item::{Inventory, Stack};
+
+pub trait InventoryStore {
+    fn find_inventory(&self, user_id: &str) -> Result<Inventory, Box<dyn Error>>;
+
+    fn save_inventory(&self, user_id: &str, inventory: &Inventory) -> Result<(), Box<dyn Error>>;
+}
+
+impl InventoryStore for mysql::Pool {
+    fn find_inventory(&self, user_id: &str) -> Result<Inventory, Box<dyn Error>> {
+        let mut conn = self.get_conn()?;
+
+        let rows: Vec<(u32, String, u16)> = conn.exec(
+            "SELECT slot, item_id, quantity FROM inventories WHERE user_id = :user_id",
+            params! { "user_id" => user_id },
+        )?;
+
+        let slots = rows
+            .into_iter()
+            .map(|(slot, item_id, quantity)| (slot as usize, Stack::new(item_id, quantity)))
+            .collect();
+
+        Ok(Inventory::from_slots(slots))
+    }
+
+    fn save_inventory(&self, user_id: &str, inventory: &Inventory) -> Result<(), Box<dyn Error>> {
+        let mut conn = self.get_conn()?;
+
+        let mut tx = conn.start_transaction(mysql::TxOpts::default())?;
+
+        write_inventory(&mut tx, user_id, inventory)?;
+
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+/**
+ * Replace the stored slots of a user within a transaction.
+ */
+pub(super) fn write_inventory(
+    tx: &mut mysql::Transaction,
+    user_id: &str,
+    inventory: &Inventory,
+) -> Result<(), Box<dyn Error>> {
+    tx.exec_drop(
+        "DELETE FROM inventories WHERE user_id = :user_id",
+        params! { "user_id" => user_id },
+    )?;
+
+    tx.exec_batch(
+        "INSERT INTO inventories (user_id, slot, item_id, quantity) VALUES (:user_id, :slot, :item_id, :quantity)",
+        inventory
+            .slots()
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, stack)| {
+                stack.as_ref().map(|stack| {
+                    params! {
+                        "user_id" => user_id,
+                        "slot" => slot as u32,
+                        "item_id" => &stack.item_id,
+                        "quantity" => stack.quantity,
+                    }
+                })
+            }),
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use std::error::Error;
+
+use mysql::{params, prelude::*};
+
+pub trait LocationStore {
+    fn find_map_id(&self, user_id: &str) -> Result<Option<String>, Box<dyn Error>>;
+}
+
+impl LocationStore for mysql::Pool {
+    fn find_map_id(&self, user_id: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let mut conn = self.get_conn()?;
+
+        let map_id = conn.exec_first(
+            "SELECT map_id FROM locations WHERE id = :id",
+            params! { "id" => user_id },
+        )?;
+
+        Ok(map_id)
+    }
+}
@@ -0,0 +1,297 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    sync::Mutex,
+};
+
+use crate::{
+    item::{Inventory, Wallet},
+    map::Profile,
+    quest::{Journal, Progress},
+};
+
+use super::{
+    ActorState, FriendStore, InventoryStore, LocationStore, ObjectStore, ProfileStore, QuestStore,
+    StateStore, TradeStore, UserStore, WalletStore,
+};
+
+/**
+ * Storage that lives in the process only.
+ *
+ * Used where a database isn't available, such as tests.
+ */
+#[derive(Default)]
+pub struct Memory {
+    users: Mutex<HashSet<String>>,
+    locations: Mutex<HashMap<String, String>>,
+    inventories: Mutex<HashMap<String, Inventory>>,
+    friends: Mutex<HashSet<(String, String)>>,
+    friend_requests: Mutex<HashSet<(String, String)>>,
+    wallets: Mutex<HashMap<String, Wallet>>,
+    quests: Mutex<HashMap<String, Vec<(String, Progress)>>>,
+    objects: Mutex<HashMap<(String, String), bool>>,
+    profiles: Mutex<HashMap<String, Profile>>,
+    states: Mutex<HashMap<String, ActorState>>,
+    unreadable_wallets: Mutex<HashSet<String>>,
+}
+
+impl Memory {
+    pub fn new() -> Self {
+        Memory::default()
+    }
+
+    pub fn add_user(&self, id: &str) {
+        self.users.lock().unwrap().insert(id.to_string());
+    }
+
+    pub fn set_wallet(&self, user_id: &str, wallet: Wallet) {
+        self.wallets
+            .lock()
+            .unwrap()
+            .insert(user_id.to_string(), wallet);
+    }
+
+    pub fn set_inventory(&self, user_id: &str, inventory: Inventory) {
+        self.inventories
+            .lock()
+            .unwrap()
+            .insert(user_id.to_string(), inventory);
+    }
+
+    pub fn set_profile(&self, user_id: &str, profile: Profile) {
+        self.profiles
+            .lock()
+            .unwrap()
+            .insert(user_id.to_string(), profile);
+    }
+
+    /**
+     * Make every later lookup of the user's wallet fail, as a database that
+     * went away would.
+     */
+    pub fn break_wallet(&self, user_id: &str) {
+        self.unreadable_wallets
+            .lock()
+            .unwrap()
+            .insert(user_id.to_string());
+    }
+
+    pub fn set_map_id(&self, user_id: &str, map_id: &str) {
+        self.locations
+            .lock()
+            .unwrap()
+            .insert(user_id.to_string(), map_id.to_string());
+    }
+}
+
+impl UserStore for Memory {
+    fn has_user(&self, id: &str) -> Result<bool, Box<dyn Error>> {
+        Ok(self.users.lock().unwrap().contains(id))
+    }
+}
+
+impl LocationStore for Memory {
+    fn find_map_id(&self, user_id: &str) -> Result<Option<String>, Box<dyn Error>> {
+        Ok(self.locations.lock().unwrap().get(user_id).cloned())
+    }
+}
+
+impl InventoryStore for Memory {
+    fn find_inventory(&self, user_id: &str) -> Result<Inventory, Box<dyn Error>> {
+        Ok(self
+            .inventories
+            .lock()
+            .unwrap()
+            .get(user_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn save_inventory(&self, user_id: &str, inventory: &Inventory) -> Result<(), Box<dyn Error>> {
+        self.inventories
+            .lock()
+            .unwrap()
+            .insert(user_id.to_string(), inventory.clone());
+
+        Ok(())
+    }
+}
+
+impl FriendStore for Memory {
+    fn find_friends(&self, user_id: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        Ok(self
+            .friends
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(user, _)| user == user_id)
+            .map(|(_, friend)| friend.to_owned())
+            .collect())
+    }
+
+    fn find_friend_requests(&self, user_id: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        Ok(self
+            .friend_requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, to)| to == user_id)
+            .map(|(from, _)| from.to_owned())
+            .collect())
+    }
+
+    fn add_friend_request(&self, from: &str, to: &str) -> Result<(), Box<dyn Error>> {
+        self.friend_requests
+            .lock()
+            .unwrap()
+            .insert((from.to_string(), to.to_string()));
+
+        Ok(())
+    }
+
+    fn accept_friend_request(&self, user_id: &str, from: &str) -> Result<bool, Box<dyn Error>> {
+        let request = (from.to_string(), user_id.to_string());
+
+        if !self.friend_requests.lock().unwrap().remove(&request) {
+            return Ok(false);
+        }
+
+        let mut friends = self.friends.lock().unwrap();
+
+        friends.insert((user_id.to_string(), from.to_string()));
+
+        friends.insert((from.to_string(), user_id.to_string()));
+
+        Ok(true)
+    }
+}
+
+impl WalletStore for Memory {
+    fn find_wallet(&self, user_id: &str) -> Result<Wallet, Box<dyn Error>> {
+        if self.unreadable_wallets.lock().unwrap().contains(user_id) {
+            return Err(format!("wallet of {} can't be read", user_id).into());
+        }
+
+        Ok(self
+            .wallets
+            .lock()
+            .unwrap()
+            .get(user_id)
+            .copied()
+            .unwrap_or_default())
+    }
+
+    fn save_wallet(&self, user_id: &str, wallet: &Wallet) -> Result<(), Box<dyn Error>> {
+        self.set_wallet(user_id, *wallet);
+
+        Ok(())
+    }
+}
+
+impl TradeStore for Memory {
+    fn save_exchange(&self, sides: &[(&str, &Inventory, &Wallet)]) -> Result<(), Box<dyn Error>> {
+        let mut inventories = self.inventories.lock().unwrap();
+
+        let mut wallets = self.wallets.lock().unwrap();
+
+        for (user_id, inventory, wallet) in sides {
+            inventories.insert(user_id.to_string(), (*inventory).clone());
+
+            wallets.insert(user_id.to_string(), **wallet);
+        }
+
+        Ok(())
+    }
+}
+
+impl QuestStore for Memory {
+    fn find_journal(&self, user_id: &str) -> Result<Journal, Box<dyn Error>> {
+        let quests = self
+            .quests
+            .lock()
+            .unwrap()
+            .get(user_id)
+            .cloned()
+            .unwrap_or_default();
+
+        Ok(Journal::from_quests(quests))
+    }
+
+    fn save_quest(
+        &self,
+        user_id: &str,
+        quest_id: &str,
+        progress: &Progress,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut quests = self.quests.lock().unwrap();
+
+        let quests = quests.entry(user_id.to_string()).or_default();
+
+        quests.retain(|(id, _)| id != quest_id);
+
+        quests.push((quest_id.to_string(), progress.clone()));
+
+        Ok(())
+    }
+}
+
+impl ObjectStore for Memory {
+    fn find_object_states(&self, map_id: &str) -> Result<HashMap<String, bool>, Box<dyn Error>> {
+        Ok(self
+            .objects
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|((map, _), _)| map == map_id)
+            .map(|((_, object_id), open)| (object_id.to_owned(), *open))
+            .collect())
+    }
+
+    fn save_object_state(
+        &self,
+        map_id: &str,
+        object_id: &str,
+        open: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        self.objects
+            .lock()
+            .unwrap()
+            .insert((map_id.to_string(), object_id.to_string()), open);
+
+        Ok(())
+    }
+}
+
+impl ProfileStore for Memory {
+    fn find_profile(&self, user_id: &str) -> Result<Profile, Box<dyn Error>> {
+        Ok(self
+            .profiles
+            .lock()
+            .unwrap()
+            .get(user_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn save_profile(&self, user_id: &str, profile: &Profile) -> Result<(), Box<dyn Error>> {
+        self.set_profile(user_id, profile.clone());
+
+        Ok(())
+    }
+}
+
+impl StateStore for Memory {
+    fn find_state(&self, user_id: &str) -> Result<Option<ActorState>, Box<dyn Error>> {
+        Ok(self.states.lock().unwrap().get(user_id).cloned())
+    }
+
+    fn save_states(&self, states: &[ActorState]) -> Result<(), Box<dyn Error>> {
+        let mut saved = self.states.lock().unwrap();
+
+        for state in states {
+            saved.insert(state.user_id.to_owned(), state.clone());
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,49 @@
 use std::error::Error;
 
+mod user;
+
+pub use user::UserStore;
+
+mod location;
+
+pub use location::LocationStore;
+
+mod inventory;
+
+pub use inventory::InventoryStore;
+
+mod friend;
+
+pub use friend::FriendStore;
+
+mod wallet;
+
+pub use wallet::WalletStore;
+
+mod trade;
+
+pub use trade::TradeStore;
+
+mod quest;
+
+pub use quest::QuestStore;
+
+mod object;
+
+pub use object::ObjectStore;
+
+mod profile;
+
+pub use profile::ProfileStore;
+
+mod state;
+
+pub use state::{ActorState, StateStore};
+
+mod memory;
+
+pub use memory::Memory;
+
 pub trait DB {
     fn init() -> Result<mysql::Pool, Box<dyn Error>>;
 }
@@ -15,3 +59,40 @@ impl DB for mysql::Pool {
         Ok(pool)
     }
 }
+
+/**
+ * Everything the workers persist.
+ *
+ * Implemented by any backend that implements every store.
+ */
+pub trait Storage:
+    UserStore
+    + LocationStore
+    + InventoryStore
+    + FriendStore
+    + WalletStore
+    + TradeStore
+    + QuestStore
+    + ObjectStore
+    + ProfileStore
+    + StateStore
+    + Send
+    + Sync
+{
+}
+
+impl<T> Storage for T where
+    T: UserStore
+        + LocationStore
+        + InventoryStore
+        + FriendStore
+        + WalletStore
+        + TradeStore
+        + QuestStore
+        + ObjectStore
+        + ProfileStore
+        + StateStore
+        + Send
+        + Sync
+{
+}
@@ -0,0 +1,46 @@
+use std::{collections::HashMap, error::Error};
+
+use mysql::{params, prelude::*};
+
+pub trait ObjectStore {
+    /**
+     * Find whether each saved object of a map is open.
+     */
+    fn find_object_states(&self, map_id: &str) -> Result<HashMap<String, bool>, Box<dyn Error>>;
+
+    fn save_object_state(
+        &self,
+        map_id: &str,
+        object_id: &str,
+        open: bool,
+    ) -> Result<(), Box<dyn Error>>;
+}
+
+impl ObjectStore for mysql::Pool {
+    fn find_object_states(&self, map_id: &str) -> Result<HashMap<String, bool>, Box<dyn Error>> {
+        let mut conn = self.get_conn()?;
+
+        let states: Vec<(String, bool)> = conn.exec(
+            "SELECT object_id, open FROM objects WHERE map_id = :map_id",
+            params! { "map_id" => map_id },
+        )?;
+
+        Ok(states.into_iter().collect())
+    }
+
+    fn save_object_state(
+        &self,
+        map_id: &str,
+        object_id: &str,
+        open: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut conn = self.get_conn()?;
+
+        conn.exec_drop(
+            "INSERT INTO objects (map_id, object_id, open) VALUES (:map_id, :object_id, :open) ON DUPLICATE KEY UPDATE open = :open",
+            params! { "map_id" => map_id, "object_id" => object_id, "open" => open },
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use std::error::Error;
+
+use mysql::{params, prelude::*};
+
+use crate::map::Profile;
+
+pub trait ProfileStore {
+    /**
+     * Find how a user is shown, falling back to a default profile.
+     */
+    fn find_profile(&self, user_id: &str) -> Result<Profile, Box<dyn Error>>;
+
+    fn save_profile(&self, user_id: &str, profile: &Profile) -> Result<(), Box<dyn Error>>;
+}
+
+impl ProfileStore for mysql::Pool {
+    fn find_profile(&self, user_id: &str) -> Result<Profile, Box<dyn Error>> {
+        let mut conn = self.get_conn()?;
+
+        let profile: Option<(String, String, u16)> = conn.exec_first(
+            "SELECT name, appearance, level FROM profiles WHERE user_id = :user_id",
+            params! { "user_id" => user_id },
+        )?;
+
+        Ok(profile
+            .map(|(name, appearance, level)| Profile::new(name, appearance, level))
+            .unwrap_or_default())
+    }
+
+    fn save_profile(&self, user_id: &str, profile: &Profile) -> Result<(), Box<dyn Error>> {
+        let mut conn = self.get_conn()?;
+
+        conn.exec_drop(
+            "INSERT INTO profiles (user_id, name, appearance, level) VALUES (:user_id, :name, :appearance, :level) ON DUPLICATE KEY UPDATE name = :name, appearance = :appearance, level = :level",
+            params! {
+                "user_id" => user_id,
+                "name" => &profile.name,
+                "appearance" => &profile.appearance,
+                "level" => profile.level,
+            },
+        )?;
+
+        Ok(())
+    }
+}
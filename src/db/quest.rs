@@ -0,0 +1,50 @@
+use std::error::Error;
+
+use mysql::{params, prelude::*};
+
+use crate::quest::{Journal, Progress};
+
+pub trait QuestStore {
+    fn find_journal(&self, user_id: &str) -> Result<Journal, Box<dyn Error>>;
+
+    fn save_quest(
+        &self,
+        user_id: &str,
+        quest_id: &str,
+        progress: &Progress,
+    ) -> Result<(), Box<dyn Error>>;
+}
+
+impl QuestStore for mysql::Pool {
+    fn find_journal(&self, user_id: &str) -> Result<Journal, Box<dyn Error>> {
+        let mut conn = self.get_conn()?;
+
+        let rows: Vec<(String, String)> = conn.exec(
+            "SELECT quest_id, progress FROM quests WHERE user_id = :user_id",
+            params! { "user_id" => user_id },
+        )?;
+
+        let quests = rows
+            .into_iter()
+            .map(|(quest_id, progress)| Ok((quest_id, progress.parse()?)))
+            .collect::<Result<_, Box<dyn Error>>>()?;
+
+        Ok(Journal::from_quests(quests))
+    }
+
+    fn save_quest(
+        &self,
+        user_id: &str,
+        quest_id: &str,
+        progress: &Progress,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut conn = self.get_conn()?;
+
+        conn.exec_drop(
+            "INSERT INTO quests (user_id, quest_id, progress) VALUES (:user_id, :quest_id, :progress) ON DUPLICATE KEY UPDATE progress = :progress",
+            params! { "user_id" => user_id, "quest_id" => quest_id, "progress" => progress.to_string() },
+        )?;
+
+        Ok(())
+    }
+}
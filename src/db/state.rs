@@ -0,0 +1,70 @@
+use std::error::Error;
+
+use east_online_core::model::Vector3;
+use mysql::{params, prelude::*};
+
+/**
+ * Where a player stood and how it fared when last saved.
+ *
+ * Inventories and wallets aren't part of it. They are written the moment
+ * they change, since trades write both sides in one transaction and an
+ * older batch landing afterwards would undo the exchange.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActorState {
+    pub user_id: String,
+    pub map_id: String,
+    pub position: Vector3,
+    pub health: u32,
+}
+
+pub trait StateStore {
+    fn find_state(&self, user_id: &str) -> Result<Option<ActorState>, Box<dyn Error>>;
+
+    /**
+     * Write the states of many players at once.
+     */
+    fn save_states(&self, states: &[ActorState]) -> Result<(), Box<dyn Error>>;
+}
+
+impl StateStore for mysql::Pool {
+    fn find_state(&self, user_id: &str) -> Result<Option<ActorState>, Box<dyn Error>> {
+        let mut conn = self.get_conn()?;
+
+        let state: Option<(String, i32, i32, i32, u32)> = conn.exec_first(
+            "SELECT map_id, x, y, z, health FROM actor_states WHERE user_id = :user_id",
+            params! { "user_id" => user_id },
+        )?;
+
+        Ok(state.map(|(map_id, x, y, z, health)| ActorState {
+            user_id: user_id.to_string(),
+            map_id,
+            position: Vector3 { x, y, z },
+            health,
+        }))
+    }
+
+    fn save_states(&self, states: &[ActorState]) -> Result<(), Box<dyn Error>> {
+        let mut conn = self.get_conn()?;
+
+        let mut tx = conn.start_transaction(mysql::TxOpts::default())?;
+
+        tx.exec_batch(
+            "INSERT INTO actor_states (user_id, map_id, x, y, z, health) VALUES (:user_id, :map_id, :x, :y, :z, :health) ON DUPLICATE KEY UPDATE map_id = :map_id, x = :x, y = :y, z = :z, health = :health",
+            states.iter().map(|state| {
+                params! {
+                    "user_id" => &state.user_id,
+                    "map_id" => &state.map_id,
+                    "x" => state.position.x,
+                    "y" => state.position.y,
+                    "z" => state.position.z,
+                    "health" => state.health,
+                }
+            }),
+        )?;
+
+        tx.commit()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+use std::error::Error;
+
+use crate::item::{Inventory, Wallet};
+
+use super::{inventory::write_inventory, wallet::write_wallet};
+
+pub trait TradeStore {
+    /**
+     * Save what every side of a trade ends up with, all or nothing.
+     */
+    fn save_exchange(&self, sides: &[(&str, &Inventory, &Wallet)]) -> Result<(), Box<dyn Error>>;
+}
+
+impl TradeStore for mysql::Pool {
+    fn save_exchange(&self, sides: &[(&str, &Inventory, &Wallet)]) -> Result<(), Box<dyn Error>> {
+        let mut conn = self.get_conn()?;
+
+        let mut tx = conn.start_transaction(mysql::TxOpts::default())?;
+
+        for (user_id, inventory, wallet) in sides {
+            write_inventory(&mut tx, user_id, inventory)?;
+
+            write_wallet(&mut tx, user_id, wallet)?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,20 @@
+use std::error::Error;
+
+use mysql::{params, prelude::*};
+
+pub trait UserStore {
+    fn has_user(&self, id: &str) -> Result<bool, Box<dyn Error>>;
+}
+
+impl UserStore for mysql::Pool {
+    fn has_user(&self, id: &str) -> Result<bool, Box<dyn Error>> {
+        let mut conn = self.get_conn()?;
+
+        let user_id: Option<String> = conn.exec_first(
+            "SELECT id FROM users WHERE id = :id",
+            params! { "id" => id },
+        )?;
+
+        Ok(user_id.is_some())
+    }
+}
@@ -0,0 +1,52 @@
+use std::error::Error;
+
+use mysql::{params, prelude::*};
+
+use crate::item::Wallet;
+
+pub trait WalletStore {
+    fn find_wallet(&self, user_id: &str) -> Result<Wallet, Box<dyn Error>>;
+
+    fn save_wallet(&self, user_id: &str, wallet: &Wallet) -> Result<(), Box<dyn Error>>;
+}
+
+impl WalletStore for mysql::Pool {
+    fn find_wallet(&self, user_id: &str) -> Result<Wallet, Box<dyn Error>> {
+        let mut conn = self.get_conn()?;
+
+        let coins: Option<u64> = conn.exec_first(
+            "SELECT coins FROM wallets WHERE user_id = :user_id",
+            params! { "user_id" => user_id },
+        )?;
+
+        Ok(Wallet::new(coins.unwrap_or_default()))
+    }
+
+    fn save_wallet(&self, user_id: &str, wallet: &Wallet) -> Result<(), Box<dyn Error>> {
+        let mut conn = self.get_conn()?;
+
+        let mut tx = conn.start_transaction(mysql::TxOpts::default())?;
+
+        write_wallet(&mut tx, user_id, wallet)?;
+
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+/**
+ * Store the coins of a user within a transaction.
+ */
+pub(super) fn write_wallet(
+    tx: &mut mysql::Transaction,
+    user_id: &str,
+    wallet: &Wallet,
+) -> Result<(), Box<dyn Error>> {
+    tx.exec_drop(
+        "INSERT INTO wallets (user_id, coins) VALUES (:user_id, :coins) ON DUPLICATE KEY UPDATE coins = :coins",
+        params! { "user_id" => user_id, "coins" => wallet.coins },
+    )?;
+
+    Ok(())
+}
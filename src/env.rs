@@ -4,6 +4,28 @@ pub const CDN_ORIGIN: &str = "CDN_ORIGIN";
 
 pub const API_ORIGIN: &str = "API_ORIGIN";
 
+pub const AUTH_PROVIDER: &str = "AUTH_PROVIDER";
+
+pub const WEBSOCKET_ADDRESS: &str = "WEBSOCKET_ADDRESS";
+
+pub const ADMIN_SOCKET: &str = "ADMIN_SOCKET";
+
+pub const MAX_STREAMS: &str = "MAX_STREAMS";
+
+pub const MAX_PLAYERS: &str = "MAX_PLAYERS";
+
+pub const MAX_MAP_PLAYERS: &str = "MAX_MAP_PLAYERS";
+
+pub const INSTANCE_MAPS: &str = "INSTANCE_MAPS";
+
+pub const INSTANCE_IDLE_SECONDS: &str = "INSTANCE_IDLE_SECONDS";
+
+pub const RATE_LIMIT_ACTION: &str = "RATE_LIMIT_ACTION";
+
+pub const TLS_CERT_PATH: &str = "TLS_CERT_PATH";
+
+pub const TLS_KEY_PATH: &str = "TLS_KEY_PATH";
+
 pub fn init() {
     dotenv().ok();
 }
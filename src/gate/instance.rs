@@ -0,0 +1,212 @@
+use std::{collections::HashMap, error::Error, sync::Arc};
+
+use east_online_core::model::Vector3;
+use tokio::{sync::mpsc, time};
+
+use crate::{
+    admin::Console,
+    db::Storage,
+    item,
+    limit::Limits,
+    map::{self, ObjectDefinition, Template},
+    net::{protocol::Capabilities, Stream},
+    party::Parties,
+    presence::Directory,
+    quest,
+};
+
+type Channel = (
+    mpsc::Sender<(Stream, String, Option<Vector3>, Capabilities)>,
+    mpsc::UnboundedReceiver<map::Event>,
+);
+
+/**
+ * Map workers made from templates while the server runs, such as dungeons.
+ *
+ * An instance is closed once nobody has been on it for the idle timeout.
+ */
+pub struct Instances {
+    templates: HashMap<String, Template>,
+    items: Arc<item::Registry>,
+    db: Arc<dyn Storage>,
+    limits: Limits,
+    idle_timeout: time::Duration,
+    console: Option<Console>,
+    parties: Option<Arc<Parties>>,
+    directory: Option<Arc<Directory>>,
+    quests: Option<Arc<quest::Registry>>,
+    next_id: usize,
+    empty_since: HashMap<String, time::Instant>,
+}
+
+impl Instances {
+    pub fn new(items: Arc<item::Registry>, db: Arc<dyn Storage>) -> Self {
+        Instances {
+            templates: HashMap::new(),
+            items,
+            db,
+            limits: Limits::default(),
+            idle_timeout: time::Duration::from_secs(300),
+            console: None,
+            parties: None,
+            directory: None,
+            quests: None,
+            next_id: 0,
+            empty_since: HashMap::new(),
+        }
+    }
+
+    pub fn add_template(&mut self, template: Template) {
+        self.templates.insert(template.id.to_owned(), template);
+    }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    pub fn set_idle_timeout(&mut self, idle_timeout: time::Duration) {
+        self.idle_timeout = idle_timeout;
+    }
+
+    /**
+     * List instances on a console while they are running.
+     */
+    pub fn set_console(&mut self, console: Console) {
+        self.console = Some(console);
+    }
+
+    pub fn set_parties(&mut self, parties: Arc<Parties>) {
+        self.parties = Some(parties);
+    }
+
+    pub fn set_directory(&mut self, directory: Arc<Directory>) {
+        self.directory = Some(directory);
+    }
+
+    pub fn set_quests(&mut self, quests: Arc<quest::Registry>) {
+        self.quests = Some(quests);
+    }
+
+    pub fn idle_timeout(&self) -> time::Duration {
+        self.idle_timeout
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.empty_since.contains_key(id)
+    }
+
+    /**
+     * Start a worker for a new instance of a template.
+     *
+     * Objects of an instance are never persisted, as instance ids start over
+     * with the server and would pick up what an older instance left behind.
+     *
+     * Return the id of the instance with the channel to its worker.
+     */
+    pub fn spawn(
+        &mut self,
+        template_id: &str,
+        now: time::Instant,
+    ) -> Result<(String, Channel), Box<dyn Error>> {
+        let template = self
+            .templates
+            .get(template_id)
+            .ok_or(format!("template {} not found", template_id))?;
+
+        let id = format!("{}#{}", template.id, self.next_id);
+
+        self.next_id = self.next_id.wrapping_add(1);
+
+        let (enter_tx, enter_rx) = mpsc::channel(16);
+
+        let (exit_tx, exit_rx) = mpsc::unbounded_channel();
+
+        let mut worker = map::Worker::new(
+            id.to_owned(),
+            template.name.to_owned(),
+            template.tiles(),
+            self.items.clone(),
+            self.db.clone(),
+            (exit_tx, enter_rx),
+        );
+
+        worker.set_limits(self.limits.clone());
+
+        worker.set_model_id(template.id.to_owned());
+
+        for shop in template.shops() {
+            worker.add_shop(shop.to_owned())?;
+        }
+
+        let objects = template
+            .objects()
+            .iter()
+            .map(|definition| ObjectDefinition {
+                persistent: false,
+                ..definition.to_owned()
+            })
+            .collect();
+
+        worker.add_objects(objects)?;
+
+        worker.add_npcs(template.npcs().to_owned())?;
+
+        if let Some(parties) = &self.parties {
+            worker.set_parties(parties.clone());
+        }
+
+        if let Some(directory) = &self.directory {
+            worker.set_directory(directory.clone());
+        }
+
+        if let Some(quests) = &self.quests {
+            worker.set_quests(quests.clone());
+        }
+
+        if let Some(console) = &self.console {
+            console.add_map(&id, worker.control());
+        }
+
+        let key = id.to_owned();
+
+        tokio::spawn(async move {
+            if let Err(e) = worker.run().await {
+                eprintln!("{} worker died for {e}", key);
+            }
+        });
+
+        println!("create instance, {}", id);
+
+        self.empty_since.insert(id.to_owned(), now);
+
+        Ok((id, (enter_tx, exit_rx)))
+    }
+
+    /**
+     * Remember when the last player left an instance.
+     */
+    pub fn empty(&mut self, id: &str, now: time::Instant) {
+        if let Some(since) = self.empty_since.get_mut(id) {
+            *since = now;
+        }
+    }
+
+    /**
+     * Check if an instance has been left alone for long enough to close.
+     */
+    pub fn is_expired(&self, id: &str, now: time::Instant) -> bool {
+        self.empty_since
+            .get(id)
+            .is_some_and(|since| now >= *since + self.idle_timeout)
+    }
+
+    pub fn close(&mut self, id: &str) {
+        self.empty_since.remove(id);
+
+        if let Some(console) = &self.console {
+            console.remove_map(id);
+        }
+
+        println!("close instance, {}", id);
+    }
+}
@@ -1,15 +1,24 @@
-use tokio::net::TcpStream;
+use east_online_core::model::Vector3;
 
-use crate::net::packet;
+use crate::{
+    map,
+    net::{packet, protocol::Capabilities, Stream},
+};
 
 pub enum Job {
-    Accept(TcpStream),
+    Accept(Stream),
     Drop(usize, String),
     Readable(usize),
     Incoming(usize, packet::Incoming),
     Send {
-        index: usize,
+        id: usize,
         user_id: String,
         map_id: String,
+        position: Option<Vector3>,
+        capabilities: Capabilities,
     },
+    Event(String, map::Event),
+    Resume(usize),
+    Expire(String),
+    Prune,
 }
@@ -1,5 +1,13 @@
 mod job;
 
+mod instance;
+
+pub use instance::Instances;
+
+mod queue;
+
+pub use queue::Capacity;
+
 mod worker;
 
 pub use worker::Worker;
@@ -0,0 +1,194 @@
+use std::collections::{HashMap, VecDeque};
+
+use east_online_core::model::Vector3;
+
+use crate::net::{packet, protocol::Capabilities};
+
+/**
+ * How many streams and players the server takes at once.
+ */
+#[derive(Debug, Clone)]
+pub struct Capacity {
+    /**
+     * Streams the gate holds before they are sent to a map.
+     */
+    pub streams: usize,
+    pub players: usize,
+    pub map_players: usize,
+}
+
+impl Default for Capacity {
+    fn default() -> Self {
+        Capacity {
+            streams: 1024,
+            players: 1000,
+            map_players: 300,
+        }
+    }
+}
+
+/**
+ * An authenticated player waiting to be sent to a map.
+ */
+pub struct Ticket {
+    pub id: usize,
+    pub user_id: String,
+    pub map_id: String,
+    pub capabilities: Capabilities,
+    /**
+     * Where the player was saved, to enter there instead of the spawn point.
+     */
+    pub restored: Option<Vector3>,
+    position: Option<usize>,
+}
+
+impl Ticket {
+    pub fn new(id: usize, user_id: String, map_id: String, capabilities: Capabilities) -> Self {
+        Ticket {
+            id,
+            user_id,
+            map_id,
+            capabilities,
+            restored: None,
+            position: None,
+        }
+    }
+
+    pub fn at(mut self, position: Option<Vector3>) -> Self {
+        self.restored = position;
+
+        self
+    }
+}
+
+/**
+ * Players on the maps and the ones waiting for a slot, in arrival order.
+ */
+#[derive(Default)]
+pub struct Queue {
+    capacity: Capacity,
+    tickets: VecDeque<Ticket>,
+    players: HashMap<String, usize>,
+}
+
+impl Queue {
+    pub fn new(capacity: Capacity) -> Self {
+        Queue {
+            capacity,
+            ..Default::default()
+        }
+    }
+
+    pub fn capacity(&self) -> &Capacity {
+        &self.capacity
+    }
+
+    pub fn push(&mut self, ticket: Ticket) {
+        self.tickets.push_back(ticket);
+    }
+
+    pub fn contains(&self, id: usize) -> bool {
+        self.tickets.iter().any(|ticket| ticket.id == id)
+    }
+
+    /**
+     * Remove a waiting player, returning whether it was there.
+     */
+    pub fn remove(&mut self, id: usize) -> bool {
+        let len = self.tickets.len();
+
+        self.tickets.retain(|ticket| ticket.id != id);
+
+        self.tickets.len() != len
+    }
+
+    /**
+     * Free the slot of a player who left a map.
+     */
+    pub fn leave(&mut self, map_id: &str) {
+        if let Some(count) = self.players.get_mut(map_id) {
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    /**
+     * Move the slot of a player from one map to another.
+     *
+     * The player keeps its slot on the way, so nobody waiting can take it.
+     */
+    pub fn transfer(&mut self, from: &str, to: &str) {
+        self.leave(from);
+
+        *self.players.entry(to.to_owned()).or_default() += 1;
+    }
+
+    /**
+     * Check if nobody is on a map or waiting for it.
+     */
+    pub fn is_idle(&self, map_id: &str) -> bool {
+        self.players.get(map_id).copied().unwrap_or_default() == 0
+            && !self.tickets.iter().any(|ticket| ticket.map_id == map_id)
+    }
+
+    /**
+     * Stop counting the players of a map that is gone.
+     */
+    pub fn forget(&mut self, map_id: &str) {
+        self.players.remove(map_id);
+    }
+
+    /**
+     * Take the waiting players that fit, taking their slots.
+     *
+     * Players stay in order, but one waiting for a full map doesn't hold
+     * back the ones behind it.
+     */
+    pub fn admit(&mut self) -> Vec<Ticket> {
+        let mut admitted = Vec::new();
+
+        let mut waiting = VecDeque::new();
+
+        while let Some(ticket) = self.tickets.pop_front() {
+            let total: usize = self.players.values().sum();
+
+            let count = self.players.entry(ticket.map_id.to_owned()).or_default();
+
+            if total < self.capacity.players && *count < self.capacity.map_players {
+                *count += 1;
+
+                admitted.push(ticket);
+            } else {
+                waiting.push_back(ticket);
+            }
+        }
+
+        self.tickets = waiting;
+
+        admitted
+    }
+
+    /**
+     * Get the queue packets for the players whose position changed.
+     */
+    pub fn moved(&mut self) -> Vec<(usize, packet::Outgoing)> {
+        let mut packets = Vec::new();
+
+        for (index, ticket) in self.tickets.iter_mut().enumerate() {
+            if ticket.position == Some(index) {
+                continue;
+            }
+
+            ticket.position = Some(index);
+
+            let packet = packet::Outgoing::Queue {
+                position: u32::try_from(index + 1).unwrap_or(u32::MAX),
+            };
+
+            if ticket.capabilities.supports(&packet) {
+                packets.push((ticket.id, packet));
+            }
+        }
+
+        packets
+    }
+}
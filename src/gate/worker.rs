@@ -1,46 +1,75 @@
-use east_online_core::model::{self, Vector3};
-use mysql::{params, prelude::*};
-use reqwest::{header::AUTHORIZATION, StatusCode};
+use east_online_core::model::Vector3;
+use futures::future::select_all;
 use std::{
-    collections::{BinaryHeap, HashMap},
+    collections::{BinaryHeap, HashMap, HashSet},
     error::Error,
     io,
+    net::IpAddr,
     sync::Arc,
 };
-use tokio::{
-    net::{TcpListener, TcpStream},
-    sync::mpsc,
-};
+use tokio::{sync::mpsc, time};
 
 use crate::{
-    env::{url, API_ORIGIN},
-    net::{io::Reader, packet},
+    auth::Authenticator,
+    db::Storage,
+    limit::{Bucket, Limiter, Limits, Verdict},
+    map::Event,
+    net::{
+        io::{get_packet_buf, Reader},
+        packet,
+        protocol::{self, Capabilities},
+        Listener, Stream,
+    },
     schedule::Schedule,
     selector::{ScheduleQueue, Waitings},
 };
 
-use super::job::Job;
+use super::{
+    instance::Instances,
+    job::Job,
+    queue::{Capacity, Queue, Ticket},
+};
+
+type Receiver = mpsc::UnboundedReceiver<Event>;
 
-type Receiver = mpsc::Receiver<(TcpStream, String, Vector3)>;
+type Sender = mpsc::Sender<(Stream, String, Option<Vector3>, Capabilities)>;
 
-type Sender = mpsc::Sender<(TcpStream, String, Vector3)>;
+const PRUNE_INTERVAL: time::Duration = time::Duration::from_secs(60);
 
 pub struct Worker {
-    listener: TcpListener,
-    streams: Vec<TcpStream>,
+    listener: Listener,
+    streams: HashMap<usize, Stream>,
+    next_id: usize,
     schedule_queue: BinaryHeap<Schedule<Job>>,
-    db: Arc<mysql::Pool>,
+    db: Arc<dyn Storage>,
+    auth: Box<dyn Authenticator>,
     channels: HashMap<String, (Sender, Receiver)>,
+    limits: Limits,
+    limiters: HashMap<usize, Limiter>,
+    paused: HashSet<usize>,
+    connections: HashMap<IpAddr, Bucket>,
+    logins: HashMap<IpAddr, Bucket>,
+    queue: Queue,
+    instances: Option<Instances>,
 }
 
 impl Worker {
-    pub fn new(db: Arc<mysql::Pool>, listener: TcpListener) -> Self {
+    pub fn new(db: Arc<dyn Storage>, auth: Box<dyn Authenticator>, listener: Listener) -> Self {
         Worker {
             listener,
-            streams: Vec::new(),
+            streams: HashMap::new(),
+            next_id: 0,
             schedule_queue: BinaryHeap::new(),
             db,
+            auth,
             channels: HashMap::new(),
+            limits: Limits::default(),
+            limiters: HashMap::new(),
+            paused: HashSet::new(),
+            connections: HashMap::new(),
+            logins: HashMap::new(),
+            queue: Queue::new(Capacity::default()),
+            instances: None,
         }
     }
 
@@ -48,7 +77,23 @@ impl Worker {
         self.channels.insert(key.to_string(), channel);
     }
 
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    pub fn set_capacity(&mut self, capacity: Capacity) {
+        self.queue = Queue::new(capacity);
+    }
+
+    pub fn set_instances(&mut self, instances: Instances) {
+        self.instances = Some(instances);
+    }
+
     pub async fn run(mut self) -> Result<(), Box<dyn Error>> {
+        let schedule = Schedule::new(Job::Prune, time::Instant::now() + PRUNE_INTERVAL);
+
+        self.schedule_queue.push(schedule);
+
         loop {
             let job = self.select_job().await;
 
@@ -64,11 +109,14 @@ impl Worker {
         }
 
         tokio::select! {
-            Ok((stream, _)) = self.listener.accept() => {
+            Some(stream) = self.listener.accept() => {
                 Job::Accept(stream)
             }
-            Ok(index) = self.streams.wait_for_readable() => {
-                Job::Readable(index)
+            Ok(id) = self.streams.wait_for_readable(&self.paused) => {
+                Job::Readable(id)
+            }
+            Some((key, event)) = recv_event(&mut self.channels) => {
+                Job::Event(key, event)
             }
             Ok(_) = self.schedule_queue.wait_for_first() => {
                 self.schedule_queue.pop().unwrap().job
@@ -84,35 +132,86 @@ impl Worker {
     async fn handle_job(&mut self, job: Job) -> Result<(), Box<dyn Error>> {
         match job {
             Job::Accept(stream) => {
-                println!("{:?} accepted by gate", stream.peer_addr()?);
+                let addr = stream.peer_addr()?;
+
+                if self.streams.len() >= self.queue.capacity().streams {
+                    println!("{:?} refused for the gate is full", addr);
+
+                    return Ok(());
+                }
+
+                let now = time::Instant::now();
+
+                let rate = self.limits.connections_per_ip;
+
+                let bucket = self
+                    .connections
+                    .entry(addr.ip())
+                    .or_insert_with(|| Bucket::new(rate, now));
+
+                if !bucket.take(now) {
+                    println!("{:?} refused for too many connections", addr);
+
+                    return Ok(());
+                }
+
+                let id = self.next_id;
+
+                self.next_id = self.next_id.wrapping_add(1);
+
+                println!("{:?} accepted by gate as {}", addr, id);
+
+                self.streams.insert(id, stream);
 
-                self.streams.push(stream);
+                self.limiters.insert(id, Limiter::new(&self.limits, now));
 
                 Ok(())
             }
-            Job::Drop(index, reason) => {
-                let stream = self.streams.remove(index);
+            Job::Drop(id, reason) => {
+                let stream = self.streams.remove(&id).ok_or("drop failed")?;
+
+                self.limiters.remove(&id);
+
+                self.paused.remove(&id);
+
+                if self.queue.remove(id) {
+                    self.admit();
+                }
 
                 println!("{:?} dropped for {}", stream.peer_addr()?, reason);
 
                 Ok(())
             }
-            Job::Readable(index) => {
-                let stream = self.streams.get(index).ok_or("stream not found")?;
+            Job::Readable(id) => {
+                let stream = self.streams.get(&id).ok_or("stream not found")?;
 
                 let schedule = match stream.try_read_packet() {
-                    Ok(packet) => Schedule::instant(Job::Incoming(index, packet)),
+                    Ok(packet) => match self.limit(id, &packet) {
+                        Verdict::Pass => Schedule::instant(Job::Incoming(id, packet)),
+                        Verdict::Drop => return Ok(()),
+                        Verdict::Throttle(wait) => {
+                            let deadline = time::Instant::now() + wait;
+
+                            self.paused.insert(id);
+
+                            self.schedule_queue
+                                .push(Schedule::new(Job::Resume(id), deadline));
+
+                            Schedule::new(Job::Incoming(id, packet), deadline)
+                        }
+                        Verdict::Disconnect(reason) => Schedule::instant(Job::Drop(id, reason)),
+                    },
                     Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(()),
-                    Err(e) => Schedule::instant(Job::Drop(index, format!("{e}"))),
+                    Err(e) => Schedule::instant(Job::Drop(id, format!("{e}"))),
                 };
 
                 self.schedule_queue.push(schedule);
 
                 Ok(())
             }
-            Job::Incoming(index, packet) => {
-                if let Err(e) = self.handle_packet(index, packet).await {
-                    let schedule = Schedule::instant(Job::Drop(index, format!("{e}")));
+            Job::Incoming(id, packet) => {
+                if let Err(e) = self.handle_packet(id, packet).await {
+                    let schedule = Schedule::instant(Job::Drop(id, format!("{e}")));
 
                     self.schedule_queue.push(schedule);
                 }
@@ -120,17 +219,138 @@ impl Worker {
                 Ok(())
             }
             Job::Send {
-                index,
+                id,
                 user_id,
                 map_id,
+                position,
+                capabilities,
             } => {
-                let stream = self.streams.remove(index);
+                self.limiters.remove(&id);
+
+                self.paused.remove(&id);
+
+                let stream = match self.streams.remove(&id) {
+                    Some(stream) => stream,
+                    None => {
+                        println!("{} closed before {} was sent", id, user_id);
+
+                        self.queue.leave(&map_id);
+
+                        self.admit();
+
+                        return Ok(());
+                    }
+                };
+
+                match self.channels.get(&map_id) {
+                    Some((sender, _)) => {
+                        sender
+                            .send((stream, user_id, position, capabilities))
+                            .await?;
 
-                if let Some((sender, _)) = self.channels.get(&map_id) {
-                    sender
-                        .send((stream, user_id, Vector3 { x: 0, y: 0, z: 0 }))
-                        .await?;
+                        Ok(())
+                    }
+                    None => {
+                        self.queue.leave(&map_id);
+
+                        self.admit();
+
+                        Err(format!("map {} not found for {}", map_id, user_id).into())
+                    }
                 }
+            }
+            Job::Event(key, Event::Left(user_id)) => {
+                println!("{} left {}", user_id, key);
+
+                self.queue.leave(&key);
+
+                self.admit();
+
+                self.expire_later(&key);
+
+                Ok(())
+            }
+            Job::Event(key, Event::Transfer { destination, users }) => {
+                let map_id = if self.channels.contains_key(&destination) {
+                    destination
+                } else {
+                    match self.spawn_instance(&destination) {
+                        Ok(map_id) => map_id,
+                        Err(e) => {
+                            eprintln!("{} sent back to {} for {e}", destination, key);
+
+                            key.to_owned()
+                        }
+                    }
+                };
+
+                let now = time::Instant::now();
+
+                for (user_id, stream, capabilities) in users {
+                    let id = self.next_id;
+
+                    self.next_id = self.next_id.wrapping_add(1);
+
+                    println!("{} transferred from {} to {}", user_id, key, map_id);
+
+                    self.streams.insert(id, stream);
+
+                    self.limiters.insert(id, Limiter::new(&self.limits, now));
+
+                    self.queue.transfer(&key, &map_id);
+
+                    let job = Job::Send {
+                        id,
+                        user_id,
+                        map_id: map_id.to_owned(),
+                        position: None,
+                        capabilities,
+                    };
+
+                    self.schedule_queue.push(Schedule::instant(job));
+                }
+
+                self.admit();
+
+                self.expire_later(&key);
+
+                Ok(())
+            }
+            Job::Resume(id) => {
+                self.paused.remove(&id);
+
+                Ok(())
+            }
+            Job::Expire(map_id) => {
+                let now = time::Instant::now();
+
+                let instances = match &mut self.instances {
+                    Some(instances) => instances,
+                    None => return Ok(()),
+                };
+
+                if !self.queue.is_idle(&map_id) || !instances.is_expired(&map_id, now) {
+                    return Ok(());
+                }
+
+                instances.close(&map_id);
+
+                self.queue.forget(&map_id);
+
+                self.channels.remove(&map_id);
+
+                Ok(())
+            }
+            Job::Prune => {
+                let now = time::Instant::now();
+
+                self.connections.retain(|_, bucket| !bucket.is_full(now));
+
+                self.logins.retain(|_, bucket| !bucket.is_full(now));
+
+                let schedule = Schedule::new(Job::Prune, now + PRUNE_INTERVAL);
+
+                self.schedule_queue.push(schedule);
 
                 Ok(())
             }
@@ -145,64 +365,207 @@ impl Worker {
      */
     async fn handle_packet(
         &mut self,
-        index: usize,
+        id: usize,
         packet: packet::Incoming,
     ) -> Result<(), Box<dyn Error>> {
         match packet {
-            packet::Incoming::Hello { token } => {
+            packet::Incoming::Hello {
+                version,
+                features,
+                token,
+            } => {
+                if self.queue.contains(id) {
+                    return Ok(());
+                }
+
+                let capabilities = match Capabilities::negotiate(version, &features) {
+                    Ok(capabilities) => capabilities,
+                    Err(message) => {
+                        let packet = packet::Outgoing::Error {
+                            code: protocol::ERROR_INCOMPATIBLE_VERSION,
+                            message: message.to_owned(),
+                        };
+
+                        self.write(id, packet)?;
+
+                        return Err(message.into());
+                    }
+                };
+
+                let addr = self
+                    .streams
+                    .get(&id)
+                    .ok_or("stream not found")?
+                    .peer_addr()?;
+
+                let now = time::Instant::now();
+
+                let rate = self.limits.logins_per_ip;
+
+                let bucket = self
+                    .logins
+                    .entry(addr.ip())
+                    .or_insert_with(|| Bucket::new(rate, now));
+
+                if !bucket.take(now) {
+                    return Err("too many login attempts".into());
+                }
+
                 println!("request with token");
 
-                let response = reqwest::Client::new()
-                    .get(url(API_ORIGIN, "auth"))
-                    .header(AUTHORIZATION, format!("Bearer {}", token))
-                    .send()
-                    .await?;
+                let user_id = self.auth.authenticate(&token).await?;
 
                 println!("response");
 
-                match response.status() {
-                    StatusCode::CREATED => {
-                        let token = response.json::<model::Token>().await?;
-
-                        let mut conn = self.db.get_conn()?;
-
-                        let user_id: String = match conn
-                            .exec_first(
-                                "SELECT id FROM users WHERE id = :id",
-                                mysql::params! { "id" => token.id },
-                            )?
-                            .map(|id| id)
-                        {
-                            Some(user_id) => user_id,
-                            None => return Err("user not found".into()),
-                        };
-
-                        let map_id: String = conn
-                            .exec_first(
-                                "SELECT map_id FROM locations WHERE id = :id",
-                                mysql::params! { "id" => user_id.clone() },
-                            )?
-                            .map(|map_id| map_id)
-                            .unwrap_or(String::from("map_0000"));
-
-                        let job = Job::Send {
-                            index,
-                            user_id,
-                            map_id,
-                        };
+                if !self.db.has_user(&user_id)? {
+                    return Err("user not found".into());
+                }
 
-                        let schedule = Schedule::instant(job);
+                let state = self
+                    .db
+                    .find_state(&user_id)?
+                    .filter(|state| self.channels.contains_key(&state.map_id));
+
+                let map_id = match &state {
+                    Some(state) => state.map_id.to_owned(),
+                    None => self
+                        .db
+                        .find_map_id(&user_id)?
+                        .unwrap_or(String::from("map_0000")),
+                };
 
-                        self.schedule_queue.push(schedule);
+                let position = state.map(|state| state.position);
 
-                        println!("send");
+                let packet = packet::Outgoing::Capabilities {
+                    version: capabilities.version,
+                    features: capabilities.features.to_owned(),
+                };
 
-                        Ok(())
-                    }
-                    _ => Err(response.text().await?.into()),
+                if capabilities.supports(&packet) {
+                    self.write(id, packet)?;
                 }
+
+                self.queue
+                    .push(Ticket::new(id, user_id, map_id, capabilities).at(position));
+
+                self.admit();
+
+                Ok(())
             }
             _ => Ok(()),
         }
     }
+
+    /**
+     * Send the players that fit to their maps and tell the rest where
+     * they are in the queue.
+     */
+    fn admit(&mut self) {
+        for ticket in self.queue.admit() {
+            let job = Job::Send {
+                id: ticket.id,
+                user_id: ticket.user_id,
+                map_id: ticket.map_id,
+                position: ticket.restored,
+                capabilities: ticket.capabilities,
+            };
+
+            self.schedule_queue.push(Schedule::instant(job));
+        }
+
+        for (id, packet) in self.queue.moved() {
+            if let Err(e) = self.write(id, packet) {
+                eprintln!("queue position of {} not written for {e}", id);
+            }
+        }
+    }
+
+    /**
+     * Start a new instance of a template and open its channel.
+     */
+    fn spawn_instance(&mut self, template: &str) -> Result<String, Box<dyn Error>> {
+        let instances = self.instances.as_mut().ok_or("instances not enabled")?;
+
+        let now = time::Instant::now();
+
+        let (map_id, channel) = instances.spawn(template, now)?;
+
+        let deadline = now + instances.idle_timeout();
+
+        self.channels.insert(map_id.to_owned(), channel);
+
+        let job = Job::Expire(map_id.to_owned());
+
+        self.schedule_queue.push(Schedule::new(job, deadline));
+
+        Ok(map_id)
+    }
+
+    /**
+     * Close an instance later if nobody is left on it.
+     */
+    fn expire_later(&mut self, map_id: &str) {
+        let instances = match &mut self.instances {
+            Some(instances) if instances.contains(map_id) => instances,
+            _ => return,
+        };
+
+        if !self.queue.is_idle(map_id) {
+            return;
+        }
+
+        let now = time::Instant::now();
+
+        instances.empty(map_id, now);
+
+        let job = Job::Expire(map_id.to_owned());
+
+        let deadline = now + instances.idle_timeout();
+
+        self.schedule_queue.push(Schedule::new(job, deadline));
+    }
+
+    fn limit(&mut self, id: usize, packet: &packet::Incoming) -> Verdict {
+        match self.limiters.get_mut(&id) {
+            Some(limiter) => limiter.check(packet.kind(), &self.limits, time::Instant::now()),
+            None => Verdict::Pass,
+        }
+    }
+
+    /**
+     * Write a packet straight to a stream that hasn't been sent yet.
+     */
+    fn write(&self, id: usize, packet: packet::Outgoing) -> Result<(), Box<dyn Error>> {
+        let stream = self.streams.get(&id).ok_or("stream not found")?;
+
+        stream.try_write(&get_packet_buf(packet)?)?;
+
+        Ok(())
+    }
+}
+
+/**
+ * Wait for the next event from any map.
+ *
+ * Maps that are gone are skipped, and `None` means every map is gone.
+ */
+async fn recv_event(channels: &mut HashMap<String, (Sender, Receiver)>) -> Option<(String, Event)> {
+    let mut waitings: Vec<_> = channels
+        .iter_mut()
+        .map(|(key, (_, receiver))| {
+            Box::pin(async move { receiver.recv().await.map(|event| (key.to_owned(), event)) })
+        })
+        .collect();
+
+    while !waitings.is_empty() {
+        let (event, _, rest) = select_all(waitings).await;
+
+        if event.is_some() {
+            return event;
+        }
+
+        waitings = rest;
+    }
+
+    None
 }
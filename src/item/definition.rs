@@ -0,0 +1,29 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+pub type Registry = HashMap<String, Definition>;
+
+#[derive(Debug, Deserialize)]
+pub struct Manifest {
+    pub items: Vec<ManifestItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ManifestItem {
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Definition {
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_max_stack")]
+    pub max_stack: u16,
+    #[serde(default)]
+    pub consumable: bool,
+}
+
+fn default_max_stack() -> u16 {
+    1
+}
@@ -0,0 +1,187 @@
+use std::error::Error;
+
+use crate::map::Component;
+
+use super::{Registry, Stack};
+
+pub const INVENTORY_SIZE: usize = 24;
+
+#[derive(Debug, Clone)]
+pub struct Inventory {
+    slots: Vec<Option<Stack>>,
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        Inventory {
+            slots: vec![None; INVENTORY_SIZE],
+        }
+    }
+
+    pub fn from_slots(slots: Vec<(usize, Stack)>) -> Self {
+        let mut inventory = Inventory::new();
+
+        for (slot, stack) in slots {
+            if let Some(entry) = inventory.slots.get_mut(slot) {
+                *entry = Some(stack);
+            }
+        }
+
+        inventory
+    }
+
+    pub fn slots(&self) -> &[Option<Stack>] {
+        &self.slots
+    }
+
+    pub fn get(&self, slot: usize) -> Option<&Stack> {
+        self.slots.get(slot).and_then(|stack| stack.as_ref())
+    }
+
+    /**
+     * Move a stack from a slot to another.
+     *
+     * Stacks of the same item are merged up to its max stack size,
+     * otherwise the two slots are swapped.
+     */
+    pub fn move_slot(
+        &mut self,
+        from: usize,
+        to: usize,
+        registry: &Registry,
+    ) -> Result<(), Box<dyn Error>> {
+        if from >= self.slots.len() || to >= self.slots.len() {
+            return Err("slot out of range".into());
+        }
+
+        if from == to {
+            return Ok(());
+        }
+
+        let source = self.slots[from].take().ok_or("empty slot")?;
+
+        let is_same_item =
+            matches!(&self.slots[to], Some(target) if target.item_id == source.item_id);
+
+        if !is_same_item {
+            self.slots[from] = self.slots[to].take();
+
+            self.slots[to] = Some(source);
+
+            return Ok(());
+        }
+
+        let max_stack = registry
+            .get(&source.item_id)
+            .map(|definition| definition.max_stack)
+            .unwrap_or(1);
+
+        let target = self.slots[to].as_mut().unwrap();
+
+        let moved = source
+            .quantity
+            .min(max_stack.saturating_sub(target.quantity));
+
+        target.quantity += moved;
+
+        if moved < source.quantity {
+            self.slots[from] = Some(Stack::new(source.item_id, source.quantity - moved));
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Take some quantity out of a slot.
+     *
+     * Throw an error if the slot doesn't hold enough of the item.
+     */
+    pub fn take(&mut self, slot: usize, quantity: u16) -> Result<Stack, Box<dyn Error>> {
+        let entry = self.slots.get_mut(slot).ok_or("slot out of range")?;
+
+        let stack = entry.as_mut().ok_or("empty slot")?;
+
+        if quantity == 0 || stack.quantity < quantity {
+            return Err("not enough quantity".into());
+        }
+
+        stack.quantity -= quantity;
+
+        let taken = Stack::new(stack.item_id.clone(), quantity);
+
+        if stack.quantity == 0 {
+            *entry = None;
+        }
+
+        Ok(taken)
+    }
+
+    /**
+     * Put a stack into the inventory.
+     *
+     * Existing stacks of the item are filled first, then empty slots.
+     * Nothing changes if the whole stack doesn't fit.
+     */
+    pub fn add(&mut self, stack: Stack, registry: &Registry) -> Result<(), Box<dyn Error>> {
+        let max_stack = registry
+            .get(&stack.item_id)
+            .ok_or("unknown item")?
+            .max_stack
+            .max(1);
+
+        if self.capacity_for(&stack.item_id, max_stack) < u32::from(stack.quantity) {
+            return Err("inventory full".into());
+        }
+
+        let mut remaining = stack.quantity;
+
+        for entry in self.slots.iter_mut().flatten() {
+            if remaining == 0 {
+                break;
+            }
+
+            if entry.item_id == stack.item_id && entry.quantity < max_stack {
+                let moved = remaining.min(max_stack - entry.quantity);
+
+                entry.quantity += moved;
+
+                remaining -= moved;
+            }
+        }
+
+        for entry in self.slots.iter_mut().filter(|entry| entry.is_none()) {
+            if remaining == 0 {
+                break;
+            }
+
+            let moved = remaining.min(max_stack);
+
+            *entry = Some(Stack::new(stack.item_id.clone(), moved));
+
+            remaining -= moved;
+        }
+
+        Ok(())
+    }
+
+    fn capacity_for(&self, item_id: &str, max_stack: u16) -> u32 {
+        self.slots
+            .iter()
+            .map(|entry| match entry {
+                Some(stack) if stack.item_id == item_id => {
+                    u32::from(max_stack.saturating_sub(stack.quantity))
+                }
+                Some(_) => 0,
+                None => u32::from(max_stack),
+            })
+            .sum()
+    }
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Inventory::new()
+    }
+}
+
+impl Component for Inventory {}
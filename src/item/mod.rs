@@ -0,0 +1,15 @@
+mod definition;
+
+pub use definition::{Definition, Manifest, Registry};
+
+mod stack;
+
+pub use stack::Stack;
+
+mod inventory;
+
+pub use inventory::Inventory;
+
+mod wallet;
+
+pub use wallet::Wallet;
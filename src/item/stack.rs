@@ -0,0 +1,13 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Stack {
+    pub item_id: String,
+    pub quantity: u16,
+}
+
+impl Stack {
+    pub fn new(item_id: String, quantity: u16) -> Self {
+        Stack { item_id, quantity }
+    }
+}
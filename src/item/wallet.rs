@@ -0,0 +1,36 @@
+use std::error::Error;
+
+use crate::map::Component;
+
+/**
+ * Currency a player carries.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Wallet {
+    pub coins: u64,
+}
+
+impl Wallet {
+    pub fn new(coins: u64) -> Self {
+        Wallet { coins }
+    }
+
+    /**
+     * Take coins out of the wallet.
+     *
+     * Throw an error if there aren't enough.
+     */
+    pub fn spend(&mut self, amount: u64) -> Result<(), Box<dyn Error>> {
+        self.coins = self.coins.checked_sub(amount).ok_or("not enough coins")?;
+
+        Ok(())
+    }
+
+    pub fn earn(&mut self, amount: u64) -> Result<(), Box<dyn Error>> {
+        self.coins = self.coins.checked_add(amount).ok_or("wallet full")?;
+
+        Ok(())
+    }
+}
+
+impl Component for Wallet {}
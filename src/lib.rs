@@ -1,13 +1,35 @@
 pub mod env;
 
+pub mod admin;
+
+pub mod auth;
+
+pub mod cdn;
+
+pub mod combat;
+
 pub mod db;
 
 pub mod net;
 
 pub mod gate;
 
+pub mod item;
+
+pub mod limit;
+
 pub mod map;
 
+pub mod party;
+
+pub mod presence;
+
+pub mod quest;
+
 pub mod schedule;
 
 pub mod selector;
+
+pub mod shop;
+
+pub mod trade;
@@ -0,0 +1,85 @@
+use tokio::time;
+
+/**
+ * How fast tokens come back and how many can be saved up.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rate {
+    pub per_second: f64,
+    pub burst: f64,
+}
+
+impl Rate {
+    pub fn new(per_second: f64, burst: f64) -> Self {
+        Rate { per_second, burst }
+    }
+}
+
+/**
+ * A token bucket, where every packet or attempt spends a token.
+ */
+#[derive(Debug, Clone)]
+pub struct Bucket {
+    rate: Rate,
+    tokens: f64,
+    updated_at: time::Instant,
+}
+
+impl Bucket {
+    pub fn new(rate: Rate, now: time::Instant) -> Self {
+        Bucket {
+            rate,
+            tokens: rate.burst,
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, now: time::Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+
+        self.tokens = (self.tokens + elapsed * self.rate.per_second).min(self.rate.burst);
+
+        self.updated_at = now;
+    }
+
+    /**
+     * Get how long it takes until a token is available.
+     */
+    pub fn wait(&mut self, now: time::Instant) -> time::Duration {
+        self.refill(now);
+
+        if self.tokens >= 1.0 {
+            return time::Duration::ZERO;
+        }
+
+        time::Duration::from_secs_f64((1.0 - self.tokens) / self.rate.per_second)
+    }
+
+    /**
+     * Spend a token, even one that isn't there yet.
+     *
+     * The debt makes the following tokens come later.
+     */
+    pub fn spend(&mut self) {
+        self.tokens -= 1.0;
+    }
+
+    /**
+     * Spend a token if one is available.
+     */
+    pub fn take(&mut self, now: time::Instant) -> bool {
+        if !self.wait(now).is_zero() {
+            return false;
+        }
+
+        self.spend();
+
+        true
+    }
+
+    pub fn is_full(&mut self, now: time::Instant) -> bool {
+        self.refill(now);
+
+        self.tokens >= self.rate.burst
+    }
+}
@@ -0,0 +1,133 @@
+use std::{collections::HashMap, str::FromStr};
+
+use tokio::time;
+
+use super::{Bucket, Rate};
+
+/**
+ * What to do with a packet over the limit.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Drop,
+    Throttle,
+    Disconnect,
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "drop" => Ok(Action::Drop),
+            "throttle" => Ok(Action::Throttle),
+            "disconnect" => Ok(Action::Disconnect),
+            _ => Err(format!("unknown rate limit action, {value}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Limits {
+    pub action: Action,
+    /**
+     * Every packet from a connection.
+     */
+    pub packets: Rate,
+    /**
+     * Packets from a connection by their kind, on top of `packets`.
+     */
+    pub kinds: HashMap<&'static str, Rate>,
+    pub connections_per_ip: Rate,
+    pub logins_per_ip: Rate,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            action: Action::Drop,
+            packets: Rate::new(30.0, 60.0),
+            kinds: HashMap::from([
+                ("hello", Rate::new(0.5, 2.0)),
+                ("move", Rate::new(10.0, 10.0)),
+                ("inventory", Rate::new(2.0, 4.0)),
+                ("move_item", Rate::new(5.0, 10.0)),
+                ("use_item", Rate::new(5.0, 10.0)),
+                ("drop_item", Rate::new(5.0, 10.0)),
+                ("attack", Rate::new(5.0, 5.0)),
+                ("party_invite", Rate::new(1.0, 3.0)),
+                ("friend_request", Rate::new(1.0, 3.0)),
+                ("trade_request", Rate::new(1.0, 3.0)),
+                ("trade_offer", Rate::new(5.0, 10.0)),
+                ("shop_buy", Rate::new(5.0, 10.0)),
+                ("shop_sell", Rate::new(5.0, 10.0)),
+            ]),
+            connections_per_ip: Rate::new(1.0, 10.0),
+            logins_per_ip: Rate::new(0.2, 5.0),
+        }
+    }
+}
+
+pub enum Verdict {
+    Pass,
+    Drop,
+    Throttle(time::Duration),
+    Disconnect(String),
+}
+
+/**
+ * The buckets of a single connection.
+ */
+pub struct Limiter {
+    packets: Bucket,
+    kinds: HashMap<&'static str, Bucket>,
+}
+
+impl Limiter {
+    pub fn new(limits: &Limits, now: time::Instant) -> Self {
+        Limiter {
+            packets: Bucket::new(limits.packets, now),
+            kinds: HashMap::new(),
+        }
+    }
+
+    /**
+     * Decide what happens to a packet of a kind.
+     *
+     * Throttled packets still spend their tokens, so the ones after them
+     * wait even longer.
+     */
+    pub fn check(&mut self, kind: &'static str, limits: &Limits, now: time::Instant) -> Verdict {
+        let mut wait = self.packets.wait(now);
+
+        let mut kind_bucket = limits.kinds.get(kind).map(|rate| {
+            self.kinds
+                .entry(kind)
+                .or_insert_with(|| Bucket::new(*rate, now))
+        });
+
+        if let Some(bucket) = kind_bucket.as_mut() {
+            wait = wait.max(bucket.wait(now));
+        }
+
+        let verdict = if wait.is_zero() {
+            Verdict::Pass
+        } else {
+            match limits.action {
+                Action::Drop => return Verdict::Drop,
+                Action::Disconnect => {
+                    return Verdict::Disconnect(format!("too many {kind} packets"));
+                }
+                Action::Throttle => Verdict::Throttle(wait),
+            }
+        };
+
+        if let Some(bucket) = kind_bucket {
+            bucket.spend();
+        }
+
+        self.packets.spend();
+
+        verdict
+    }
+}
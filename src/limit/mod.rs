@@ -0,0 +1,7 @@
+mod bucket;
+
+pub use bucket::{Bucket, Rate};
+
+mod limiter;
+
+pub use limiter::{Action, Limiter, Limits, Verdict};
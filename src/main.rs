@@ -1,12 +1,18 @@
-use std::{error::Error, sync::Arc};
+use std::{collections::HashMap, error::Error, sync::Arc};
 
-use east_online_core::model;
 use east_online_server::{
+    admin,
+    auth::{self, Authenticator},
+    cdn,
     db::DB,
-    env::{self, url, CDN_ORIGIN},
-    gate, map,
+    env::{
+        self, ADMIN_SOCKET, AUTH_PROVIDER, INSTANCE_IDLE_SECONDS, INSTANCE_MAPS, MAX_MAP_PLAYERS,
+        MAX_PLAYERS, MAX_STREAMS, RATE_LIMIT_ACTION, TLS_CERT_PATH, TLS_KEY_PATH,
+        WEBSOCKET_ADDRESS,
+    },
+    gate, item, limit, map, net, party, presence, quest,
 };
-use tokio::{net::TcpListener, sync::mpsc};
+use tokio::{net::TcpListener, sync::mpsc, time};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
@@ -14,28 +20,177 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     let pool = Arc::new(mysql::Pool::init()?);
 
-    let listener = TcpListener::bind("0.0.0.0:3000").await?;
+    let acceptor = match (std::env::var(TLS_CERT_PATH), std::env::var(TLS_KEY_PATH)) {
+        (Ok(cert_path), Ok(key_path)) => {
+            println!("terminate tls with {}", cert_path);
+
+            Some(net::tls::acceptor(&cert_path, &key_path)?)
+        }
+        _ => None,
+    };
+
+    let mut listener = net::Listener::new();
+
+    listener.listen(
+        TcpListener::bind("0.0.0.0:3000").await?,
+        acceptor.clone(),
+        net::Transport::Raw,
+    )?;
+
+    if let Ok(address) = std::env::var(WEBSOCKET_ADDRESS) {
+        println!("accept websockets on {}", address);
+
+        listener.listen(
+            TcpListener::bind(address).await?,
+            acceptor,
+            net::Transport::WebSocket,
+        )?;
+    }
 
     println!("fetch manifest");
 
-    let mut gate_worker = gate::Worker::new(pool.clone(), listener);
+    let authenticator: Box<dyn Authenticator> = match std::env::var(AUTH_PROVIDER).as_deref() {
+        Ok("stub") => {
+            println!("authenticate with stub tokens");
+
+            Box::new(auth::Stub)
+        }
+        _ => Box::new(auth::Api::new()),
+    };
+
+    let mut limits = limit::Limits::default();
+
+    if let Ok(action) = std::env::var(RATE_LIMIT_ACTION) {
+        limits.action = action.parse()?;
+    }
+
+    let mut gate_worker = gate::Worker::new(pool.clone(), authenticator, listener);
+
+    gate_worker.set_limits(limits.clone());
+
+    let mut capacity = gate::Capacity::default();
+
+    if let Ok(streams) = std::env::var(MAX_STREAMS) {
+        capacity.streams = streams.parse()?;
+    }
+
+    if let Ok(players) = std::env::var(MAX_PLAYERS) {
+        capacity.players = players.parse()?;
+    }
+
+    if let Ok(map_players) = std::env::var(MAX_MAP_PLAYERS) {
+        capacity.map_players = map_players.parse()?;
+    }
+
+    gate_worker.set_capacity(capacity);
+
+    let item_manifest = cdn::fetch_item_manifest().await?;
+
+    let mut items = HashMap::new();
+
+    for item in item_manifest.items {
+        let definition = cdn::fetch_item(&item.id).await?;
+
+        items.insert(definition.id.clone(), definition);
+    }
+
+    let items: Arc<item::Registry> = Arc::new(items);
+
+    let quest_manifest = cdn::fetch_quest_manifest().await?;
+
+    let mut quests = HashMap::new();
+
+    for quest in quest_manifest.items {
+        let definition = cdn::fetch_quest(&quest.id).await?;
+
+        quests.insert(definition.id.clone(), definition);
+    }
+
+    let quests: Arc<quest::Registry> = Arc::new(quests);
+
+    let map_manifest = cdn::fetch_map_manifest().await?;
+
+    let console = admin::Console::new(Arc::new(cdn::Cdn));
+
+    let instance_maps = std::env::var(INSTANCE_MAPS).unwrap_or_default();
+
+    let instance_maps: Vec<&str> = instance_maps.split(',').map(str::trim).collect();
+
+    let parties = Arc::new(party::Parties::new());
+
+    let directory = Arc::new(presence::Directory::new());
+
+    let mut instances = gate::Instances::new(items.clone(), pool.clone());
+
+    instances.set_parties(parties.clone());
 
-    let map_manifest = fetch_map_manifest().await?;
+    instances.set_directory(directory.clone());
+
+    instances.set_quests(quests.clone());
+
+    instances.set_limits(limits.clone());
+
+    instances.set_console(console.clone());
+
+    if let Ok(seconds) = std::env::var(INSTANCE_IDLE_SECONDS) {
+        instances.set_idle_timeout(time::Duration::from_secs(seconds.parse()?));
+    }
 
     for item in map_manifest.items {
-        let map = fetch_map(&item.id).await?;
+        let map = cdn::fetch_map(&item.id).await?;
+
+        let shops = cdn::fetch_shops(&item.id).await?;
+
+        let objects = cdn::fetch_objects(&item.id).await?;
+
+        let npcs = cdn::fetch_npcs(&item.id).await?;
+
+        if instance_maps.contains(&map.id.as_str()) {
+            println!("create template, {}", &map.id);
+
+            let mut template = map::Template::from_map(map);
+
+            template.set_shops(shops);
+
+            template.set_objects(objects);
+
+            template.set_npcs(npcs);
+
+            instances.add_template(template);
+
+            continue;
+        }
 
         let map_id = map.id.clone();
 
         let (enter_tx, enter_rx) = mpsc::channel(16);
 
-        let (exit_tx, exit_rx) = mpsc::channel(16);
+        let (exit_tx, exit_rx) = mpsc::unbounded_channel();
 
         gate_worker.add_channel(&map_id, (enter_tx, exit_rx));
 
         println!("create worker, {}", &map_id);
 
-        let map_worker = map::Worker::from_map(map, pool.clone(), (exit_tx, enter_rx));
+        let mut map_worker =
+            map::Worker::from_map(map, items.clone(), pool.clone(), (exit_tx, enter_rx));
+
+        map_worker.set_limits(limits.clone());
+
+        for shop in shops {
+            map_worker.add_shop(shop)?;
+        }
+
+        map_worker.add_objects(objects)?;
+
+        map_worker.add_npcs(npcs)?;
+
+        map_worker.set_parties(parties.clone());
+
+        map_worker.set_directory(directory.clone());
+
+        map_worker.set_quests(quests.clone());
+
+        console.add_map(&map_id, map_worker.control());
 
         tokio::spawn(async move {
             if let Err(e) = map_worker.run().await {
@@ -44,6 +199,18 @@ async fn main() -> Result<(), Box<dyn Error>> {
         });
     }
 
+    gate_worker.set_instances(instances);
+
+    if let Ok(path) = std::env::var(ADMIN_SOCKET) {
+        println!("open admin console on {}", path);
+
+        tokio::spawn(async move {
+            if let Err(e) = console.listen(path).await {
+                eprintln!("admin console died for {e}");
+            }
+        });
+    }
+
     println!("open gate");
 
     if let Err(e) = gate_worker.run().await {
@@ -52,23 +219,3 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
-
-async fn fetch_map_manifest() -> Result<model::MapManifest, Box<dyn Error>> {
-    let response = reqwest::get(url(CDN_ORIGIN, "maps/manifest.yml")).await?;
-
-    let bytes = response.bytes().await?;
-
-    let result: model::MapManifest = serde_yaml::from_slice(&bytes)?;
-
-    Ok(result)
-}
-
-async fn fetch_map(id: &str) -> Result<model::Map, Box<dyn Error>> {
-    let response = reqwest::get(url(CDN_ORIGIN, &format!("maps/{}.yml", id))).await?;
-
-    let bytes = response.bytes().await?;
-
-    let result: model::Map = serde_yaml::from_slice(&bytes)?;
-
-    Ok(result)
-}
@@ -1,31 +1,61 @@
-use tokio::time;
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+};
 
-use east_online_core::model::Direction;
+/**
+ * Anything an actor can carry.
+ *
+ * Every actor holds at most one component of each type.
+ */
+pub trait Component: Any + Send + Sync {}
 
 pub struct Actor {
     pub id: String,
-    pub movable: Movable,
+    components: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
 }
 
 impl Actor {
     pub fn new(id: String) -> Self {
         Actor {
             id,
-            movable: Movable::new(),
+            components: HashMap::new(),
         }
     }
-}
 
-pub struct Movable {
-    pub direction: Direction,
-    pub moved_at: time::Instant,
-}
+    pub fn with<T: Component>(mut self, component: T) -> Self {
+        self.insert(component);
 
-impl Movable {
-    pub fn new() -> Self {
-        Movable {
-            direction: Direction::Idle,
-            moved_at: time::Instant::now(),
-        }
+        self
+    }
+
+    pub fn insert<T: Component>(&mut self, component: T) -> Option<T> {
+        self.components
+            .insert(TypeId::of::<T>(), Box::new(component))
+            .and_then(|previous| previous.downcast().ok())
+            .map(|previous| *previous)
+    }
+
+    pub fn remove<T: Component>(&mut self) -> Option<T> {
+        self.components
+            .remove(&TypeId::of::<T>())
+            .and_then(|component| component.downcast().ok())
+            .map(|component| *component)
+    }
+
+    pub fn has<T: Component>(&self) -> bool {
+        self.components.contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn get<T: Component>(&self) -> Option<&T> {
+        self.components
+            .get(&TypeId::of::<T>())
+            .and_then(|component| component.downcast_ref())
+    }
+
+    pub fn get_mut<T: Component>(&mut self) -> Option<&mut T> {
+        self.components
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|component| component.downcast_mut())
     }
 }
@@ -0,0 +1,40 @@
+use std::collections::HashMap;
+
+use east_online_core::model::Vector3;
+use tokio::sync::oneshot;
+
+use crate::net::packet;
+
+use super::{ProfileChange, SaveReport, Tile};
+
+/**
+ * What a map tells about itself.
+ */
+#[derive(Debug, Clone)]
+pub struct Status {
+    pub id: String,
+    pub name: String,
+    pub users: Vec<(String, Vector3)>,
+    /**
+     * Players changed since the last autosave.
+     */
+    pub dirty: usize,
+    pub autosave: SaveReport,
+}
+
+/**
+ * An operator request to a map, answered through its reply channel.
+ */
+pub enum Command {
+    Status(oneshot::Sender<Status>),
+    Kick(String, oneshot::Sender<bool>),
+    Teleport(String, Vector3, oneshot::Sender<Result<(), String>>),
+    Notice(String),
+    Reload(
+        HashMap<Vector3, Tile>,
+        oneshot::Sender<Result<usize, String>>,
+    ),
+    Transfer(Vec<String>, String, oneshot::Sender<Result<usize, String>>),
+    Deliver(String, packet::Outgoing),
+    Profile(String, ProfileChange, oneshot::Sender<Result<(), String>>),
+}
@@ -0,0 +1,34 @@
+/**
+ * The animations an actor can play for the players around it.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emote {
+    Wave,
+    Sit,
+    Bow,
+    Cheer,
+    Laugh,
+}
+
+impl Emote {
+    pub fn to_byte(self) -> u8 {
+        match self {
+            Emote::Wave => 0,
+            Emote::Sit => 1,
+            Emote::Bow => 2,
+            Emote::Cheer => 3,
+            Emote::Laugh => 4,
+        }
+    }
+
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Emote::Wave),
+            1 => Some(Emote::Sit),
+            2 => Some(Emote::Bow),
+            3 => Some(Emote::Cheer),
+            4 => Some(Emote::Laugh),
+            _ => None,
+        }
+    }
+}
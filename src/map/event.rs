@@ -0,0 +1,20 @@
+use crate::net::{protocol::Capabilities, Stream};
+
+/**
+ * Something a map reports back to the gate.
+ *
+ * Events go over an unbounded channel, so a map never waits on a gate
+ * that is waiting on the map in turn.
+ */
+#[derive(Debug)]
+pub enum Event {
+    Left(String),
+    /**
+     * Players taken off the map to be sent to another map, or to a new
+     * instance when the destination is a template.
+     */
+    Transfer {
+        destination: String,
+        users: Vec<(String, Stream, Capabilities)>,
+    },
+}
@@ -1,14 +1,28 @@
 use east_online_core::model::Vector3;
-use tokio::{net::TcpStream, time};
+use tokio::time;
 
-use crate::net::packet;
+use crate::{
+    map::Command,
+    net::{packet, protocol::Capabilities, Stream},
+};
 
 pub enum Job {
-    Accept(TcpStream, String, Vector3),
+    Accept(Stream, String, Option<Vector3>, Capabilities),
     Drop(String, String),
     Readable(String),
     Incoming(String, packet::Incoming),
     Write(String, packet::Outgoing),
     Broadcast(packet::Outgoing),
+    BroadcastNear(Vector3, packet::Outgoing),
     Move(String, time::Duration),
+    Respawn(String),
+    /**
+     * An NPC striking whatever stands in front of it.
+     */
+    Attack(String),
+    Resume(String),
+    EmoteReady(String),
+    Autosave,
+    Command(Command),
+    Close,
 }
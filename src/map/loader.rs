@@ -0,0 +1,13 @@
+use std::{collections::HashMap, error::Error};
+
+use east_online_core::model::Vector3;
+
+use super::Tile;
+
+/**
+ * Where the tiles of a map come from when it is loaded again.
+ */
+#[async_trait::async_trait]
+pub trait Loader: Send + Sync {
+    async fn load(&self, id: &str) -> Result<HashMap<Vector3, Tile>, Box<dyn Error + Send + Sync>>;
+}
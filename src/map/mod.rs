@@ -1,14 +1,54 @@
 mod object;
 
-pub use object::Object;
+pub use object::{Object, ObjectDefinition, ObjectKind, ObjectManifest};
+
+mod npc;
+
+pub use npc::{NpcDefinition, NpcManifest};
+
+mod emote;
+
+pub use emote::Emote;
 
 mod actor;
 
-pub use actor::Actor;
+pub use actor::{Actor, Component};
+
+mod movable;
+
+pub use movable::Movable;
+
+mod species;
+
+pub use species::Species;
+
+mod profile;
+
+pub use profile::{Profile, ProfileChange, MAX_NAME_LENGTH};
 
 mod tile;
 
-pub use tile::Tile;
+pub use tile::{Kind, Tile};
+
+mod command;
+
+pub use command::{Command, Status};
+
+mod event;
+
+pub use event::Event;
+
+mod template;
+
+pub use template::Template;
+
+mod loader;
+
+pub use loader::Loader;
+
+mod saver;
+
+pub use saver::{SaveReport, Saver, AUTOSAVE_INTERVAL};
 
 mod job;
 
@@ -0,0 +1,29 @@
+use tokio::time;
+
+use east_online_core::model::Direction;
+
+use super::Component;
+
+pub struct Movable {
+    pub direction: Direction,
+    pub facing: Direction,
+    pub moved_at: time::Instant,
+}
+
+impl Movable {
+    pub fn new() -> Self {
+        Movable {
+            direction: Direction::Idle,
+            facing: Direction::Down,
+            moved_at: time::Instant::now(),
+        }
+    }
+}
+
+impl Default for Movable {
+    fn default() -> Self {
+        Movable::new()
+    }
+}
+
+impl Component for Movable {}
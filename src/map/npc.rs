@@ -0,0 +1,44 @@
+use east_online_core::model::{Direction, Vector3};
+use serde::Deserialize;
+
+/**
+ * Where players enter a map and the NPCs fighting on it, loaded next to its
+ * tiles.
+ */
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NpcManifest {
+    /**
+     * Where players enter and come back after dying, the origin if unset.
+     */
+    #[serde(default)]
+    pub spawn: Option<Vector3>,
+    #[serde(default)]
+    pub npcs: Vec<NpcDefinition>,
+}
+
+/**
+ * An NPC taking part in combat, identified like any other actor.
+ *
+ * One with power hits whatever stands in front of it whenever its attack
+ * is ready, and every one comes back where it was placed after dying.
+ * Defeating one of a species counts toward the quests asking for it.
+ */
+#[derive(Debug, Clone, Deserialize)]
+pub struct NpcDefinition {
+    pub id: String,
+    pub name: String,
+    pub position: Vector3,
+    #[serde(default = "default_facing")]
+    pub facing: Direction,
+    pub health: u32,
+    #[serde(default)]
+    pub defense: u32,
+    #[serde(default)]
+    pub power: u32,
+    #[serde(default)]
+    pub species: Option<String>,
+}
+
+fn default_facing() -> Direction {
+    Direction::Down
+}
@@ -1,3 +1,120 @@
+use east_online_core::model::Vector3;
+use serde::Deserialize;
+
+use crate::item;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectKind {
+    Door,
+    Chest,
+    Switch,
+    Portal,
+}
+
+impl ObjectKind {
+    pub fn to_byte(self) -> u8 {
+        match self {
+            ObjectKind::Door => 0,
+            ObjectKind::Chest => 1,
+            ObjectKind::Switch => 2,
+            ObjectKind::Portal => 3,
+        }
+    }
+
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(ObjectKind::Door),
+            1 => Some(ObjectKind::Chest),
+            2 => Some(ObjectKind::Switch),
+            3 => Some(ObjectKind::Portal),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * The objects of a map, loaded next to its tiles.
+ */
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ObjectManifest {
+    #[serde(default)]
+    pub objects: Vec<ObjectDefinition>,
+}
+
+/**
+ * An object as it is placed in the map data.
+ *
+ * A switch toggles the objects it targets along with itself, a portal
+ * sends whoever uses it to its destination with their party, and a
+ * persistent object keeps its state across restarts.
+ */
+#[derive(Debug, Clone, Deserialize)]
+pub struct ObjectDefinition {
+    pub id: String,
+    pub kind: ObjectKind,
+    pub position: Vector3,
+    #[serde(default)]
+    pub open: bool,
+    #[serde(default)]
+    pub loot: Vec<item::Stack>,
+    #[serde(default)]
+    pub targets: Vec<String>,
+    /**
+     * Id of the map or template a portal leads to.
+     */
+    #[serde(default)]
+    pub destination: Option<String>,
+    #[serde(default)]
+    pub persistent: bool,
+}
+
 pub struct Object {
     pub id: String,
+    pub kind: ObjectKind,
+    /**
+     * Whether a door or chest is open, or a switch is on.
+     */
+    pub open: bool,
+    pub loot: Vec<item::Stack>,
+    pub targets: Vec<String>,
+    pub destination: Option<String>,
+    pub persistent: bool,
+}
+
+impl Object {
+    pub fn from_definition(definition: ObjectDefinition) -> Self {
+        Object {
+            id: definition.id,
+            kind: definition.kind,
+            open: definition.open,
+            loot: definition.loot,
+            targets: definition.targets,
+            destination: definition.destination,
+            persistent: definition.persistent,
+        }
+    }
+
+    /**
+     * Check if the object keeps actors off its tile, as a closed door does.
+     */
+    pub fn is_blocking(&self) -> bool {
+        self.kind == ObjectKind::Door && !self.open
+    }
+
+    /**
+     * Flip the state of the object.
+     *
+     * Return false if nothing changed, as a looted chest stays open and a
+     * portal has nothing to flip.
+     */
+    pub fn toggle(&mut self) -> bool {
+        if self.kind == ObjectKind::Chest && self.open || self.kind == ObjectKind::Portal {
+            return false;
+        }
+
+        self.open = !self.open;
+
+        true
+    }
 }
@@ -0,0 +1,68 @@
+use std::error::Error;
+
+use super::Component;
+
+/**
+ * Longest display name a player may pick.
+ */
+pub const MAX_NAME_LENGTH: usize = 32;
+
+/**
+ * How an actor is shown to others.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    pub name: String,
+    pub appearance: String,
+    pub level: u16,
+}
+
+impl Profile {
+    pub fn new(name: String, appearance: String, level: u16) -> Self {
+        Profile {
+            name,
+            appearance,
+            level,
+        }
+    }
+
+    /**
+     * Change one part of the profile.
+     *
+     * Throw an error if the result can't be shown.
+     */
+    pub fn apply(&mut self, change: ProfileChange) -> Result<(), Box<dyn Error>> {
+        match change {
+            ProfileChange::Name(name) if name.is_empty() || name.len() > MAX_NAME_LENGTH => {
+                return Err(format!("name must be 1 to {} bytes", MAX_NAME_LENGTH).into());
+            }
+            ProfileChange::Name(name) => self.name = name,
+            ProfileChange::Appearance(appearance) if appearance.len() > usize::from(u8::MAX) => {
+                return Err("appearance too long".into());
+            }
+            ProfileChange::Appearance(appearance) => self.appearance = appearance,
+            ProfileChange::Level(0) => return Err("level starts at 1".into()),
+            ProfileChange::Level(level) => self.level = level,
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Profile::new(String::new(), String::new(), 1)
+    }
+}
+
+impl Component for Profile {}
+
+/**
+ * A change to a profile made while its player is on a map.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileChange {
+    Name(String),
+    Appearance(String),
+    Level(u16),
+}
@@ -0,0 +1,123 @@
+use std::{
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::db::{ActorState, Storage};
+
+/**
+ * How often a map writes the players that changed since its last save.
+ */
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/**
+ * How the background saves of a map have been doing.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SaveReport {
+    /**
+     * Batches handed over but not written yet.
+     */
+    pub pending: usize,
+    pub saves: u64,
+    pub failures: u64,
+    /**
+     * Players written by the last batch that went through.
+     */
+    pub last_count: usize,
+    pub last_latency: Option<Duration>,
+}
+
+/**
+ * Writes batches of player states on a thread of its own, so slow storage
+ * never holds up the event loop.
+ *
+ * Batches are written one after another in the order they were handed
+ * over, so a newer state is never overwritten by an older one.
+ */
+pub struct Saver {
+    map_id: String,
+    pool: Arc<dyn Storage>,
+    sender: mpsc::Sender<Vec<ActorState>>,
+    receiver: Option<mpsc::Receiver<Vec<ActorState>>>,
+    report: Arc<Mutex<SaveReport>>,
+}
+
+impl Saver {
+    pub fn new(map_id: String, pool: Arc<dyn Storage>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        Saver {
+            map_id,
+            pool,
+            sender,
+            receiver: Some(receiver),
+            report: Arc::new(Mutex::new(SaveReport::default())),
+        }
+    }
+
+    /**
+     * Start writing the batches handed over so far and from now on.
+     *
+     * Does nothing once started.
+     */
+    pub fn start(&mut self) {
+        let receiver = match self.receiver.take() {
+            Some(receiver) => receiver,
+            None => return,
+        };
+
+        let map_id = self.map_id.to_owned();
+
+        let pool = self.pool.clone();
+
+        let report = self.report.clone();
+
+        thread::spawn(move || {
+            while let Ok(states) = receiver.recv() {
+                let started = Instant::now();
+
+                let result = pool.save_states(&states);
+
+                let mut report = report.lock().unwrap();
+
+                report.pending -= 1;
+
+                match result {
+                    Ok(_) => {
+                        report.saves += 1;
+
+                        report.last_count = states.len();
+
+                        report.last_latency = Some(started.elapsed());
+                    }
+                    Err(e) => {
+                        report.failures += 1;
+
+                        eprintln!("{} states of {} not saved for {e}", states.len(), map_id);
+                    }
+                }
+            }
+        });
+    }
+
+    /**
+     * Hand a batch over to be written.
+     */
+    pub fn save(&self, states: Vec<ActorState>) {
+        if states.is_empty() {
+            return;
+        }
+
+        self.report.lock().unwrap().pending += 1;
+
+        if self.sender.send(states).is_err() {
+            self.report.lock().unwrap().pending -= 1;
+        }
+    }
+
+    pub fn report(&self) -> SaveReport {
+        self.report.lock().unwrap().to_owned()
+    }
+}
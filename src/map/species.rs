@@ -0,0 +1,17 @@
+use super::Component;
+
+/**
+ * What sort of creature an actor is, such as the wolves a quest asks to
+ * defeat.
+ */
+pub struct Species {
+    pub id: String,
+}
+
+impl Species {
+    pub fn new(id: String) -> Self {
+        Species { id }
+    }
+}
+
+impl Component for Species {}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use east_online_core::model::{self, Vector3};
+
+use crate::shop;
+
+use super::{NpcManifest, ObjectDefinition, Tile};
+
+/**
+ * A map that is copied into a new worker for every instance of it.
+ */
+pub struct Template {
+    pub id: String,
+    pub name: String,
+    tiles: HashMap<Vector3, Tile>,
+    shops: Vec<shop::Definition>,
+    objects: Vec<ObjectDefinition>,
+    npcs: NpcManifest,
+}
+
+impl Template {
+    pub fn new(id: String, name: String, tiles: HashMap<Vector3, Tile>) -> Self {
+        Template {
+            id,
+            name,
+            tiles,
+            shops: vec![],
+            objects: vec![],
+            npcs: NpcManifest::default(),
+        }
+    }
+
+    pub fn from_map(map: model::Map) -> Self {
+        let tiles = map
+            .tiles
+            .into_iter()
+            .map(|(position, placable)| (position, Tile::from_placable(placable)))
+            .collect();
+
+        Template::new(map.id, map.name, tiles)
+    }
+
+    pub fn set_shops(&mut self, shops: Vec<shop::Definition>) {
+        self.shops = shops;
+    }
+
+    pub fn shops(&self) -> &[shop::Definition] {
+        &self.shops
+    }
+
+    pub fn set_objects(&mut self, objects: Vec<ObjectDefinition>) {
+        self.objects = objects;
+    }
+
+    pub fn objects(&self) -> &[ObjectDefinition] {
+        &self.objects
+    }
+
+    pub fn set_npcs(&mut self, npcs: NpcManifest) {
+        self.npcs = npcs;
+    }
+
+    pub fn npcs(&self) -> &NpcManifest {
+        &self.npcs
+    }
+
+    /**
+     * Get a fresh copy of the tiles for a new instance.
+     */
+    pub fn tiles(&self) -> HashMap<Vector3, Tile> {
+        self.tiles
+            .iter()
+            .map(|(position, tile)| (position.to_owned(), tile.blank()))
+            .collect()
+    }
+}
@@ -1,21 +1,126 @@
 use std::collections::HashMap;
 
-use east_online_core::model::{self, Rotation};
+use east_online_core::model::{self, Direction, Rotation};
 
-use super::{object::Object, Actor};
+use crate::item;
+
+use super::{object::Object, Actor, Component};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Floor,
+    Stairs,
+}
+
+impl Kind {
+    /**
+     * Tell the kind of a tile from the id of its model.
+     *
+     * Stairs and ramps both connect a level to the one above.
+     */
+    pub fn from_model_id(id: &str) -> Self {
+        if id.starts_with("stairs") || id.starts_with("ramp") {
+            Kind::Stairs
+        } else {
+            Kind::Floor
+        }
+    }
+}
 
 pub struct Tile {
+    pub kind: Kind,
     pub rotation: Rotation,
+    /**
+     * Id of the model placed here, empty for tiles made in code.
+     */
+    pub model_id: String,
     pub object: Option<Object>,
     pub actors: HashMap<String, Actor>,
+    pub items: Vec<item::Stack>,
 }
 
 impl Tile {
-    pub fn from_placable(placable: model::Placable) -> Self {
+    pub fn new(kind: Kind, rotation: Rotation) -> Self {
         Tile {
-            rotation: placable.rotation,
+            kind,
+            rotation,
+            model_id: String::new(),
             object: None,
             actors: HashMap::new(),
+            items: Vec::new(),
+        }
+    }
+
+    pub fn from_placable(placable: model::Placable) -> Self {
+        Tile {
+            model_id: placable.id.to_owned(),
+            ..Tile::new(Kind::from_model_id(&placable.id), placable.rotation)
+        }
+    }
+
+    /**
+     * Copy the shape of the tile, leaving out whatever is on it.
+     */
+    pub fn blank(&self) -> Tile {
+        Tile {
+            model_id: self.model_id.to_owned(),
+            ..Tile::new(self.kind, self.rotation)
+        }
+    }
+
+    /**
+     * Get the rotation as it is written in packets.
+     */
+    pub fn rotation_byte(&self) -> u8 {
+        match self.rotation {
+            Rotation::Up => 0,
+            Rotation::Right => 1,
+            Rotation::Down => 2,
+            Rotation::Left => 3,
+        }
+    }
+
+    /**
+     * Check if another tile places the same model the same way.
+     */
+    pub fn is_same_shape(&self, other: &Tile) -> bool {
+        self.kind == other.kind
+            && self.model_id == other.model_id
+            && self.rotation_byte() == other.rotation_byte()
+    }
+
+    /**
+     * Get the direction in which the tile goes up.
+     *
+     * Only meaningful for stairs, whose upper side faces their rotation.
+     */
+    pub fn get_ascent(&self) -> Direction {
+        match self.rotation {
+            Rotation::Up => Direction::Up,
+            Rotation::Right => Direction::Right,
+            Rotation::Down => Direction::Down,
+            Rotation::Left => Direction::Left,
+        }
+    }
+
+    /**
+     * Check if an actor heading to a direction can step in at the same level.
+     *
+     * Stairs can only be entered from their lower side, and tiles with a
+     * closed door not at all.
+     */
+    pub fn is_enterable(&self, direction: Direction) -> bool {
+        if self.object.as_ref().is_some_and(Object::is_blocking) {
+            return false;
         }
+
+        match self.kind {
+            Kind::Floor => true,
+            Kind::Stairs => self.get_ascent() == direction,
+        }
+    }
+
+    pub fn actors_with_mut<T: Component>(&mut self) -> impl Iterator<Item = &mut Actor> {
+        self.actors.values_mut().filter(|actor| actor.has::<T>())
     }
 }
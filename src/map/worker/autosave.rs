@@ -0,0 +1,75 @@
+use std::error::Error;
+
+use tokio::time;
+
+use crate::{
+    combat::Health,
+    db::ActorState,
+    map::{Job, AUTOSAVE_INTERVAL},
+    schedule::Schedule,
+};
+
+use super::Worker;
+
+impl Worker {
+    /**
+     * Hand the players that changed since the last autosave over to be
+     * written, then schedule the next autosave.
+     */
+    pub(super) fn handle_autosave(&mut self) -> Result<(), Box<dyn Error>> {
+        let keys = std::mem::take(&mut self.dirty);
+
+        let states = keys
+            .iter()
+            .filter_map(|key| self.actor_state(key))
+            .collect();
+
+        self.saver.save(states);
+
+        self.schedule_autosave();
+
+        Ok(())
+    }
+
+    pub(super) fn schedule_autosave(&mut self) {
+        let deadline = time::Instant::now() + AUTOSAVE_INTERVAL;
+
+        self.schedule_queue
+            .push(Schedule::new(Job::Autosave, deadline));
+    }
+
+    /**
+     * Remember that a player changed since the last save.
+     *
+     * NPCs aren't saved.
+     */
+    pub(super) fn mark_dirty(&mut self, key: &str) {
+        if self.streams.contains_key(key) {
+            self.dirty.insert(key.to_owned());
+        }
+    }
+
+    /**
+     * Save a player leaving the map at once, if it changed.
+     */
+    pub(super) fn flush_state(&mut self, key: &str) {
+        if !self.dirty.remove(key) {
+            return;
+        }
+
+        if let Some(state) = self.actor_state(key) {
+            self.saver.save(vec![state]);
+        }
+    }
+
+    fn actor_state(&self, key: &str) -> Option<ActorState> {
+        let health = self.get_actor(key).ok()?.get::<Health>()?;
+
+        Some(ActorState {
+            user_id: key.to_owned(),
+            map_id: self.model_id.to_owned(),
+            position: self.get_position(key).ok()?,
+            health: health.current,
+        })
+    }
+}
@@ -0,0 +1,200 @@
+use std::error::Error;
+
+use east_online_core::model::{Direction, Vector3};
+use tokio::time;
+
+use crate::{
+    combat::{self, Attack, Health},
+    map::{Job, Movable, Species},
+    net::packet,
+    quest::Trigger,
+    schedule::Schedule,
+};
+
+use super::{movement::get_destination, Worker};
+
+impl Worker {
+    /**
+     * Attack the tile in front of an actor.
+     *
+     * The target follows the same stairs an actor would walk on,
+     * so nothing can be hit across a cliff.
+     *
+     * Nothing happens while the actor is dead or its attack is cooling down.
+     */
+    pub(super) fn handle_attack(&mut self, key: String) -> Result<(), Box<dyn Error>> {
+        let position = self.get_position(&key)?;
+
+        let now = time::Instant::now();
+
+        let actor = self.get_actor_mut(&key)?;
+
+        if combat::is_dead(actor) {
+            return Ok(());
+        }
+
+        let facing = actor
+            .get::<Movable>()
+            .map(|movable| movable.facing)
+            .ok_or("not movable")?;
+
+        let attack = actor.get_mut::<Attack>().ok_or("no attack")?;
+
+        if !attack.is_ready(now) {
+            return Ok(());
+        }
+
+        attack.attacked_at = Some(now);
+
+        let power = attack.power;
+
+        match get_destination(&self.map, &position, facing) {
+            Some(target) => self.attack(key, target, power),
+            None => Ok(()),
+        }
+    }
+
+    /**
+     * Let an NPC attack and schedule its next attack once it cools down.
+     */
+    pub(super) fn handle_npc_attack(&mut self, key: String) -> Result<(), Box<dyn Error>> {
+        let cooldown = self
+            .get_actor(&key)?
+            .get::<Attack>()
+            .ok_or("no attack")?
+            .cooldown;
+
+        let deadline = time::Instant::now() + cooldown;
+
+        self.schedule_queue
+            .push(Schedule::new(Job::Attack(key.to_owned()), deadline));
+
+        self.handle_attack(key)
+    }
+
+    /**
+     * Deal damage to the first living actor on a tile.
+     *
+     * Players and NPCs take the same path, and a dead target is
+     * scheduled to respawn.
+     */
+    fn attack(
+        &mut self,
+        attacker: String,
+        target: Vector3,
+        power: u32,
+    ) -> Result<(), Box<dyn Error>> {
+        let tile = match self.map.get_mut(&target) {
+            Some(tile) => tile,
+            None => return Ok(()),
+        };
+
+        let victim = match tile
+            .actors_with_mut::<Health>()
+            .find(|actor| actor.id != attacker && !combat::is_dead(actor))
+        {
+            Some(victim) => victim,
+            None => return Ok(()),
+        };
+
+        let id = victim.id.to_owned();
+
+        let species = victim.get::<Species>().map(|species| species.id.to_owned());
+
+        let health = victim.get_mut::<Health>().unwrap();
+
+        let amount = health.hurt(combat::damage(power, health.defense));
+
+        let is_dead = health.is_dead();
+
+        let packet = packet::Outgoing::Damage {
+            id: attacker.to_owned(),
+            target: id.to_owned(),
+            amount,
+            health: health.current,
+        };
+
+        self.schedule_queue
+            .push(Schedule::instant(Job::BroadcastNear(
+                target.to_owned(),
+                packet,
+            )));
+
+        if is_dead {
+            if let Some(movable) = victim.get_mut::<Movable>() {
+                movable.direction = Direction::Idle;
+            }
+
+            let packet = packet::Outgoing::Die { id: id.to_owned() };
+
+            self.schedule_queue
+                .push(Schedule::instant(Job::Broadcast(packet)));
+
+            let deadline = time::Instant::now() + combat::RESPAWN_DELAY;
+
+            self.schedule_queue
+                .push(Schedule::new(Job::Respawn(id.to_owned()), deadline));
+
+            if let Some(species) = species {
+                self.progress(&attacker, Trigger::Defeat { species })?;
+            }
+        }
+
+        self.mark_dirty(&id);
+
+        Ok(())
+    }
+
+    /**
+     * Bring a dead actor back with full health, an NPC where it was placed
+     * and a player to the spawn point.
+     */
+    pub(super) fn handle_respawn(&mut self, key: String) -> Result<(), Box<dyn Error>> {
+        let position = self.get_position(&key)?;
+
+        let spawn = self.homes.get(&key).unwrap_or(&self.spawn).to_owned();
+
+        if !self.map.contains_key(&spawn) {
+            return Err("no spawn tile".into());
+        }
+
+        let mut actor = self
+            .map
+            .get_mut(&position)
+            .ok_or("no tile")?
+            .actors
+            .remove(&key)
+            .ok_or("no actor")?;
+
+        let health = actor.get_mut::<Health>().ok_or("no health")?;
+
+        health.restore();
+
+        let health = health.current;
+
+        if let Some(movable) = actor.get_mut::<Movable>() {
+            movable.direction = Direction::Idle;
+        }
+
+        self.map
+            .get_mut(&spawn)
+            .unwrap()
+            .actors
+            .insert(key.to_owned(), actor);
+
+        self.positions.insert(key.to_owned(), spawn.to_owned());
+
+        self.mark_dirty(&key);
+
+        let packet = packet::Outgoing::Respawn {
+            id: key,
+            position: spawn,
+            health,
+        };
+
+        self.schedule_queue
+            .push(Schedule::instant(Job::Broadcast(packet)));
+
+        Ok(())
+    }
+}
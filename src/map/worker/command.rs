@@ -0,0 +1,208 @@
+use std::error::Error;
+
+use east_online_core::model::{Direction, Vector3};
+
+use crate::{
+    map::{Command, Event, Job, Movable, Status},
+    net::{packet, protocol::Capabilities, Stream},
+    schedule::Schedule,
+};
+
+use super::Worker;
+
+impl Worker {
+    /**
+     * Handle a command from the admin console.
+     *
+     * The reply is dropped silently if the console stopped waiting.
+     */
+    pub(super) async fn handle_command(&mut self, command: Command) -> Result<(), Box<dyn Error>> {
+        match command {
+            Command::Status(reply) => {
+                let users = self
+                    .streams
+                    .keys()
+                    .filter_map(|key| Some((key.to_owned(), self.positions.get(key)?.to_owned())))
+                    .collect();
+
+                let status = Status {
+                    id: self.id.to_owned(),
+                    name: self.name.to_owned(),
+                    users,
+                    dirty: self.dirty.len(),
+                    autosave: self.saver.report(),
+                };
+
+                reply.send(status).ok();
+            }
+            Command::Kick(key, reply) => {
+                let found = self.streams.contains_key(&key);
+
+                if found {
+                    let job = Job::Drop(key, String::from("kicked by admin"));
+
+                    self.schedule_queue.push(Schedule::instant(job));
+                }
+
+                reply.send(found).ok();
+            }
+            Command::Teleport(key, position, reply) => {
+                let result = self.teleport(&key, position).map_err(|e| e.to_string());
+
+                reply.send(result).ok();
+            }
+            Command::Notice(message) => {
+                let packet = packet::Outgoing::Notice { message };
+
+                self.schedule_queue
+                    .push(Schedule::instant(Job::Broadcast(packet)));
+            }
+            Command::Reload(tiles, reply) => {
+                let result = self.reload(tiles).map_err(|e| e.to_string());
+
+                reply.send(result).ok();
+            }
+            Command::Transfer(keys, destination, reply) => {
+                reply.send(self.transfer(keys, destination)).ok();
+            }
+            Command::Deliver(key, packet) => {
+                self.schedule_queue
+                    .push(Schedule::instant(Job::Write(key, packet)));
+            }
+            Command::Profile(key, change, reply) => {
+                let result = self.change_profile(&key, change).map_err(|e| e.to_string());
+
+                reply.send(result).ok();
+            }
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Send players to another map or a new instance along with their
+     * parties.
+     *
+     * Turned down if a member is on another map, so nobody is left behind.
+     * Return how many players were sent.
+     */
+    pub(super) fn transfer(
+        &mut self,
+        keys: Vec<String>,
+        destination: String,
+    ) -> Result<usize, String> {
+        let members: Vec<String> = keys
+            .iter()
+            .filter_map(|key| self.parties.find(key))
+            .flat_map(|party| party.members)
+            .collect();
+
+        let mut keys = keys;
+
+        keys.extend(members);
+
+        keys.sort();
+
+        keys.dedup();
+
+        let elsewhere = keys.iter().find(|key| {
+            self.parties
+                .location(key)
+                .is_some_and(|location| location.map != self.id)
+        });
+
+        if let Some(key) = elsewhere {
+            return Err(format!("{} is on another map", key));
+        }
+
+        let users: Vec<_> = keys.iter().filter_map(|key| self.take_user(key)).collect();
+
+        if users.is_empty() {
+            return Err(String::from("no user to transfer"));
+        }
+
+        let count = users.len();
+
+        self.channel
+            .0
+            .send(Event::Transfer { destination, users })
+            .map_err(|e| e.to_string())?;
+
+        Ok(count)
+    }
+
+    /**
+     * Take a player off the map with its stream, leaving the gate to send
+     * it somewhere else.
+     */
+    fn take_user(&mut self, key: &str) -> Option<(String, Stream, Capabilities)> {
+        let stream = self.streams.remove(key)?;
+
+        let capabilities = self.capabilities.remove(key)?;
+
+        self.leave_trade(key);
+
+        self.limiters.remove(key);
+
+        self.paused.remove(key);
+
+        self.flush_state(key);
+
+        self.parties.unlocate(key);
+
+        if let Some(position) = self.positions.remove(key) {
+            if let Some(tile) = self.map.get_mut(&position) {
+                tile.actors.remove(key);
+            }
+        }
+
+        Some((key.to_owned(), stream, capabilities))
+    }
+
+    /**
+     * Put an actor on a tile at once, stopping it there.
+     */
+    fn teleport(&mut self, key: &str, destination: Vector3) -> Result<(), Box<dyn Error>> {
+        let position = self.get_position(key)?;
+
+        if !self.map.contains_key(&destination) {
+            return Err("no tile at the destination".into());
+        }
+
+        let mut actor = self
+            .map
+            .get_mut(&position)
+            .ok_or("no tile")?
+            .actors
+            .remove(key)
+            .ok_or("no actor")?;
+
+        if let Some(movable) = actor.get_mut::<Movable>() {
+            movable.direction = Direction::Idle;
+        }
+
+        self.map
+            .get_mut(&destination)
+            .unwrap()
+            .actors
+            .insert(key.to_owned(), actor);
+
+        self.positions
+            .insert(key.to_owned(), destination.to_owned());
+
+        self.mark_dirty(key);
+
+        let packet = packet::Outgoing::Stop {
+            id: key.to_owned(),
+            position: destination,
+            facing: self.get_facing(key),
+        };
+
+        self.schedule_queue
+            .push(Schedule::instant(Job::Broadcast(packet)));
+
+        self.share_position(key);
+
+        Ok(())
+    }
+}
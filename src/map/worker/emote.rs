@@ -0,0 +1,57 @@
+use std::error::Error;
+
+use tokio::time;
+
+use crate::{
+    combat,
+    map::{Emote, Job},
+    net::{packet, protocol},
+    schedule::Schedule,
+};
+
+use super::Worker;
+
+const EMOTE_COOLDOWN: time::Duration = time::Duration::from_secs(2);
+
+impl Worker {
+    /**
+     * Show an animation of an actor to the players around it.
+     *
+     * Unknown emotes are turned down, and those sent before the last one
+     * cooled down are ignored.
+     */
+    pub(super) fn handle_emote(&mut self, key: String, emote: u8) -> Result<(), Box<dyn Error>> {
+        let position = self.get_position(&key)?;
+
+        if Emote::from_byte(emote).is_none() {
+            self.refuse(
+                &key,
+                protocol::ERROR_EMOTE,
+                format!("unknown emote {}", emote),
+            );
+
+            return Ok(());
+        }
+
+        if combat::is_dead(self.get_actor(&key)?) || self.emoting.contains(&key) {
+            return Ok(());
+        }
+
+        self.emoting.insert(key.to_owned());
+
+        let packet = packet::Outgoing::Emote {
+            id: key.to_owned(),
+            emote,
+        };
+
+        self.schedule_queue
+            .push(Schedule::instant(Job::BroadcastNear(position, packet)));
+
+        let deadline = time::Instant::now() + EMOTE_COOLDOWN;
+
+        self.schedule_queue
+            .push(Schedule::new(Job::EmoteReady(key), deadline));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+use std::error::Error;
+
+use east_online_core::model::Vector3;
+use tokio::time;
+
+use crate::{
+    combat::{Attack, Health, ATTACK_COOLDOWN},
+    limit::Limiter,
+    map::{Actor, Event, Job, Movable},
+    net::{packet, protocol::Capabilities, Stream},
+    presence,
+    quest::Journal,
+    schedule::Schedule,
+};
+
+use super::{interaction, inventory, is_near, Worker};
+
+impl Worker {
+    /**
+     * Place a player handed over by the gate on the map.
+     *
+     * A player that could not be placed is reported offline and handed back
+     * to the gate so the slot it counted is released, and one that failed
+     * after being placed is dropped as if it had disconnected.
+     */
+    pub(super) fn handle_accept(
+        &mut self,
+        stream: Stream,
+        id: String,
+        position: Option<Vector3>,
+        capabilities: Capabilities,
+    ) -> Result<(), Box<dyn Error>> {
+        let result = self.accept(stream, &id, position, capabilities);
+
+        if let Err(e) = &result {
+            if self.streams.contains_key(&id) {
+                let schedule = Schedule::instant(Job::Drop(id, format!("{e}")));
+
+                self.schedule_queue.push(schedule);
+            } else {
+                self.directory.offline(&id);
+
+                self.publish_presence(&id);
+
+                self.channel.0.send(Event::Left(id))?;
+            }
+        }
+
+        result
+    }
+
+    /**
+     * Tell the player's friends where it is now, or that it went offline.
+     */
+    pub(super) fn publish_presence(&self, key: &str) {
+        if let Err(e) = presence::publish(&self.directory, self.pool.as_ref(), key) {
+            eprintln!("presence of {} not published for {e}", key);
+        }
+    }
+
+    /**
+     * Load everything the player needs from storage before touching the map,
+     * so a failed lookup leaves nothing behind.
+     */
+    fn accept(
+        &mut self,
+        stream: Stream,
+        id: &str,
+        position: Option<Vector3>,
+        capabilities: Capabilities,
+    ) -> Result<(), Box<dyn Error>> {
+        let position = position
+            .filter(|position| self.map.contains_key(position))
+            .unwrap_or(self.spawn);
+
+        if !self.map.contains_key(&position) {
+            return Err("wrong position".into());
+        }
+
+        let addr = stream.peer_addr()?;
+
+        let inventory = self.pool.find_inventory(id)?;
+
+        let inventory_packet = inventory::inventory_packet(&inventory);
+
+        let wallet = self.pool.find_wallet(id)?;
+
+        let journal: Journal = self.pool.find_journal(id)?;
+
+        let profile = self.pool.find_profile(id)?;
+
+        let mut health = Health::new(100, 0);
+
+        if let Some(state) = self.pool.find_state(id)? {
+            if state.health > 0 {
+                health.current = state.health.min(health.max);
+            }
+        }
+
+        println!("{:?} accepted by {}", addr, self.id);
+
+        let wallet_packet = packet::Outgoing::Wallet {
+            coins: wallet.coins,
+        };
+
+        let person = Actor::new(id.to_owned())
+            .with(Movable::new())
+            .with(health)
+            .with(Attack::new(10, ATTACK_COOLDOWN))
+            .with(inventory)
+            .with(wallet)
+            .with(journal)
+            .with(profile);
+
+        let tile = self.map.get_mut(&position).unwrap();
+
+        tile.actors.insert(id.to_owned(), person);
+
+        self.positions.insert(id.to_owned(), position.to_owned());
+
+        self.streams.insert(id.to_owned(), stream);
+
+        self.capabilities.insert(id.to_owned(), capabilities);
+
+        let limiter = Limiter::new(&self.limits, time::Instant::now());
+
+        self.limiters.insert(id.to_owned(), limiter);
+
+        self.mark_dirty(id);
+
+        self.directory.online(id, &self.id);
+
+        self.publish_presence(id);
+
+        let users = self
+            .positions
+            .keys()
+            .map(|key| self.snapshot(key))
+            .collect::<Result<_, _>>()?;
+
+        let packet = packet::Outgoing::Hello {
+            id: id.to_owned(),
+            map_id: self.model_id.to_owned(),
+            actors: users,
+        };
+
+        let schedule = Schedule::instant(Job::Write(id.to_owned(), packet));
+
+        self.schedule_queue.push(schedule);
+
+        let schedule = Schedule::instant(Job::Write(id.to_owned(), inventory_packet));
+
+        self.schedule_queue.push(schedule);
+
+        let schedule = Schedule::instant(Job::Write(id.to_owned(), wallet_packet));
+
+        self.schedule_queue.push(schedule);
+
+        self.show_profile(id);
+
+        self.share_position(id);
+
+        if let Some(party) = self.parties.find(id) {
+            let packet = packet::Outgoing::Party {
+                members: party.members,
+            };
+
+            self.schedule_queue
+                .push(Schedule::instant(Job::Write(id.to_owned(), packet)));
+        }
+
+        self.greet_friends(id)?;
+
+        self.open_journal(id)?;
+
+        for (tile_position, tile) in &self.map {
+            if !is_near(&position, tile_position) {
+                continue;
+            }
+
+            if !tile.items.is_empty() {
+                let packet = packet::Outgoing::TileItems {
+                    position: tile_position.to_owned(),
+                    items: tile.items.to_owned(),
+                };
+
+                let schedule = Schedule::instant(Job::Write(id.to_owned(), packet));
+
+                self.schedule_queue.push(schedule);
+            }
+
+            if let Some(object) = &tile.object {
+                let packet = interaction::object_packet(tile_position, object);
+
+                let schedule = Schedule::instant(Job::Write(id.to_owned(), packet));
+
+                self.schedule_queue.push(schedule);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use std::error::Error;
+
+use crate::{
+    map::Job,
+    net::{packet, protocol},
+    schedule::Schedule,
+};
+
+use super::Worker;
+
+impl Worker {
+    pub(super) fn handle_friend_request(
+        &mut self,
+        key: String,
+        user_id: String,
+    ) -> Result<(), Box<dyn Error>> {
+        if key == user_id || !self.pool.has_user(&user_id)? {
+            self.refuse(&key, protocol::ERROR_FRIEND, String::from("no such user"));
+
+            return Ok(());
+        }
+
+        if self.pool.find_friends(&key)?.contains(&user_id) {
+            let message = format!("already friends with {}", user_id);
+
+            self.refuse(&key, protocol::ERROR_FRIEND, message);
+
+            return Ok(());
+        }
+
+        self.pool.add_friend_request(&key, &user_id)?;
+
+        self.tell(&user_id, packet::Outgoing::FriendRequest { id: key });
+
+        Ok(())
+    }
+
+    pub(super) fn handle_friend_accept(
+        &mut self,
+        key: String,
+        user_id: String,
+    ) -> Result<(), Box<dyn Error>> {
+        if !self.pool.accept_friend_request(&key, &user_id)? {
+            let message = format!("no request from {}", user_id);
+
+            self.refuse(&key, protocol::ERROR_FRIEND, message);
+
+            return Ok(());
+        }
+
+        let packet = packet::Outgoing::Presence {
+            id: key.to_owned(),
+            map_id: self.directory.map_of(&key),
+        };
+
+        self.tell(&user_id, packet);
+
+        let packet = packet::Outgoing::Presence {
+            map_id: self.directory.map_of(&user_id),
+            id: user_id,
+        };
+
+        self.tell(&key, packet);
+
+        Ok(())
+    }
+
+    /**
+     * Show a player who entered the map its pending requests and the
+     * friends that are online.
+     */
+    pub(super) fn greet_friends(&mut self, key: &str) -> Result<(), Box<dyn Error>> {
+        for user_id in self.pool.find_friend_requests(key)? {
+            let packet = packet::Outgoing::FriendRequest { id: user_id };
+
+            self.schedule_queue
+                .push(Schedule::instant(Job::Write(key.to_owned(), packet)));
+        }
+
+        for user_id in self.pool.find_friends(key)? {
+            if let Some(map_id) = self.directory.map_of(&user_id) {
+                let packet = packet::Outgoing::Presence {
+                    id: user_id,
+                    map_id: Some(map_id),
+                };
+
+                self.schedule_queue
+                    .push(Schedule::instant(Job::Write(key.to_owned(), packet)));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+use std::error::Error;
+
+use east_online_core::model::Vector3;
+
+use crate::{
+    combat,
+    item::{self, Inventory},
+    map::{Job, Movable, Object, ObjectKind, Tile},
+    net::{packet, protocol},
+    schedule::Schedule,
+};
+
+use super::{movement::get_adjacent, Worker};
+
+impl Worker {
+    /**
+     * Use the object on the tile in front of an actor.
+     *
+     * Doors open and close, switches flip along with the objects they
+     * target, chests hand their loot over once and portals send the actor
+     * away with its party.
+     */
+    pub(super) fn handle_interact(&mut self, key: String) -> Result<(), Box<dyn Error>> {
+        let position = self.get_position(&key)?;
+
+        let actor = self.get_actor(&key)?;
+
+        if combat::is_dead(actor) {
+            return Ok(());
+        }
+
+        let facing = actor
+            .get::<Movable>()
+            .map(|movable| movable.facing)
+            .ok_or("not movable")?;
+
+        let target = match get_adjacent(&position, facing) {
+            Some(target) => target,
+            None => return Ok(()),
+        };
+
+        let tile = match self.map.get_mut(&target) {
+            Some(tile) => tile,
+            None => return Ok(()),
+        };
+
+        let destination = match &tile.object {
+            Some(object) if object.kind == ObjectKind::Portal => object.destination.to_owned(),
+            _ => None,
+        };
+
+        if let Some(destination) = destination {
+            if let Err(message) = self.transfer(vec![key.to_owned()], destination) {
+                self.refuse(&key, protocol::ERROR_PARTY, message);
+            }
+
+            return Ok(());
+        }
+
+        if !toggle(tile) {
+            return Ok(());
+        }
+
+        let object = tile.object.as_mut().ok_or("no object")?;
+
+        let loot = match object.kind {
+            ObjectKind::Chest => std::mem::take(&mut object.loot),
+            _ => vec![],
+        };
+
+        let targets = match object.kind {
+            ObjectKind::Switch => object.targets.to_owned(),
+            _ => vec![],
+        };
+
+        self.commit_object(&target)?;
+
+        for object_id in targets {
+            self.toggle_target(&object_id)?;
+        }
+
+        if loot.is_empty() {
+            return Ok(());
+        }
+
+        self.take_loot(key, loot)
+    }
+
+    fn toggle_target(&mut self, object_id: &str) -> Result<(), Box<dyn Error>> {
+        let position = self
+            .map
+            .iter()
+            .find(|(_, tile)| {
+                tile.object
+                    .as_ref()
+                    .is_some_and(|object| object.id == object_id)
+            })
+            .map(|(position, _)| position.to_owned());
+
+        let position = match position {
+            Some(position) => position,
+            None => return Ok(()),
+        };
+
+        if toggle(self.map.get_mut(&position).ok_or("no tile")?) {
+            self.commit_object(&position)?;
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Save the state of an object where needed and show it to the players
+     * nearby.
+     */
+    fn commit_object(&mut self, position: &Vector3) -> Result<(), Box<dyn Error>> {
+        let object = self
+            .map
+            .get(position)
+            .and_then(|tile| tile.object.as_ref())
+            .ok_or("no object")?;
+
+        if object.persistent {
+            self.pool
+                .save_object_state(&self.id, &object.id, object.open)?;
+        }
+
+        let packet = object_packet(position, object);
+
+        self.schedule_queue
+            .push(Schedule::instant(Job::BroadcastNear(
+                position.to_owned(),
+                packet,
+            )));
+
+        Ok(())
+    }
+
+    /**
+     * Put the loot of a chest into the inventory of a player.
+     *
+     * Whatever doesn't fit falls on the tile the player stands on.
+     */
+    fn take_loot(&mut self, key: String, loot: Vec<item::Stack>) -> Result<(), Box<dyn Error>> {
+        let items = self.items.clone();
+
+        let position = self.get_position(&key)?;
+
+        let inventory = self
+            .get_actor_mut(&key)?
+            .get_mut::<Inventory>()
+            .ok_or("no inventory")?;
+
+        let left: Vec<item::Stack> = loot
+            .into_iter()
+            .filter(|stack| inventory.add(stack.to_owned(), &items).is_err())
+            .collect();
+
+        if !left.is_empty() {
+            let tile = self.map.get_mut(&position).ok_or("no tile")?;
+
+            tile.items.extend(left);
+
+            let packet = packet::Outgoing::TileItems {
+                position: position.to_owned(),
+                items: tile.items.to_owned(),
+            };
+
+            self.schedule_queue
+                .push(Schedule::instant(Job::BroadcastNear(position, packet)));
+        }
+
+        self.commit_inventory(key)
+    }
+}
+
+/**
+ * Toggle the object on a tile, keeping a door open while someone stands in
+ * it.
+ *
+ * Return whether the object changed.
+ */
+fn toggle(tile: &mut Tile) -> bool {
+    let object = match tile.object.as_mut() {
+        Some(object) => object,
+        None => return false,
+    };
+
+    if object.kind == ObjectKind::Door && object.open && !tile.actors.is_empty() {
+        return false;
+    }
+
+    object.toggle()
+}
+
+pub(super) fn object_packet(position: &Vector3, object: &Object) -> packet::Outgoing {
+    packet::Outgoing::Object {
+        id: object.id.to_owned(),
+        position: position.to_owned(),
+        kind: object.kind,
+        open: object.open,
+    }
+}
@@ -0,0 +1,179 @@
+use std::error::Error;
+
+use crate::{
+    item::{self, Inventory},
+    map::Job,
+    net::packet,
+    schedule::Schedule,
+};
+
+use super::Worker;
+
+impl Worker {
+    pub(super) fn handle_inventory(&mut self, key: String) -> Result<(), Box<dyn Error>> {
+        let inventory = self
+            .get_actor(&key)?
+            .get::<Inventory>()
+            .ok_or("no inventory")?;
+
+        let packet = inventory_packet(inventory);
+
+        self.schedule_queue
+            .push(Schedule::instant(Job::Write(key, packet)));
+
+        Ok(())
+    }
+
+    pub(super) fn handle_move_item(
+        &mut self,
+        key: String,
+        from: u8,
+        to: u8,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.refuse_while_trading(&key) {
+            return self.commit_inventory(key);
+        }
+
+        let items = self.items.clone();
+
+        let inventory = self
+            .get_actor_mut(&key)?
+            .get_mut::<Inventory>()
+            .ok_or("no inventory")?;
+
+        if let Err(e) = inventory.move_slot(usize::from(from), usize::from(to), &items) {
+            println!("{} failed to move item for {e}", key);
+        }
+
+        self.commit_inventory(key)
+    }
+
+    pub(super) fn handle_use_item(&mut self, key: String, slot: u8) -> Result<(), Box<dyn Error>> {
+        if self.refuse_while_trading(&key) {
+            return self.commit_inventory(key);
+        }
+
+        let items = self.items.clone();
+
+        let position = self.get_position(&key)?;
+
+        let inventory = self
+            .get_actor_mut(&key)?
+            .get_mut::<Inventory>()
+            .ok_or("no inventory")?;
+
+        let is_consumable = inventory
+            .get(usize::from(slot))
+            .and_then(|stack| items.get(&stack.item_id))
+            .map(|definition| definition.consumable)
+            .unwrap_or(false);
+
+        if !is_consumable {
+            return self.commit_inventory(key);
+        }
+
+        let stack = inventory.take(usize::from(slot), 1)?;
+
+        let packet = packet::Outgoing::UseItem {
+            id: key.to_owned(),
+            item_id: stack.item_id,
+        };
+
+        self.schedule_queue
+            .push(Schedule::instant(Job::BroadcastNear(position, packet)));
+
+        self.commit_inventory(key)
+    }
+
+    pub(super) fn handle_drop_item(
+        &mut self,
+        key: String,
+        slot: u8,
+        quantity: u16,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.refuse_while_trading(&key) {
+            return self.commit_inventory(key);
+        }
+
+        let position = self.get_position(&key)?;
+
+        let inventory = self
+            .get_actor_mut(&key)?
+            .get_mut::<Inventory>()
+            .ok_or("no inventory")?;
+
+        let stack = match inventory.take(usize::from(slot), quantity) {
+            Ok(stack) => stack,
+            Err(e) => {
+                println!("{} failed to drop item for {e}", key);
+
+                return self.commit_inventory(key);
+            }
+        };
+
+        let tile = self.map.get_mut(&position).ok_or("no tile")?;
+
+        let mut left = stack.quantity;
+
+        for item in tile
+            .items
+            .iter_mut()
+            .filter(|item| item.item_id == stack.item_id)
+        {
+            let added = left.min(u16::MAX - item.quantity);
+
+            item.quantity += added;
+
+            left -= added;
+        }
+
+        if left > 0 {
+            tile.items.push(item::Stack::new(stack.item_id, left));
+        }
+
+        let packet = packet::Outgoing::TileItems {
+            position: position.to_owned(),
+            items: tile.items.to_owned(),
+        };
+
+        self.schedule_queue
+            .push(Schedule::instant(Job::BroadcastNear(position, packet)));
+
+        self.commit_inventory(key)
+    }
+
+    /**
+     * Persist the inventory of an actor and send it back to the owner.
+     *
+     * The owner always gets the latest state, so a rejected request
+     * doesn't leave the client out of sync.
+     */
+    pub(super) fn commit_inventory(&mut self, key: String) -> Result<(), Box<dyn Error>> {
+        let inventory = self
+            .get_actor(&key)?
+            .get::<Inventory>()
+            .ok_or("no inventory")?;
+
+        let packet = inventory_packet(inventory);
+
+        self.pool.save_inventory(&key, inventory)?;
+
+        self.track_inventory(&key)?;
+
+        self.schedule_queue
+            .push(Schedule::instant(Job::Write(key, packet)));
+
+        Ok(())
+    }
+}
+
+pub(super) fn inventory_packet(inventory: &Inventory) -> packet::Outgoing {
+    let slots = inventory
+        .slots()
+        .iter()
+        .enumerate()
+        .filter_map(|(slot, stack)| stack.as_ref().map(|stack| (slot as u8, stack.to_owned())))
+        .collect();
+
+    packet::Outgoing::Inventory { slots }
+}
@@ -0,0 +1,718 @@
+use east_online_core::model::{self, Vector3};
+use tokio::{sync::mpsc, time};
+
+use crate::{
+    combat::{Attack, Health, ATTACK_COOLDOWN},
+    db::Storage,
+    item,
+    limit::{Limiter, Limits, Verdict},
+    map::{
+        Actor, Command, Event, Movable, NpcManifest, Object, ObjectDefinition, ObjectKind, Profile,
+        ProfileChange, Saver, Species,
+    },
+    net::{
+        io::{get_packet_buf_for, Reader},
+        packet,
+        protocol::{Capabilities, PROTOCOL_VERSION},
+        Stream,
+    },
+    party::Parties,
+    presence::Directory,
+    quest,
+    schedule::Schedule,
+    selector::{ScheduleQueue, Waitings},
+    shop::{self, Shop},
+    trade::Session,
+};
+
+use super::{Job, Tile};
+use std::{
+    collections::{hash_map::Entry, BinaryHeap, HashMap, HashSet},
+    error::Error,
+    io,
+    sync::Arc,
+};
+
+mod autosave;
+
+mod combat;
+
+mod friend;
+
+mod command;
+
+mod emote;
+
+mod entry;
+
+mod interaction;
+
+mod inventory;
+
+mod movement;
+
+mod party;
+
+mod profile;
+
+mod progress;
+
+mod reload;
+
+mod shop_keeper;
+
+mod trade;
+
+type Sender = mpsc::UnboundedSender<Event>;
+
+type Receiver = mpsc::Receiver<(Stream, String, Option<Vector3>, Capabilities)>;
+
+const VIEW_DISTANCE: i32 = 12;
+
+pub struct Worker {
+    id: String,
+    name: String,
+    model_id: String,
+    map: HashMap<Vector3, Tile>,
+    items: Arc<item::Registry>,
+    channel: (Sender, Receiver),
+    pool: Arc<dyn Storage>,
+    spawn: Vector3,
+    positions: HashMap<String, Vector3>,
+    /**
+     * Where NPCs were placed, to come back to after dying.
+     */
+    homes: HashMap<String, Vector3>,
+    streams: HashMap<String, Stream>,
+    capabilities: HashMap<String, Capabilities>,
+    limits: Limits,
+    limiters: HashMap<String, Limiter>,
+    paused: HashSet<String>,
+    emoting: HashSet<String>,
+    control: (mpsc::Sender<Command>, mpsc::Receiver<Command>),
+    parties: Arc<Parties>,
+    directory: Arc<Directory>,
+    quests: Arc<quest::Registry>,
+    trade_requests: HashMap<String, HashSet<String>>,
+    trades: HashMap<String, Session>,
+    trading: HashMap<String, String>,
+    dirty: HashSet<String>,
+    saver: Saver,
+    schedule_queue: BinaryHeap<Schedule<Job>>,
+}
+
+impl Worker {
+    pub fn new(
+        id: String,
+        name: String,
+        map: HashMap<Vector3, Tile>,
+        items: Arc<item::Registry>,
+        db: Arc<dyn Storage>,
+        channel: (Sender, Receiver),
+    ) -> Self {
+        let saver = Saver::new(id.to_owned(), db.clone());
+
+        Worker {
+            model_id: id.to_owned(),
+            id,
+            name,
+            map,
+            items,
+            channel,
+            pool: db,
+            spawn: Vector3 { x: 0, y: 0, z: 0 },
+            positions: HashMap::new(),
+            homes: HashMap::new(),
+            streams: HashMap::new(),
+            capabilities: HashMap::new(),
+            limits: Limits::default(),
+            limiters: HashMap::new(),
+            paused: HashSet::new(),
+            emoting: HashSet::new(),
+            control: mpsc::channel(16),
+            parties: Arc::new(Parties::new()),
+            directory: Arc::new(Directory::new()),
+            quests: Arc::new(quest::Registry::new()),
+            trade_requests: HashMap::new(),
+            trades: HashMap::new(),
+            trading: HashMap::new(),
+            dirty: HashSet::new(),
+            saver,
+            schedule_queue: ScheduleQueue::new(),
+        }
+    }
+
+    pub fn from_map(
+        map: model::Map,
+        items: Arc<item::Registry>,
+        db: Arc<dyn Storage>,
+        channel: (Sender, Receiver),
+    ) -> Self {
+        let tiles = map
+            .tiles
+            .into_iter()
+            .map(|(position, placable)| (position, Tile::from_placable(placable)))
+            .collect();
+
+        Worker::new(map.id, map.name, tiles, items, db, channel)
+    }
+
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /**
+     * Get a sender for commands to this map.
+     */
+    pub fn control(&self) -> mpsc::Sender<Command> {
+        self.control.0.clone()
+    }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    pub fn set_parties(&mut self, parties: Arc<Parties>) {
+        self.parties = parties;
+    }
+
+    /**
+     * Share the players online with other workers, letting them reach the
+     * ones here.
+     */
+    pub fn set_directory(&mut self, directory: Arc<Directory>) {
+        directory.add_map(&self.id, self.control());
+
+        self.directory = directory;
+    }
+
+    pub fn set_quests(&mut self, quests: Arc<quest::Registry>) {
+        self.quests = quests;
+    }
+
+    /**
+     * Tell clients to show another map, for instances of a template.
+     */
+    pub fn set_model_id(&mut self, model_id: String) {
+        self.model_id = model_id;
+    }
+
+    /**
+     * Place an actor that isn't backed by a stream, such as an NPC.
+     *
+     * It shares every job with the players except reading and writing.
+     */
+    pub fn add_npc(&mut self, actor: Actor, position: Vector3) -> Result<(), Box<dyn Error>> {
+        let tile = self.map.get_mut(&position).ok_or("wrong position")?;
+
+        self.positions.insert(actor.id.to_owned(), position);
+
+        tile.actors.insert(actor.id.to_owned(), actor);
+
+        Ok(())
+    }
+
+    /**
+     * Place a shopkeeper on its tile.
+     */
+    pub fn add_shop(&mut self, definition: shop::Definition) -> Result<(), Box<dyn Error>> {
+        if definition.id.len() != packet::ID_LENGTH {
+            return Err(format!("shop id {} is malformed", definition.id).into());
+        }
+
+        let mut profile = Profile::default();
+
+        profile
+            .apply(ProfileChange::Name(definition.name))
+            .map_err(|e| format!("shop {} is misnamed, {e}", definition.id))?;
+
+        let actor = Actor::new(definition.id)
+            .with(Shop::new(definition.goods))
+            .with(profile);
+
+        self.add_npc(actor, definition.position)
+    }
+
+    /**
+     * Set where players enter the map and place the NPCs fighting on it.
+     *
+     * NPCs with power start attacking right away.
+     */
+    pub fn add_npcs(&mut self, manifest: NpcManifest) -> Result<(), Box<dyn Error>> {
+        if let Some(spawn) = manifest.spawn {
+            if !self.map.contains_key(&spawn) {
+                return Err("no spawn tile".into());
+            }
+
+            self.spawn = spawn;
+        }
+
+        for definition in manifest.npcs {
+            if definition.id.len() != packet::ID_LENGTH {
+                return Err(format!("npc id {} is malformed", definition.id).into());
+            }
+
+            let mut profile = Profile::default();
+
+            profile
+                .apply(ProfileChange::Name(definition.name))
+                .map_err(|e| format!("npc {} is misnamed, {e}", definition.id))?;
+
+            let movable = Movable {
+                facing: definition.facing,
+                ..Movable::new()
+            };
+
+            let mut actor = Actor::new(definition.id.to_owned())
+                .with(movable)
+                .with(Health::new(definition.health, definition.defense))
+                .with(Attack::new(definition.power, ATTACK_COOLDOWN))
+                .with(profile);
+
+            if let Some(species) = definition.species {
+                actor = actor.with(Species::new(species));
+            }
+
+            self.add_npc(actor, definition.position.to_owned())?;
+
+            self.homes
+                .insert(definition.id.to_owned(), definition.position);
+
+            if definition.power > 0 {
+                self.schedule_queue
+                    .push(Schedule::instant(Job::Attack(definition.id)));
+            }
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Place objects on their tiles, in the state they were last saved in.
+     *
+     * Switches can't target chests, as a chest opened that way would have
+     * nobody to hand its loot to, and loot must be registered items.
+     */
+    pub fn add_objects(
+        &mut self,
+        definitions: Vec<ObjectDefinition>,
+    ) -> Result<(), Box<dyn Error>> {
+        if definitions.is_empty() {
+            return Ok(());
+        }
+
+        let chests: HashSet<&str> = definitions
+            .iter()
+            .filter(|definition| definition.kind == ObjectKind::Chest)
+            .map(|definition| definition.id.as_str())
+            .collect();
+
+        let switch = definitions.iter().find(|definition| {
+            definition
+                .targets
+                .iter()
+                .any(|target| chests.contains(target.as_str()))
+        });
+
+        if let Some(switch) = switch {
+            return Err(format!("switch {} targets a chest", switch.id).into());
+        }
+
+        let states = self.pool.find_object_states(&self.id)?;
+
+        for definition in definitions {
+            if definition.kind == ObjectKind::Portal && definition.destination.is_none() {
+                return Err(format!("portal {} leads nowhere", definition.id).into());
+            }
+
+            let unknown = definition
+                .loot
+                .iter()
+                .find(|stack| !self.items.contains_key(&stack.item_id));
+
+            if let Some(stack) = unknown {
+                return Err(
+                    format!("{} holds unknown item {}", definition.id, stack.item_id).into(),
+                );
+            }
+
+            let tile = self
+                .map
+                .get_mut(&definition.position)
+                .ok_or("wrong position")?;
+
+            let mut object = Object::from_definition(definition);
+
+            if let Some(open) = states.get(&object.id).filter(|_| object.persistent) {
+                object.open = *open;
+            }
+
+            tile.object = Some(object);
+        }
+
+        Ok(())
+    }
+
+    pub async fn run(mut self) -> Result<(), Box<dyn Error>> {
+        self.saver.start();
+
+        self.schedule_autosave();
+
+        loop {
+            let job = self.select_job().await;
+
+            if let Job::Close = job {
+                self.directory.remove_map(&self.id);
+
+                println!("{} closed", self.id);
+
+                return Ok(());
+            }
+
+            if let Err(e) = self.handle_job(job).await {
+                eprintln!("{e}");
+            }
+        }
+    }
+
+    async fn select_job(&mut self) -> Job {
+        if self.schedule_queue.is_first_urgent() {
+            return self.schedule_queue.pop().unwrap().job;
+        }
+
+        tokio::select! {
+            received = self.channel.1.recv() => match received {
+                Some((stream, id, position, capabilities)) => {
+                    Job::Accept(stream, id, position, capabilities)
+                }
+                None => Job::Close,
+            },
+            Ok(index) = self.streams.wait_for_readable(&self.paused) => {
+                Job::Readable(index)
+            }
+            Some(command) = self.control.1.recv() => {
+                Job::Command(command)
+            }
+            Ok(_) = self.schedule_queue.wait_for_first() => {
+                self.schedule_queue.pop().unwrap().job
+            },
+        }
+    }
+
+    /**
+     * Handle a scheduled job.
+     *
+     * Throw an error if something went wrong with itself.
+     */
+    async fn handle_job(&mut self, job: Job) -> Result<(), Box<dyn Error>> {
+        match job {
+            Job::Accept(stream, id, position, capabilities) => {
+                self.handle_accept(stream, id, position, capabilities)
+            }
+            Job::Drop(key, reason) => {
+                if let Some(stream) = self.streams.remove(&key) {
+                    self.capabilities.remove(&key);
+
+                    self.limiters.remove(&key);
+
+                    self.paused.remove(&key);
+
+                    self.flush_state(&key);
+
+                    if let Some(position) = self.positions.remove(&key) {
+                        if let Some(tile) = self.map.get_mut(&position) {
+                            tile.actors.remove(&key);
+                        }
+                    }
+
+                    self.leave_trade(&key);
+
+                    self.leave_party(&key);
+
+                    self.parties.unlocate(&key);
+
+                    self.directory.offline(&key);
+
+                    self.publish_presence(&key);
+
+                    self.channel.0.send(Event::Left(key))?;
+
+                    let addr = stream.peer_addr()?;
+
+                    println!("{:?} dropped for {}", addr, reason);
+
+                    Ok(())
+                } else {
+                    Err("drop failed".into())
+                }
+            }
+            Job::Readable(key) => {
+                let stream = self.streams.get(&key).ok_or("stream not found")?;
+
+                let schedule = match stream.try_read_packet() {
+                    Ok(packet) => match self.limit(&key, &packet) {
+                        Verdict::Pass => Schedule::instant(Job::Incoming(key, packet)),
+                        Verdict::Drop => return Ok(()),
+                        Verdict::Throttle(wait) => {
+                            let deadline = time::Instant::now() + wait;
+
+                            self.paused.insert(key.to_owned());
+
+                            let job = Job::Resume(key.to_owned());
+
+                            self.schedule_queue.push(Schedule::new(job, deadline));
+
+                            Schedule::new(Job::Incoming(key, packet), deadline)
+                        }
+                        Verdict::Disconnect(reason) => Schedule::instant(Job::Drop(key, reason)),
+                    },
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                    Err(e) => Schedule::instant(Job::Drop(key, format!("{e}"))),
+                };
+
+                self.schedule_queue.push(schedule);
+
+                Ok(())
+            }
+            Job::Incoming(key, packet) => {
+                if let Err(e) = self.handle_packet(key.to_owned(), packet).await {
+                    let schedule = Schedule::instant(Job::Drop(key, format!("{e}")));
+
+                    self.schedule_queue.push(schedule);
+                }
+
+                Ok(())
+            }
+            Job::Write(key, packet) => {
+                if let Some(stream) = self.streams.get(&key) {
+                    if !self.supports(&key, &packet) {
+                        return Ok(());
+                    }
+
+                    let buf = get_packet_buf_for(packet, self.version(&key))?;
+
+                    match stream.try_write(&buf) {
+                        Ok(_) => {}
+                        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                        Err(e) => {
+                            let job = Job::Drop(key.to_owned(), format!("{e}"));
+
+                            let schedule = Schedule::instant(job);
+
+                            self.schedule_queue.push(schedule);
+                        }
+                    }
+                }
+
+                Ok(())
+            }
+            Job::Broadcast(packet) => {
+                let mut bufs = HashMap::new();
+
+                for (key, stream) in &self.streams {
+                    if !self.supports(key, &packet) {
+                        continue;
+                    }
+
+                    let buf = frame(&mut bufs, &packet, self.version(key))?;
+
+                    match stream.try_write(buf) {
+                        Ok(_) => {
+                            continue;
+                        }
+                        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                            continue;
+                        }
+                        Err(e) => {
+                            let job = Job::Drop(key.to_owned(), format!("{e}"));
+
+                            let schedule = Schedule::instant(job);
+
+                            self.schedule_queue.push(schedule);
+                        }
+                    }
+                }
+
+                Ok(())
+            }
+            Job::BroadcastNear(origin, packet) => {
+                let mut bufs = HashMap::new();
+
+                for (key, stream) in &self.streams {
+                    match self.positions.get(key) {
+                        Some(position) if is_near(&origin, position) => {}
+                        _ => continue,
+                    }
+
+                    if !self.supports(key, &packet) {
+                        continue;
+                    }
+
+                    let buf = frame(&mut bufs, &packet, self.version(key))?;
+
+                    match stream.try_write(buf) {
+                        Ok(_) => {
+                            continue;
+                        }
+                        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                            continue;
+                        }
+                        Err(e) => {
+                            let job = Job::Drop(key.to_owned(), format!("{e}"));
+
+                            let schedule = Schedule::instant(job);
+
+                            self.schedule_queue.push(schedule);
+                        }
+                    }
+                }
+
+                Ok(())
+            }
+            Job::Move(key, duration) => self.handle_move(key, duration),
+            Job::Respawn(key) => self.handle_respawn(key),
+            Job::Attack(key) => self.handle_npc_attack(key),
+            Job::Resume(key) => {
+                self.paused.remove(&key);
+
+                Ok(())
+            }
+            Job::EmoteReady(key) => {
+                self.emoting.remove(&key);
+
+                Ok(())
+            }
+            Job::Autosave => self.handle_autosave(),
+            Job::Command(command) => self.handle_command(command).await,
+            Job::Close => Ok(()),
+        }
+    }
+
+    /**
+     * Handle a incoming packet from a stream.
+     *
+     * Throw an error if something went wrong with the stream.
+     * The stream is going to be dropped immediately.
+     */
+    async fn handle_packet(
+        &mut self,
+        key: String,
+        packet: packet::Incoming,
+    ) -> Result<(), Box<dyn Error>> {
+        match packet {
+            packet::Incoming::Move { direction } => self.handle_direction(key, direction),
+            packet::Incoming::Inventory => self.handle_inventory(key),
+            packet::Incoming::MoveItem { from, to } => self.handle_move_item(key, from, to),
+            packet::Incoming::UseItem { slot } => self.handle_use_item(key, slot),
+            packet::Incoming::DropItem { slot, quantity } => {
+                self.handle_drop_item(key, slot, quantity)
+            }
+            packet::Incoming::Attack => self.handle_attack(key),
+            packet::Incoming::PartyInvite { user_id } => self.handle_party_invite(key, user_id),
+            packet::Incoming::PartyAccept { user_id } => self.handle_party_accept(key, user_id),
+            packet::Incoming::PartyLeave => self.handle_party_leave(key),
+            packet::Incoming::PartyKick { user_id } => self.handle_party_kick(key, user_id),
+            packet::Incoming::FriendRequest { user_id } => self.handle_friend_request(key, user_id),
+            packet::Incoming::FriendAccept { user_id } => self.handle_friend_accept(key, user_id),
+            packet::Incoming::TradeRequest { user_id } => self.handle_trade_request(key, user_id),
+            packet::Incoming::TradeAccept { user_id } => self.handle_trade_accept(key, user_id),
+            packet::Incoming::TradeOffer { slots, coins } => {
+                self.handle_trade_offer(key, slots, coins)
+            }
+            packet::Incoming::TradeLock => self.handle_trade_lock(key),
+            packet::Incoming::TradeConfirm => self.handle_trade_confirm(key),
+            packet::Incoming::TradeCancel => self.handle_trade_cancel(key),
+            packet::Incoming::ShopOpen { shop_id } => self.handle_shop_open(key, shop_id),
+            packet::Incoming::ShopBuy {
+                shop_id,
+                item_id,
+                quantity,
+            } => self.handle_shop_buy(key, shop_id, item_id, quantity),
+            packet::Incoming::ShopSell {
+                shop_id,
+                slot,
+                quantity,
+            } => self.handle_shop_sell(key, shop_id, slot, quantity),
+            packet::Incoming::Talk { npc_id } => self.handle_talk(key, npc_id),
+            packet::Incoming::Interact => self.handle_interact(key),
+            packet::Incoming::Emote { emote } => self.handle_emote(key, emote),
+            _ => Ok(()),
+        }
+    }
+
+    fn limit(&mut self, key: &str, packet: &packet::Incoming) -> Verdict {
+        match self.limiters.get_mut(key) {
+            Some(limiter) => limiter.check(packet.kind(), &self.limits, time::Instant::now()),
+            None => Verdict::Pass,
+        }
+    }
+
+    /**
+     * Check if the client behind a stream understands a packet.
+     */
+    fn supports(&self, key: &str, packet: &packet::Outgoing) -> bool {
+        self.capabilities
+            .get(key)
+            .is_some_and(|capabilities| capabilities.supports(packet))
+    }
+
+    /**
+     * Get the protocol version the client behind a stream speaks.
+     */
+    fn version(&self, key: &str) -> u16 {
+        self.capabilities
+            .get(key)
+            .map_or(PROTOCOL_VERSION, |capabilities| capabilities.version)
+    }
+
+    fn get_position(&self, key: &str) -> Result<Vector3, Box<dyn Error>> {
+        let position = self.positions.get(key).ok_or("no position")?;
+
+        Ok(position.to_owned())
+    }
+
+    fn get_actor(&self, key: &str) -> Result<&Actor, Box<dyn Error>> {
+        let position = self.positions.get(key).ok_or("no position")?;
+
+        let tile = self.map.get(position).ok_or("no tile")?;
+
+        Ok(tile.actors.get(key).ok_or("no actor")?)
+    }
+
+    fn get_actor_mut(&mut self, key: &str) -> Result<&mut Actor, Box<dyn Error>> {
+        let position = self.positions.get(key).ok_or("no position")?;
+
+        let tile = self.map.get_mut(position).ok_or("no tile")?;
+
+        Ok(tile.actors.get_mut(key).ok_or("no actor")?)
+    }
+}
+
+fn is_near(a: &Vector3, b: &Vector3) -> bool {
+    (a.x - b.x).abs() <= VIEW_DISTANCE && (a.z - b.z).abs() <= VIEW_DISTANCE
+}
+
+/**
+ * Check if two positions are next to each other on the same level.
+ */
+fn is_adjacent(a: &Vector3, b: &Vector3) -> bool {
+    a.y == b.y && (a.x - b.x).abs() + (a.z - b.z).abs() == 1
+}
+
+/**
+ * Frame a packet once for each version of the protocol it's sent in.
+ */
+fn frame<'a>(
+    bufs: &'a mut HashMap<u16, Vec<u8>>,
+    packet: &packet::Outgoing,
+    version: u16,
+) -> Result<&'a [u8], Box<dyn Error>> {
+    match bufs.entry(version) {
+        Entry::Occupied(entry) => Ok(entry.into_mut()),
+        Entry::Vacant(entry) => Ok(entry.insert(get_packet_buf_for(packet.to_owned(), version)?)),
+    }
+}
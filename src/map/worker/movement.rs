@@ -0,0 +1,252 @@
+use std::{collections::HashMap, error::Error};
+
+use east_online_core::model::{Direction, Vector3};
+use tokio::time;
+
+use crate::{
+    combat,
+    map::{Job, Kind, Movable, Tile},
+    net::packet,
+    quest::Trigger,
+    schedule::Schedule,
+};
+
+use super::Worker;
+
+impl Worker {
+    /**
+     * Step an actor toward its direction and schedule the next step.
+     *
+     * The actor stops when it turns idle, dies or runs into the end of the map
+     * or a cliff.
+     */
+    pub(super) fn handle_move(
+        &mut self,
+        key: String,
+        duration: time::Duration,
+    ) -> Result<(), Box<dyn Error>> {
+        let position = self.positions.get_mut(&key).ok_or("no position")?;
+
+        let next = {
+            let current_tile = self.map.get(position).ok_or("no tile")?;
+
+            let actor = current_tile.actors.get(&key).ok_or("no actor")?;
+
+            let movable = actor.get::<Movable>().ok_or("not movable")?;
+
+            match get_destination(&self.map, position, movable.direction) {
+                Some(next) if !combat::is_dead(actor) => next,
+                _ => {
+                    let packet = packet::Outgoing::Stop {
+                        id: key.to_owned(),
+                        position: position.to_owned(),
+                        facing: movable.facing,
+                    };
+
+                    let schedule = Schedule::instant(Job::Broadcast(packet));
+
+                    self.schedule_queue.push(schedule);
+
+                    self.share_position(&key);
+
+                    return Ok(());
+                }
+            }
+        };
+
+        let mut actor = self
+            .map
+            .get_mut(position)
+            .ok_or("no actor")?
+            .actors
+            .remove(&key)
+            .ok_or("no actor")?;
+
+        if let Some(movable) = actor.get_mut::<Movable>() {
+            movable.moved_at = time::Instant::now();
+        }
+
+        self.map
+            .get_mut(&next)
+            .unwrap()
+            .actors
+            .insert(key.to_owned(), actor);
+
+        *position = next.to_owned();
+
+        self.mark_dirty(&key);
+
+        let trigger = Trigger::Reach {
+            map_id: self.model_id.to_owned(),
+            position: next.to_owned(),
+        };
+
+        self.progress(&key, trigger)?;
+
+        let packet = packet::Outgoing::Move {
+            id: key.to_owned(),
+            position: next.to_owned(),
+            duration,
+        };
+
+        self.schedule_queue
+            .push(Schedule::instant(Job::Broadcast(packet)));
+
+        let deadline = time::Instant::now() + duration;
+
+        self.schedule_queue
+            .push(Schedule::new(Job::Move(key, duration), deadline));
+
+        Ok(())
+    }
+
+    /**
+     * Change the direction of an actor.
+     *
+     * A move job is started only when the actor begins to walk from idle.
+     */
+    pub(super) fn handle_direction(
+        &mut self,
+        key: String,
+        direction: Direction,
+    ) -> Result<(), Box<dyn Error>> {
+        let actor = self.get_actor_mut(&key)?;
+
+        if combat::is_dead(actor) {
+            return Ok(());
+        }
+
+        let movable = actor.get_mut::<Movable>().ok_or("not movable")?;
+
+        if movable.direction == direction {
+            return Ok(());
+        }
+
+        if direction != Direction::Idle {
+            movable.facing = direction;
+        }
+
+        let duration = time::Duration::from_millis(300);
+
+        let last_direction = movable.direction;
+
+        movable.direction = direction;
+
+        let is_cool = movable.moved_at + duration > time::Instant::now();
+
+        if is_cool || direction == Direction::Idle || last_direction != Direction::Idle {
+            return Ok(());
+        }
+
+        let job = Job::Move(key, duration);
+
+        self.schedule_queue.push(Schedule::instant(job));
+
+        Ok(())
+    }
+
+    /**
+     * Get the way an actor faces, down for one that can't move.
+     */
+    pub(super) fn get_facing(&self, key: &str) -> Direction {
+        self.get_actor(key)
+            .ok()
+            .and_then(|actor| actor.get::<Movable>())
+            .map_or(Direction::Down, |movable| movable.facing)
+    }
+}
+
+pub(super) fn get_adjacent(position: &Vector3, direction: Direction) -> Option<Vector3> {
+    match direction {
+        Direction::Idle => None,
+        Direction::Up => Some(Vector3 {
+            x: position.x,
+            y: position.y,
+            z: position.z + 1,
+        }),
+        Direction::Right => Some(Vector3 {
+            x: position.x - 1,
+            y: position.y,
+            z: position.z,
+        }),
+        Direction::Down => Some(Vector3 {
+            x: position.x,
+            y: position.y,
+            z: position.z - 1,
+        }),
+        Direction::Left => Some(Vector3 {
+            x: position.x + 1,
+            y: position.y,
+            z: position.z,
+        }),
+    }
+}
+
+/**
+ * Get the position an actor reaches by stepping from a tile to a direction.
+ *
+ * Stairs carry the actor a level up when walked along their ascent, and
+ * a level down when walked against it. Any other change of height is a
+ * cliff, which can't be crossed.
+ */
+pub(super) fn get_destination(
+    map: &HashMap<Vector3, Tile>,
+    position: &Vector3,
+    direction: Direction,
+) -> Option<Vector3> {
+    let current = map.get(position)?;
+
+    let next = get_adjacent(position, direction)?;
+
+    if current.kind == Kind::Stairs {
+        let ascent = current.get_ascent();
+
+        if ascent == direction {
+            let above = Vector3 {
+                x: next.x,
+                y: next.y + 1,
+                z: next.z,
+            };
+
+            return match map.get(&above) {
+                Some(tile) if tile.is_enterable(direction) => Some(above),
+                _ => None,
+            };
+        }
+
+        if ascent != get_opposite(direction) {
+            return None;
+        }
+    }
+
+    if let Some(tile) = map.get(&next) {
+        if !tile.is_enterable(direction) {
+            return None;
+        }
+
+        return Some(next);
+    }
+
+    let below = Vector3 {
+        x: next.x,
+        y: next.y - 1,
+        z: next.z,
+    };
+
+    match map.get(&below) {
+        Some(tile) if tile.kind == Kind::Stairs && tile.get_ascent() == get_opposite(direction) => {
+            Some(below)
+        }
+        _ => None,
+    }
+}
+
+fn get_opposite(direction: Direction) -> Direction {
+    match direction {
+        Direction::Idle => Direction::Idle,
+        Direction::Up => Direction::Down,
+        Direction::Right => Direction::Left,
+        Direction::Down => Direction::Up,
+        Direction::Left => Direction::Right,
+    }
+}
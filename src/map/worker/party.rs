@@ -0,0 +1,172 @@
+use std::error::Error;
+
+use crate::{
+    map::Job,
+    net::{packet, protocol},
+    party::{Location, Party},
+    schedule::Schedule,
+};
+
+use super::Worker;
+
+impl Worker {
+    pub(super) fn handle_party_invite(
+        &mut self,
+        key: String,
+        user_id: String,
+    ) -> Result<(), Box<dyn Error>> {
+        match self.parties.invite(&key, &user_id) {
+            Ok(_) => self.tell(&user_id, packet::Outgoing::PartyInvite { id: key }),
+            Err(message) => self.refuse(&key, protocol::ERROR_PARTY, message),
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn handle_party_accept(
+        &mut self,
+        key: String,
+        user_id: String,
+    ) -> Result<(), Box<dyn Error>> {
+        match self.parties.accept(&key, &user_id) {
+            Ok(party) => {
+                self.announce(&party);
+
+                self.share_positions(&party);
+            }
+            Err(message) => self.refuse(&key, protocol::ERROR_PARTY, message),
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn handle_party_leave(&mut self, key: String) -> Result<(), Box<dyn Error>> {
+        self.leave_party(&key);
+
+        Ok(())
+    }
+
+    pub(super) fn handle_party_kick(
+        &mut self,
+        key: String,
+        user_id: String,
+    ) -> Result<(), Box<dyn Error>> {
+        match self.parties.kick(&key, &user_id) {
+            Ok(party) => {
+                self.tell(&user_id, packet::Outgoing::Party { members: vec![] });
+
+                self.announce(&party);
+            }
+            Err(message) => self.refuse(&key, protocol::ERROR_PARTY, message),
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Take a player out of its party and tell the ones left.
+     */
+    pub(super) fn leave_party(&mut self, key: &str) {
+        if let Some(party) = self.parties.leave(key) {
+            self.tell(key, packet::Outgoing::Party { members: vec![] });
+
+            self.announce(&party);
+        }
+    }
+
+    /**
+     * Send the party to each of its members.
+     */
+    pub(super) fn announce(&mut self, party: &Party) {
+        let members = match party.is_active() {
+            true => party.members.to_owned(),
+            false => vec![],
+        };
+
+        for member in &party.members {
+            let packet = packet::Outgoing::Party {
+                members: members.to_owned(),
+            };
+
+            self.tell(member, packet);
+        }
+    }
+
+    /**
+     * Record where a player is and show it to the party members on other
+     * maps, as the ones on this map already see it move.
+     */
+    pub(super) fn share_position(&mut self, key: &str) {
+        let position = match self.positions.get(key) {
+            Some(position) => position.to_owned(),
+            None => return,
+        };
+
+        let location = Location {
+            map: self.id.to_owned(),
+            model_id: self.model_id.to_owned(),
+            position,
+        };
+
+        self.parties.locate(key, location.to_owned());
+
+        if let Some(party) = self.parties.find(key) {
+            for member in &party.members {
+                if member != key && !self.streams.contains_key(member) {
+                    self.tell(member, member_packet(key, &location));
+                }
+            }
+        }
+    }
+
+    /**
+     * Show the members of a party to each other wherever they are apart.
+     */
+    fn share_positions(&mut self, party: &Party) {
+        let locations: Vec<_> = party
+            .members
+            .iter()
+            .filter_map(|member| Some((member.to_owned(), self.parties.location(member)?)))
+            .collect();
+
+        for (member, location) in &locations {
+            for (other, other_location) in &locations {
+                if member != other && location.map != other_location.map {
+                    self.tell(other, member_packet(member, location));
+                }
+            }
+        }
+    }
+
+    /**
+     * Write a packet to a player, on this map or any other.
+     */
+    pub(super) fn tell(&mut self, user_id: &str, packet: packet::Outgoing) {
+        if self.streams.contains_key(user_id) {
+            let job = Job::Write(user_id.to_owned(), packet);
+
+            self.schedule_queue.push(Schedule::instant(job));
+
+            return;
+        }
+
+        self.directory.deliver(user_id, packet);
+    }
+
+    /**
+     * Tell a player why its request was turned down.
+     */
+    pub(super) fn refuse(&mut self, key: &str, code: u16, message: String) {
+        let packet = packet::Outgoing::Error { code, message };
+
+        self.tell(key, packet);
+    }
+}
+
+fn member_packet(id: &str, location: &Location) -> packet::Outgoing {
+    packet::Outgoing::PartyMember {
+        id: id.to_owned(),
+        map_id: location.model_id.to_owned(),
+        position: location.position.to_owned(),
+    }
+}
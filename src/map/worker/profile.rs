@@ -0,0 +1,69 @@
+use std::error::Error;
+
+use crate::{
+    map::{Job, Profile, ProfileChange},
+    net::packet,
+    schedule::Schedule,
+};
+
+use super::Worker;
+
+impl Worker {
+    /**
+     * Change the profile of a player, saving it before everyone on the map
+     * sees it.
+     */
+    pub(super) fn change_profile(
+        &mut self,
+        key: &str,
+        change: ProfileChange,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut profile = self.get_profile(key);
+
+        profile.apply(change)?;
+
+        self.pool.save_profile(key, &profile)?;
+
+        self.get_actor_mut(key)?.insert(profile);
+
+        self.show_profile(key);
+
+        Ok(())
+    }
+
+    /**
+     * Tell everyone on the map how an actor is shown.
+     */
+    pub(super) fn show_profile(&mut self, key: &str) {
+        let packet = packet::Outgoing::Profile {
+            id: key.to_owned(),
+            profile: self.get_profile(key),
+        };
+
+        self.schedule_queue
+            .push(Schedule::instant(Job::Broadcast(packet)));
+    }
+
+    /**
+     * Describe an actor to a player entering the map.
+     */
+    pub(super) fn snapshot(&self, key: &str) -> Result<packet::ActorSnapshot, Box<dyn Error>> {
+        Ok(packet::ActorSnapshot {
+            id: key.to_owned(),
+            position: self.get_position(key)?,
+            facing: self.get_facing(key),
+            profile: self.get_profile(key),
+        })
+    }
+
+    /**
+     * Get how an actor is shown, the default for one without a profile.
+     */
+    fn get_profile(&self, key: &str) -> Profile {
+        self.get_actor(key)
+            .ok()
+            .and_then(|actor| actor.get::<Profile>())
+            .cloned()
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,230 @@
+use std::{collections::BTreeMap, error::Error};
+
+use crate::{
+    item::Inventory,
+    map::Job,
+    net::packet,
+    quest::{Journal, Progress, Trigger},
+    schedule::Schedule,
+};
+
+use super::{is_adjacent, Worker};
+
+impl Worker {
+    /**
+     * Talk to an NPC next to a player, starting the quests it gives.
+     */
+    pub(super) fn handle_talk(
+        &mut self,
+        key: String,
+        npc_id: String,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.streams.contains_key(&npc_id) {
+            return Ok(());
+        }
+
+        match (self.positions.get(&key), self.positions.get(&npc_id)) {
+            (Some(a), Some(b)) if is_adjacent(a, b) => {}
+            _ => return Ok(()),
+        }
+
+        let quest_ids: Vec<String> = self
+            .quests
+            .values()
+            .filter(|definition| definition.giver.as_deref() == Some(npc_id.as_str()))
+            .map(|definition| definition.id.to_owned())
+            .collect();
+
+        let mut shown = BTreeMap::new();
+
+        for quest_id in quest_ids {
+            if let Some(progress) = self.start_quest(&key, &quest_id)? {
+                shown.insert(quest_id, progress);
+            }
+        }
+
+        shown.extend(self.record(&key, Trigger::Talk { npc_id })?);
+
+        for (quest_id, progress) in shown {
+            self.show_quest(&key, &quest_id, &progress);
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Show a player who entered the map its quests, starting the ones that
+     * need no giver and counting what it already holds and stands on.
+     */
+    pub(super) fn open_journal(&mut self, key: &str) -> Result<(), Box<dyn Error>> {
+        let quest_ids: Vec<String> = self
+            .quests
+            .values()
+            .filter(|definition| definition.giver.is_none())
+            .map(|definition| definition.id.to_owned())
+            .collect();
+
+        for quest_id in quest_ids {
+            self.start_quest(key, &quest_id)?;
+        }
+
+        let mut triggers = self.holdings(key)?;
+
+        triggers.push(Trigger::Reach {
+            map_id: self.model_id.to_owned(),
+            position: self.get_position(key)?,
+        });
+
+        for trigger in triggers {
+            self.record(key, trigger)?;
+        }
+
+        let quests: Vec<(String, Progress)> = self
+            .get_actor(key)?
+            .get::<Journal>()
+            .ok_or("no journal")?
+            .quests()
+            .iter()
+            .map(|(quest_id, progress)| (quest_id.to_owned(), progress.to_owned()))
+            .collect();
+
+        for (quest_id, progress) in quests {
+            self.show_quest(key, &quest_id, &progress);
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Count something a player did toward its quests, showing the ones
+     * that moved on.
+     *
+     * Actors without a journal, such as NPCs, are skipped.
+     */
+    pub(super) fn progress(&mut self, key: &str, trigger: Trigger) -> Result<(), Box<dyn Error>> {
+        for (quest_id, progress) in self.record(key, trigger)? {
+            self.show_quest(key, &quest_id, &progress);
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Count something a player did toward its quests and save the ones
+     * that moved on.
+     *
+     * Return the quests that moved on.
+     */
+    fn record(
+        &mut self,
+        key: &str,
+        trigger: Trigger,
+    ) -> Result<Vec<(String, Progress)>, Box<dyn Error>> {
+        let quests = self.quests.clone();
+
+        let journal = match self
+            .get_actor_mut(key)
+            .ok()
+            .and_then(|actor| actor.get_mut::<Journal>())
+        {
+            Some(journal) => journal,
+            None => return Ok(vec![]),
+        };
+
+        let changed: Vec<(String, Progress)> = journal
+            .record(&quests, &trigger)
+            .into_iter()
+            .filter_map(|quest_id| {
+                let progress = journal.get(&quest_id)?.to_owned();
+
+                Some((quest_id, progress))
+            })
+            .collect();
+
+        for (quest_id, progress) in &changed {
+            self.pool.save_quest(key, quest_id, progress)?;
+        }
+
+        Ok(changed)
+    }
+
+    /**
+     * Count the items a player holds toward its quests.
+     */
+    pub(super) fn track_inventory(&mut self, key: &str) -> Result<(), Box<dyn Error>> {
+        for trigger in self.holdings(key)? {
+            self.progress(key, trigger)?;
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Get how many of each item a player holds.
+     */
+    fn holdings(&self, key: &str) -> Result<Vec<Trigger>, Box<dyn Error>> {
+        let inventory = self
+            .get_actor(key)?
+            .get::<Inventory>()
+            .ok_or("no inventory")?;
+
+        let mut totals: BTreeMap<String, u16> = BTreeMap::new();
+
+        for stack in inventory.slots().iter().flatten() {
+            let total = totals.entry(stack.item_id.to_owned()).or_default();
+
+            *total = total.saturating_add(stack.quantity);
+        }
+
+        Ok(totals
+            .into_iter()
+            .map(|(item_id, quantity)| Trigger::Hold { item_id, quantity })
+            .collect())
+    }
+
+    /**
+     * Start a quest for a player.
+     *
+     * Return its progress if it wasn't started before.
+     */
+    fn start_quest(
+        &mut self,
+        key: &str,
+        quest_id: &str,
+    ) -> Result<Option<Progress>, Box<dyn Error>> {
+        let journal = self
+            .get_actor_mut(key)?
+            .get_mut::<Journal>()
+            .ok_or("no journal")?;
+
+        if !journal.start(quest_id) {
+            return Ok(None);
+        }
+
+        let progress = journal.get(quest_id).ok_or("no quest")?.to_owned();
+
+        self.pool.save_quest(key, quest_id, &progress)?;
+
+        Ok(Some(progress))
+    }
+
+    fn show_quest(&mut self, key: &str, quest_id: &str, progress: &Progress) {
+        let definition = match self.quests.get(quest_id) {
+            Some(definition) => definition,
+            None => return,
+        };
+
+        let counts = (0..definition.objectives.len())
+            .map(|index| progress.count(index))
+            .collect();
+
+        let packet = packet::Outgoing::Quest {
+            id: quest_id.to_owned(),
+            counts,
+            completed: progress.is_complete(definition),
+        };
+
+        self.schedule_queue
+            .push(Schedule::instant(Job::Write(key.to_owned(), packet)));
+    }
+}
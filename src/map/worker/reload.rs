@@ -0,0 +1,137 @@
+use std::{collections::HashMap, error::Error};
+
+use east_online_core::model::Vector3;
+
+use crate::{
+    map::{Actor, Job, Kind, Tile},
+    net::packet,
+    schedule::Schedule,
+};
+
+use super::Worker;
+
+impl Worker {
+    /**
+     * Apply a new version of the map to the live one.
+     *
+     * Tiles that kept their shape are left alone. Actors and items on
+     * removed tiles move to the nearest floor that is left, and clients are
+     * told about every changed tile. Return how many actors were moved.
+     */
+    pub(super) fn reload(
+        &mut self,
+        tiles: HashMap<Vector3, Tile>,
+    ) -> Result<usize, Box<dyn Error>> {
+        if !tiles.contains_key(&self.spawn) {
+            return Err("no spawn tile".into());
+        }
+
+        let mut updates = Vec::new();
+
+        let removed: Vec<Vector3> = self
+            .map
+            .keys()
+            .filter(|position| !tiles.contains_key(position))
+            .cloned()
+            .collect();
+
+        for (position, tile) in tiles {
+            match self.map.get_mut(&position) {
+                Some(current) if current.is_same_shape(&tile) => continue,
+                Some(current) => {
+                    current.kind = tile.kind;
+
+                    current.rotation = tile.rotation;
+
+                    current.model_id = tile.model_id;
+                }
+                None => {
+                    self.map.insert(position.to_owned(), tile);
+                }
+            }
+
+            let tile = &self.map[&position];
+
+            updates.push((
+                position,
+                Some((tile.model_id.to_owned(), tile.rotation_byte())),
+            ));
+        }
+
+        let mut displaced: Vec<(Vector3, String, Actor)> = Vec::new();
+
+        let removed: Vec<(Vector3, Tile)> = removed
+            .into_iter()
+            .map(|position| {
+                let tile = self.map.remove(&position).unwrap();
+
+                (position, tile)
+            })
+            .collect();
+
+        for (position, tile) in removed {
+            let safe = self.find_safe_tile(&position);
+
+            self.map.get_mut(&safe).unwrap().items.extend(tile.items);
+
+            for (key, actor) in tile.actors {
+                displaced.push((safe.to_owned(), key, actor));
+            }
+
+            updates.push((position, None));
+        }
+
+        if !updates.is_empty() {
+            let packet = packet::Outgoing::MapUpdate { tiles: updates };
+
+            self.schedule_queue
+                .push(Schedule::instant(Job::Broadcast(packet)));
+        }
+
+        let count = displaced.len();
+
+        for (position, key, actor) in displaced {
+            self.map
+                .get_mut(&position)
+                .unwrap()
+                .actors
+                .insert(key.to_owned(), actor);
+
+            self.positions.insert(key.to_owned(), position.to_owned());
+
+            self.mark_dirty(&key);
+
+            let facing = self.get_facing(&key);
+
+            let packet = packet::Outgoing::Stop {
+                id: key,
+                position,
+                facing,
+            };
+
+            self.schedule_queue
+                .push(Schedule::instant(Job::Broadcast(packet)));
+        }
+
+        Ok(count)
+    }
+
+    /**
+     * Find the floor tile closest to a position, falling back to the spawn.
+     */
+    fn find_safe_tile(&self, position: &Vector3) -> Vector3 {
+        self.map
+            .iter()
+            .filter(|(_, tile)| tile.kind == Kind::Floor)
+            .map(|(candidate, _)| candidate)
+            .min_by_key(|candidate| {
+                let distance = (candidate.x - position.x).abs()
+                    + (candidate.y - position.y).abs()
+                    + (candidate.z - position.z).abs();
+
+                (distance, candidate.x, candidate.y, candidate.z)
+            })
+            .unwrap_or(&self.spawn)
+            .to_owned()
+    }
+}
@@ -0,0 +1,189 @@
+use std::error::Error;
+
+use crate::{
+    item::{self, Inventory, Wallet},
+    map::Job,
+    net::{packet, protocol},
+    schedule::Schedule,
+    shop::Shop,
+};
+
+use super::{inventory::inventory_packet, is_adjacent, Worker};
+
+impl Worker {
+    pub(super) fn handle_shop_open(
+        &mut self,
+        key: String,
+        shop_id: String,
+    ) -> Result<(), Box<dyn Error>> {
+        if let Err(e) = self.check_shop(&key, &shop_id) {
+            self.refuse(&key, protocol::ERROR_SHOP, format!("{e}"));
+
+            return Ok(());
+        }
+
+        self.show_shop(&key, &shop_id)
+    }
+
+    pub(super) fn handle_shop_buy(
+        &mut self,
+        key: String,
+        shop_id: String,
+        item_id: String,
+        quantity: u16,
+    ) -> Result<(), Box<dyn Error>> {
+        let result = self.check_shop(&key, &shop_id).and_then(|shop| {
+            let price = shop.buy_price(&item_id, quantity)?;
+
+            let (mut inventory, mut wallet) = self.belongings(&key)?;
+
+            wallet.spend(price)?;
+
+            inventory.add(item::Stack::new(item_id.to_owned(), quantity), &self.items)?;
+
+            Ok((inventory, wallet))
+        });
+
+        match result {
+            Ok((inventory, wallet)) => {
+                self.commit_deal(&key, inventory, wallet)?;
+
+                self.get_shop_mut(&shop_id)?
+                    .remove_stock(&item_id, quantity);
+
+                self.show_shop(&key, &shop_id)
+            }
+            Err(e) => {
+                self.refuse(&key, protocol::ERROR_SHOP, format!("{e}"));
+
+                Ok(())
+            }
+        }
+    }
+
+    pub(super) fn handle_shop_sell(
+        &mut self,
+        key: String,
+        shop_id: String,
+        slot: u8,
+        quantity: u16,
+    ) -> Result<(), Box<dyn Error>> {
+        let result = self.check_shop(&key, &shop_id).and_then(|shop| {
+            let (mut inventory, mut wallet) = self.belongings(&key)?;
+
+            let stack = inventory.take(usize::from(slot), quantity)?;
+
+            wallet.earn(shop.sell_price(&stack.item_id, quantity)?)?;
+
+            Ok((inventory, wallet, stack.item_id))
+        });
+
+        match result {
+            Ok((inventory, wallet, item_id)) => {
+                self.commit_deal(&key, inventory, wallet)?;
+
+                self.get_shop_mut(&shop_id)?.add_stock(&item_id, quantity);
+
+                self.show_shop(&key, &shop_id)
+            }
+            Err(e) => {
+                self.refuse(&key, protocol::ERROR_SHOP, format!("{e}"));
+
+                Ok(())
+            }
+        }
+    }
+
+    /**
+     * Find a shop the player stands next to.
+     *
+     * Players in a trade can't deal, as their items are on the table.
+     */
+    fn check_shop(&self, key: &str, shop_id: &str) -> Result<&Shop, Box<dyn Error>> {
+        if self.trading.contains_key(key) {
+            return Err("busy trading".into());
+        }
+
+        let shop = self
+            .get_actor(shop_id)
+            .ok()
+            .and_then(|actor| actor.get::<Shop>())
+            .ok_or("no such shop")?;
+
+        match (self.positions.get(key), self.positions.get(shop_id)) {
+            (Some(a), Some(b)) if is_adjacent(a, b) => Ok(shop),
+            _ => Err("shop is out of reach".into()),
+        }
+    }
+
+    /**
+     * Copy what a player carries, to be changed by a deal.
+     */
+    fn belongings(&self, key: &str) -> Result<(Inventory, Wallet), Box<dyn Error>> {
+        let actor = self.get_actor(key)?;
+
+        let inventory = actor.get::<Inventory>().ok_or("no inventory")?;
+
+        let wallet = actor.get::<Wallet>().ok_or("no wallet")?;
+
+        Ok((inventory.to_owned(), *wallet))
+    }
+
+    /**
+     * Save the items and coins of a player together, then hand them over.
+     */
+    fn commit_deal(
+        &mut self,
+        key: &str,
+        inventory: Inventory,
+        wallet: Wallet,
+    ) -> Result<(), Box<dyn Error>> {
+        self.pool.save_exchange(&[(key, &inventory, &wallet)])?;
+
+        let packet = inventory_packet(&inventory);
+
+        let actor = self.get_actor_mut(key)?;
+
+        actor.insert(inventory);
+
+        actor.insert(wallet);
+
+        self.track_inventory(key)?;
+
+        for packet in [
+            packet,
+            packet::Outgoing::Wallet {
+                coins: wallet.coins,
+            },
+        ] {
+            self.schedule_queue
+                .push(Schedule::instant(Job::Write(key.to_owned(), packet)));
+        }
+
+        Ok(())
+    }
+
+    fn show_shop(&mut self, key: &str, shop_id: &str) -> Result<(), Box<dyn Error>> {
+        let shop = self
+            .get_actor(shop_id)?
+            .get::<Shop>()
+            .ok_or("no such shop")?;
+
+        let packet = packet::Outgoing::Shop {
+            id: shop_id.to_owned(),
+            goods: shop.goods().to_vec(),
+        };
+
+        self.schedule_queue
+            .push(Schedule::instant(Job::Write(key.to_owned(), packet)));
+
+        Ok(())
+    }
+
+    fn get_shop_mut(&mut self, shop_id: &str) -> Result<&mut Shop, Box<dyn Error>> {
+        Ok(self
+            .get_actor_mut(shop_id)?
+            .get_mut::<Shop>()
+            .ok_or("no such shop")?)
+    }
+}
@@ -0,0 +1,385 @@
+use std::{collections::HashSet, error::Error};
+
+use crate::{
+    item::{self, Inventory, Wallet},
+    map::Job,
+    net::{packet, protocol},
+    schedule::Schedule,
+    trade::{self, Offer, Session},
+};
+
+use super::{inventory::inventory_packet, is_adjacent, Worker};
+
+impl Worker {
+    pub(super) fn handle_trade_request(
+        &mut self,
+        key: String,
+        user_id: String,
+    ) -> Result<(), Box<dyn Error>> {
+        if let Err(message) = self.check_traders(&key, &user_id) {
+            self.refuse(&key, protocol::ERROR_TRADE, message);
+
+            return Ok(());
+        }
+
+        self.trade_requests
+            .entry(user_id.to_owned())
+            .or_default()
+            .insert(key.to_owned());
+
+        let packet = packet::Outgoing::TradeRequest { id: key };
+
+        self.schedule_queue
+            .push(Schedule::instant(Job::Write(user_id, packet)));
+
+        Ok(())
+    }
+
+    pub(super) fn handle_trade_accept(
+        &mut self,
+        key: String,
+        user_id: String,
+    ) -> Result<(), Box<dyn Error>> {
+        let requested = self
+            .trade_requests
+            .get_mut(&key)
+            .is_some_and(|requests| requests.remove(&user_id));
+
+        if !requested {
+            let message = format!("no request from {}", user_id);
+
+            self.refuse(&key, protocol::ERROR_TRADE, message);
+
+            return Ok(());
+        }
+
+        if let Err(message) = self.check_traders(&key, &user_id) {
+            self.refuse(&key, protocol::ERROR_TRADE, message);
+
+            return Ok(());
+        }
+
+        self.trading.insert(key.to_owned(), user_id.to_owned());
+
+        self.trading.insert(user_id.to_owned(), user_id.to_owned());
+
+        self.trades
+            .insert(user_id.to_owned(), Session::new(user_id, key.to_owned()));
+
+        self.show_trade(&key)
+    }
+
+    pub(super) fn handle_trade_offer(
+        &mut self,
+        key: String,
+        slots: Vec<(u8, u16)>,
+        coins: u64,
+    ) -> Result<(), Box<dyn Error>> {
+        let offer = match self.make_offer(&key, slots, coins) {
+            Ok(offer) => offer,
+            Err(e) => {
+                self.refuse(&key, protocol::ERROR_TRADE, format!("{e}"));
+
+                return Ok(());
+            }
+        };
+
+        match self
+            .get_trade_mut(&key)
+            .and_then(|session| session.offer(&key, offer))
+        {
+            Ok(_) => self.show_trade(&key),
+            Err(e) => {
+                self.refuse(&key, protocol::ERROR_TRADE, format!("{e}"));
+
+                Ok(())
+            }
+        }
+    }
+
+    pub(super) fn handle_trade_lock(&mut self, key: String) -> Result<(), Box<dyn Error>> {
+        match self
+            .get_trade_mut(&key)
+            .and_then(|session| session.lock(&key))
+        {
+            Ok(_) => self.show_trade(&key),
+            Err(e) => {
+                self.refuse(&key, protocol::ERROR_TRADE, format!("{e}"));
+
+                Ok(())
+            }
+        }
+    }
+
+    pub(super) fn handle_trade_confirm(&mut self, key: String) -> Result<(), Box<dyn Error>> {
+        match self
+            .get_trade_mut(&key)
+            .and_then(|session| session.confirm(&key))
+        {
+            Ok(true) => self.complete_trade(&key),
+            Ok(false) => self.show_trade(&key),
+            Err(e) => {
+                self.refuse(&key, protocol::ERROR_TRADE, format!("{e}"));
+
+                Ok(())
+            }
+        }
+    }
+
+    pub(super) fn handle_trade_cancel(&mut self, key: String) -> Result<(), Box<dyn Error>> {
+        self.close_trade(&key, "cancelled");
+
+        Ok(())
+    }
+
+    /**
+     * Forget the requests of a player leaving the map and call off its
+     * trade, if any.
+     */
+    pub(super) fn leave_trade(&mut self, key: &str) {
+        self.trade_requests.remove(key);
+
+        for requests in self.trade_requests.values_mut() {
+            requests.remove(key);
+        }
+
+        self.close_trade(key, "cancelled");
+    }
+
+    /**
+     * Turn down a change to the items of a player in a trade, as it would
+     * change what the partner agreed to.
+     *
+     * Return whether it was turned down.
+     */
+    pub(super) fn refuse_while_trading(&mut self, key: &str) -> bool {
+        if !self.trading.contains_key(key) {
+            return false;
+        }
+
+        self.refuse(key, protocol::ERROR_TRADE, String::from("busy trading"));
+
+        true
+    }
+
+    /**
+     * Check that two players are able to start a trade with each other.
+     */
+    fn check_traders(&self, key: &str, user_id: &str) -> Result<(), String> {
+        if key == user_id {
+            return Err(String::from("no trading with oneself"));
+        }
+
+        match (self.positions.get(key), self.positions.get(user_id)) {
+            (Some(a), Some(b)) if is_adjacent(a, b) => {}
+            _ => return Err(format!("{} is out of reach", user_id)),
+        }
+
+        if self.trading.contains_key(key) || self.trading.contains_key(user_id) {
+            return Err(String::from("already trading"));
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Check that a player owns everything in its offer, noting the item in
+     * each offered slot.
+     */
+    fn make_offer(
+        &self,
+        key: &str,
+        slots: Vec<(u8, u16)>,
+        coins: u64,
+    ) -> Result<Offer, Box<dyn Error>> {
+        let actor = self.get_actor(key)?;
+
+        let inventory = actor.get::<Inventory>().ok_or("no inventory")?;
+
+        let wallet = actor.get::<Wallet>().ok_or("no wallet")?;
+
+        let mut offered = HashSet::new();
+
+        let mut stacks = Vec::new();
+
+        for (slot, quantity) in slots {
+            if !offered.insert(slot) {
+                return Err("slot offered twice".into());
+            }
+
+            match inventory.get(usize::from(slot)) {
+                Some(stack) if quantity > 0 && stack.quantity >= quantity => {
+                    stacks.push((slot, item::Stack::new(stack.item_id.to_owned(), quantity)));
+                }
+                _ => return Err("not enough items".into()),
+            }
+        }
+
+        if wallet.coins < coins {
+            return Err("not enough coins".into());
+        }
+
+        Ok(Offer {
+            slots: stacks,
+            coins,
+        })
+    }
+
+    /**
+     * Swap both offers, saving both sides at once before anything changes
+     * on the map.
+     */
+    fn complete_trade(&mut self, key: &str) -> Result<(), Box<dyn Error>> {
+        let session = self.get_trade_mut(key)?.to_owned();
+
+        let [first, second] = session.users();
+
+        match (self.positions.get(first), self.positions.get(second)) {
+            (Some(a), Some(b)) if is_adjacent(a, b) => {}
+            _ => {
+                self.close_trade(key, "out of reach");
+
+                return Ok(());
+            }
+        }
+
+        let result = self.exchange(&session);
+
+        let sides = match result {
+            Ok(sides) => sides,
+            Err(e) => {
+                self.close_trade(key, &format!("{e}"));
+
+                return Ok(());
+            }
+        };
+
+        let [(first_inventory, first_wallet), (second_inventory, second_wallet)] = &sides;
+
+        let saved = self.pool.save_exchange(&[
+            (first, first_inventory, first_wallet),
+            (second, second_inventory, second_wallet),
+        ]);
+
+        if let Err(e) = saved {
+            eprintln!("trade of {} and {} not saved for {e}", first, second);
+
+            self.close_trade(key, "not saved");
+
+            return Ok(());
+        }
+
+        for (user_id, (inventory, wallet)) in session.users().iter().zip(sides) {
+            let actor = self.get_actor_mut(user_id)?;
+
+            actor.insert(inventory.to_owned());
+
+            actor.insert(wallet);
+
+            self.track_inventory(user_id)?;
+
+            for packet in [
+                inventory_packet(&inventory),
+                packet::Outgoing::Wallet {
+                    coins: wallet.coins,
+                },
+            ] {
+                self.schedule_queue
+                    .push(Schedule::instant(Job::Write(user_id.to_owned(), packet)));
+            }
+        }
+
+        self.close_trade(key, "completed");
+
+        Ok(())
+    }
+
+    fn exchange(&self, session: &Session) -> Result<[(Inventory, Wallet); 2], Box<dyn Error>> {
+        let [first, second] = session.users();
+
+        let [first_offer, second_offer] = session.offers();
+
+        let first = self.get_actor(first)?;
+
+        let second = self.get_actor(second)?;
+
+        trade::exchange(
+            [
+                (
+                    first.get::<Inventory>().ok_or("no inventory")?,
+                    first.get::<Wallet>().ok_or("no wallet")?,
+                    first_offer,
+                ),
+                (
+                    second.get::<Inventory>().ok_or("no inventory")?,
+                    second.get::<Wallet>().ok_or("no wallet")?,
+                    second_offer,
+                ),
+            ],
+            &self.items,
+        )
+    }
+
+    /**
+     * End the trade of a player, telling both sides why.
+     */
+    fn close_trade(&mut self, key: &str, message: &str) {
+        let session = match self.trading.get(key).and_then(|id| self.trades.remove(id)) {
+            Some(session) => session,
+            None => return,
+        };
+
+        for user_id in session.users() {
+            self.trading.remove(user_id);
+
+            let packet = packet::Outgoing::TradeClosed {
+                message: message.to_owned(),
+            };
+
+            self.schedule_queue
+                .push(Schedule::instant(Job::Write(user_id.to_owned(), packet)));
+        }
+    }
+
+    /**
+     * Send the state of a trade to both sides.
+     */
+    fn show_trade(&mut self, key: &str) -> Result<(), Box<dyn Error>> {
+        let session = self.get_trade_mut(key)?.to_owned();
+
+        let mut sides = Vec::new();
+
+        for (side, (user_id, offer)) in session.users().iter().zip(session.offers()).enumerate() {
+            let items = offer
+                .slots
+                .iter()
+                .map(|(_, stack)| stack.to_owned())
+                .collect();
+
+            sides.push(packet::TradeSide {
+                id: user_id.to_owned(),
+                items,
+                coins: offer.coins,
+                locked: session.is_locked(side),
+                confirmed: session.is_confirmed(side),
+            });
+        }
+
+        for user_id in session.users() {
+            let packet = packet::Outgoing::Trade {
+                sides: sides.to_owned(),
+            };
+
+            self.schedule_queue
+                .push(Schedule::instant(Job::Write(user_id.to_owned(), packet)));
+        }
+
+        Ok(())
+    }
+
+    fn get_trade_mut(&mut self, key: &str) -> Result<&mut Session, Box<dyn Error>> {
+        let id = self.trading.get(key).ok_or("not trading")?;
+
+        Ok(self.trades.get_mut(id).ok_or("no trade")?)
+    }
+}
@@ -0,0 +1,52 @@
+use std::io;
+
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::{TcpStream, ToSocketAddrs},
+};
+
+use crate::net::packet;
+
+use super::{read_packet, write_packet};
+
+/**
+ * A client side connection to the server.
+ *
+ * It sends incoming packets and receives outgoing ones.
+ */
+pub struct Connection<S> {
+    stream: S,
+}
+
+impl Connection<TcpStream> {
+    pub async fn connect<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        let stream = TcpStream::connect(address).await?;
+
+        Ok(Connection::new(stream))
+    }
+}
+
+impl<S> Connection<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    pub fn new(stream: S) -> Self {
+        Connection { stream }
+    }
+
+    pub async fn send(&mut self, packet: &packet::Incoming) -> io::Result<()> {
+        write_packet(&mut self.stream, packet).await?;
+
+        Ok(())
+    }
+
+    pub async fn recv(&mut self) -> io::Result<packet::Outgoing> {
+        let (packet, _) = read_packet(&mut self.stream).await?;
+
+        Ok(packet)
+    }
+
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
@@ -0,0 +1,82 @@
+use std::io;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::net::{packet, protocol::PROTOCOL_VERSION};
+
+/**
+ * Frame an incoming packet the way the server reads it.
+ */
+pub fn get_packet_buf(packet: &packet::Incoming) -> io::Result<Vec<u8>> {
+    let buf = packet
+        .serialize()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+
+    let size = u16::try_from(buf.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "incoming packet too large"))?;
+
+    Ok([&size.to_le_bytes() as &[u8], &buf].concat())
+}
+
+/**
+ * Write a packet and return the number of bytes written.
+ */
+pub async fn write_packet<W>(writer: &mut W, packet: &packet::Incoming) -> io::Result<usize>
+where
+    W: AsyncWrite + Unpin,
+{
+    let buf = get_packet_buf(packet)?;
+
+    writer.write_all(&buf).await?;
+
+    Ok(buf.len())
+}
+
+/**
+ * Read a packet and return it with the number of bytes read.
+ */
+pub async fn read_packet<R>(reader: &mut R) -> io::Result<(packet::Outgoing, usize)>
+where
+    R: AsyncRead + Unpin,
+{
+    let size = usize::from(reader.read_u16_le().await?);
+
+    let mut buf = vec![0; size];
+
+    reader.read_exact(&mut buf).await?;
+
+    let packet = packet::Outgoing::deserialize(&buf)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+
+    Ok((packet, size + 2))
+}
+
+/**
+ * Take a packet from the front of a buffer once it has fully arrived.
+ *
+ * Meant for clients that read without blocking.
+ */
+pub fn take_packet(buf: &mut Vec<u8>) -> io::Result<Option<packet::Outgoing>> {
+    take_packet_for(buf, PROTOCOL_VERSION)
+}
+
+/**
+ * Take a packet written in the layout a version of the protocol reads.
+ */
+pub fn take_packet_for(buf: &mut Vec<u8>, version: u16) -> io::Result<Option<packet::Outgoing>> {
+    if buf.len() < 2 {
+        return Ok(None);
+    }
+
+    let size = usize::from(u16::from_le_bytes([buf[0], buf[1]]));
+
+    if buf.len() < size + 2 {
+        return Ok(None);
+    }
+
+    let frame: Vec<u8> = buf.drain(..size + 2).skip(2).collect();
+
+    packet::Outgoing::deserialize_for(&frame, version)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+}
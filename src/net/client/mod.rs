@@ -0,0 +1,7 @@
+mod connection;
+
+pub use connection::Connection;
+
+mod frame;
+
+pub use frame::{get_packet_buf, read_packet, take_packet, take_packet_for, write_packet};
@@ -4,4 +4,4 @@ pub use reader::Reader;
 
 mod writer;
 
-pub use writer::get_packet_buf;
+pub use writer::{get_packet_buf, get_packet_buf_for};
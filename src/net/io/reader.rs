@@ -1,14 +1,12 @@
 use std::io;
 
-use tokio::net::TcpStream;
-
-use crate::net::packet;
+use crate::net::{packet, Stream};
 
 pub trait Reader {
     fn try_read_packet(&self) -> io::Result<packet::Incoming>;
 }
 
-impl Reader for TcpStream {
+impl Reader for Stream {
     fn try_read_packet(&self) -> io::Result<packet::Incoming> {
         let mut buf = Vec::with_capacity(2);
 
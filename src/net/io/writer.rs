@@ -1,9 +1,19 @@
 use std::error::Error;
 
-use crate::net::packet;
+use crate::net::{packet, protocol::PROTOCOL_VERSION};
 
 pub fn get_packet_buf(packet: packet::Outgoing) -> Result<Vec<u8>, Box<dyn Error>> {
-    let buf = packet.serialize()?;
+    get_packet_buf_for(packet, PROTOCOL_VERSION)
+}
+
+/**
+ * Frame a packet in the layout a version of the protocol reads.
+ */
+pub fn get_packet_buf_for(
+    packet: packet::Outgoing,
+    version: u16,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let buf = packet.serialize_for(version)?;
 
     let size: u16 = match buf.len().try_into() {
         Ok(size) => size,
@@ -0,0 +1,135 @@
+use std::{error::Error, io, net::SocketAddr};
+
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    time,
+};
+use tokio_rustls::TlsAcceptor;
+
+use super::{Stream, Tunnel};
+
+const HANDSHAKE_TIMEOUT: time::Duration = time::Duration::from_secs(10);
+
+/**
+ * How packets are carried over an accepted connection.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    Raw,
+    WebSocket,
+}
+
+/**
+ * Accept streams in the background from every bound socket.
+ *
+ * TLS and WebSocket handshakes run on their own tasks
+ * so a slow client can't hold the gate.
+ */
+pub struct Listener {
+    sender: mpsc::Sender<Stream>,
+    receiver: mpsc::Receiver<Stream>,
+}
+
+impl Listener {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel(64);
+
+        Listener { sender, receiver }
+    }
+
+    /**
+     * Start accepting on a socket, terminating TLS if an acceptor is given.
+     */
+    pub fn listen(
+        &mut self,
+        listener: TcpListener,
+        acceptor: Option<TlsAcceptor>,
+        transport: Transport,
+    ) -> io::Result<SocketAddr> {
+        let local_addr = listener.local_addr()?;
+
+        let sender = self.sender.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let (stream, addr) = match listener.accept().await {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        eprintln!("accept failed for {e}");
+
+                        continue;
+                    }
+                };
+
+                if acceptor.is_none() && transport == Transport::Raw {
+                    if sender.send(Stream::Tcp(stream)).await.is_err() {
+                        return;
+                    }
+
+                    continue;
+                }
+
+                let acceptor = acceptor.clone();
+
+                let sender = sender.clone();
+
+                tokio::spawn(async move {
+                    let handshake = handshake(stream, addr, acceptor, transport);
+
+                    match time::timeout(HANDSHAKE_TIMEOUT, handshake).await {
+                        Ok(Ok(stream)) => {
+                            sender.send(stream).await.ok();
+                        }
+                        Ok(Err(e)) => eprintln!("{:?} failed handshake for {e}", addr),
+                        Err(_) => eprintln!("{:?} timed out on handshake", addr),
+                    }
+                });
+            }
+        });
+
+        Ok(local_addr)
+    }
+
+    pub async fn accept(&mut self) -> Option<Stream> {
+        self.receiver.recv().await
+    }
+}
+
+impl Default for Listener {
+    fn default() -> Self {
+        Listener::new()
+    }
+}
+
+async fn handshake(
+    stream: TcpStream,
+    addr: SocketAddr,
+    acceptor: Option<TlsAcceptor>,
+    transport: Transport,
+) -> Result<Stream, Box<dyn Error + Send + Sync>> {
+    match (acceptor, transport) {
+        (None, Transport::Raw) => Ok(Stream::Tcp(stream)),
+        (Some(acceptor), Transport::Raw) => {
+            let stream = acceptor.accept(stream).await?;
+
+            Ok(Stream::Tunnel(Tunnel::spawn(stream, addr)))
+        }
+        (None, Transport::WebSocket) => {
+            let config = Tunnel::websocket_config();
+
+            let stream = tokio_tungstenite::accept_async_with_config(stream, Some(config)).await?;
+
+            Ok(Stream::Tunnel(Tunnel::spawn_websocket(stream, addr)))
+        }
+        (Some(acceptor), Transport::WebSocket) => {
+            let stream = acceptor.accept(stream).await?;
+
+            let config = Tunnel::websocket_config();
+
+            let stream = tokio_tungstenite::accept_async_with_config(stream, Some(config)).await?;
+
+            Ok(Stream::Tunnel(Tunnel::spawn_websocket(stream, addr)))
+        }
+    }
+}
@@ -1,3 +1,17 @@
 pub mod packet;
 
 pub mod io;
+
+pub mod protocol;
+
+pub mod client;
+
+pub mod tls;
+
+mod stream;
+
+pub use stream::{Stream, Tunnel};
+
+mod listener;
+
+pub use listener::{Listener, Transport};
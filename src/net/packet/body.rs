@@ -0,0 +1,136 @@
+use std::error::Error;
+
+use east_online_core::model::{Direction, Vector3};
+
+/**
+ * Length of the ids written without a length prefix.
+ */
+pub const ID_LENGTH: usize = 36;
+
+pub const MAP_ID_LENGTH: usize = 8;
+
+pub const ITEM_ID_LENGTH: usize = 9;
+
+/**
+ * Read the fields of a packet body from the front.
+ */
+pub struct Body<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Body<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Body { buf }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    pub fn take(&mut self, size: usize) -> Result<&'a [u8], Box<dyn Error>> {
+        if self.buf.len() < size {
+            return Err(format!("body too short, {} < {}", self.buf.len(), size).into());
+        }
+
+        let (head, tail) = self.buf.split_at(size);
+
+        self.buf = tail;
+
+        Ok(head)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, Box<dyn Error>> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, Box<dyn Error>> {
+        let buf = self.take(2)?;
+
+        Ok(u16::from_le_bytes([buf[0], buf[1]]))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, Box<dyn Error>> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    pub fn i32(&mut self) -> Result<i32, Box<dyn Error>> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, Box<dyn Error>> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+
+    pub fn i64(&mut self) -> Result<i64, Box<dyn Error>> {
+        Ok(i64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+
+    pub fn string(&mut self, size: usize) -> Result<String, Box<dyn Error>> {
+        Ok(String::from_utf8(self.take(size)?.to_vec())?)
+    }
+
+    /**
+     * Read a string prefixed with its length in a byte.
+     */
+    pub fn short_string(&mut self) -> Result<String, Box<dyn Error>> {
+        let size = usize::from(self.u8()?);
+
+        self.string(size)
+    }
+
+    /**
+     * Read whatever is left as a string.
+     */
+    pub fn rest_string(&mut self) -> Result<String, Box<dyn Error>> {
+        let size = self.buf.len();
+
+        self.string(size)
+    }
+
+    /**
+     * Read a position in the layout of `Vector3::to_bytes`.
+     */
+    pub fn vector3(&mut self) -> Result<Vector3, Box<dyn Error>> {
+        Ok(Vector3 {
+            x: self.i32()?,
+            y: self.i32()?,
+            z: self.i32()?,
+        })
+    }
+
+    /**
+     * Read a direction in the layout of `direction_byte`.
+     */
+    pub fn direction(&mut self) -> Result<Direction, Box<dyn Error>> {
+        match self.u8()? {
+            0 => Ok(Direction::Idle),
+            1 => Ok(Direction::Up),
+            2 => Ok(Direction::Right),
+            3 => Ok(Direction::Down),
+            4 => Ok(Direction::Left),
+            _ => Err("unknown direction".into()),
+        }
+    }
+}
+
+/**
+ * Write a direction in a byte.
+ */
+pub fn direction_byte(direction: Direction) -> u8 {
+    match direction {
+        Direction::Idle => 0,
+        Direction::Up => 1,
+        Direction::Right => 2,
+        Direction::Down => 3,
+        Direction::Left => 4,
+    }
+}
+
+/**
+ * Write a string prefixed with its length in a byte.
+ */
+pub fn short_string_bytes(value: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let size = u8::try_from(value.len()).map_err(|_| "string too long")?;
+
+    Ok([&[size] as &[u8], value.as_bytes()].concat())
+}
@@ -2,13 +2,224 @@ use std::error::Error;
 
 use east_online_core::model::Direction;
 
-#[derive(Debug)]
+use super::{direction_byte, short_string_bytes, Body, ID_LENGTH, ITEM_ID_LENGTH};
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Incoming {
-    Hello { token: String },
-    Move { direction: Direction },
+    Hello {
+        version: u16,
+        features: Vec<String>,
+        token: String,
+    },
+    Move {
+        direction: Direction,
+    },
+    Inventory,
+    MoveItem {
+        from: u8,
+        to: u8,
+    },
+    UseItem {
+        slot: u8,
+    },
+    DropItem {
+        slot: u8,
+        quantity: u16,
+    },
+    Attack,
+    PartyInvite {
+        user_id: String,
+    },
+    PartyAccept {
+        user_id: String,
+    },
+    PartyLeave,
+    PartyKick {
+        user_id: String,
+    },
+    FriendRequest {
+        user_id: String,
+    },
+    FriendAccept {
+        user_id: String,
+    },
+    TradeRequest {
+        user_id: String,
+    },
+    TradeAccept {
+        user_id: String,
+    },
+    TradeOffer {
+        slots: Vec<(u8, u16)>,
+        coins: u64,
+    },
+    TradeLock,
+    TradeConfirm,
+    TradeCancel,
+    ShopOpen {
+        shop_id: String,
+    },
+    ShopBuy {
+        shop_id: String,
+        item_id: String,
+        quantity: u16,
+    },
+    ShopSell {
+        shop_id: String,
+        slot: u8,
+        quantity: u16,
+    },
+    Talk {
+        npc_id: String,
+    },
+    Interact,
+    /**
+     * Play an animation, such as waving or sitting, for nearby players.
+     */
+    Emote {
+        emote: u8,
+    },
 }
 
 impl Incoming {
+    /**
+     * Name of the packet, used to pick its rate limit.
+     */
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Incoming::Hello { .. } => "hello",
+            Incoming::Move { .. } => "move",
+            Incoming::Inventory => "inventory",
+            Incoming::MoveItem { .. } => "move_item",
+            Incoming::UseItem { .. } => "use_item",
+            Incoming::DropItem { .. } => "drop_item",
+            Incoming::Attack => "attack",
+            Incoming::PartyInvite { .. } => "party_invite",
+            Incoming::PartyAccept { .. } => "party_accept",
+            Incoming::PartyLeave => "party_leave",
+            Incoming::PartyKick { .. } => "party_kick",
+            Incoming::FriendRequest { .. } => "friend_request",
+            Incoming::FriendAccept { .. } => "friend_accept",
+            Incoming::TradeRequest { .. } => "trade_request",
+            Incoming::TradeAccept { .. } => "trade_accept",
+            Incoming::TradeOffer { .. } => "trade_offer",
+            Incoming::TradeLock => "trade_lock",
+            Incoming::TradeConfirm => "trade_confirm",
+            Incoming::TradeCancel => "trade_cancel",
+            Incoming::ShopOpen { .. } => "shop_open",
+            Incoming::ShopBuy { .. } => "shop_buy",
+            Incoming::ShopSell { .. } => "shop_sell",
+            Incoming::Talk { .. } => "talk",
+            Incoming::Interact => "interact",
+            Incoming::Emote { .. } => "emote",
+        }
+    }
+
+    pub fn serialize(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        match self {
+            Incoming::Hello {
+                version,
+                features,
+                token,
+            } if *version == 1 && features.is_empty() => {
+                Ok([&[1 as u8, 0] as &[u8], token.as_bytes()].concat())
+            }
+            Incoming::Hello {
+                version,
+                features,
+                token,
+            } => {
+                let count = u8::try_from(features.len()).map_err(|_| "too many features")?;
+
+                let features = features
+                    .iter()
+                    .map(|feature| short_string_bytes(feature))
+                    .collect::<Result<Vec<_>, _>>()?
+                    .concat();
+
+                Ok([
+                    &[8 as u8, 0] as &[u8],
+                    &version.to_le_bytes(),
+                    &[count],
+                    &features,
+                    token.as_bytes(),
+                ]
+                .concat())
+            }
+            Incoming::Move { direction } => Ok(vec![2, 0, direction_byte(*direction)]),
+            Incoming::Inventory => Ok(vec![3, 0]),
+            Incoming::MoveItem { from, to } => Ok(vec![4, 0, *from, *to]),
+            Incoming::UseItem { slot } => Ok(vec![5, 0, *slot]),
+            Incoming::DropItem { slot, quantity } => {
+                Ok([&[6 as u8, 0, *slot] as &[u8], &quantity.to_le_bytes()].concat())
+            }
+            Incoming::Attack => Ok(vec![7, 0]),
+            Incoming::PartyInvite { user_id } => {
+                Ok([&[9 as u8, 0] as &[u8], user_id.as_bytes()].concat())
+            }
+            Incoming::PartyAccept { user_id } => {
+                Ok([&[10 as u8, 0] as &[u8], user_id.as_bytes()].concat())
+            }
+            Incoming::PartyLeave => Ok(vec![11, 0]),
+            Incoming::PartyKick { user_id } => {
+                Ok([&[12 as u8, 0] as &[u8], user_id.as_bytes()].concat())
+            }
+            Incoming::FriendRequest { user_id } => {
+                Ok([&[13 as u8, 0] as &[u8], user_id.as_bytes()].concat())
+            }
+            Incoming::FriendAccept { user_id } => {
+                Ok([&[14 as u8, 0] as &[u8], user_id.as_bytes()].concat())
+            }
+            Incoming::TradeRequest { user_id } => {
+                Ok([&[15 as u8, 0] as &[u8], user_id.as_bytes()].concat())
+            }
+            Incoming::TradeAccept { user_id } => {
+                Ok([&[16 as u8, 0] as &[u8], user_id.as_bytes()].concat())
+            }
+            Incoming::TradeOffer { slots, coins } => {
+                let slots: Vec<u8> = slots
+                    .iter()
+                    .flat_map(|(slot, quantity)| {
+                        [&[*slot] as &[u8], &quantity.to_le_bytes()].concat()
+                    })
+                    .collect();
+
+                Ok([&[17 as u8, 0] as &[u8], &coins.to_le_bytes(), &slots].concat())
+            }
+            Incoming::TradeLock => Ok(vec![18, 0]),
+            Incoming::TradeConfirm => Ok(vec![19, 0]),
+            Incoming::TradeCancel => Ok(vec![20, 0]),
+            Incoming::ShopOpen { shop_id } => {
+                Ok([&[21 as u8, 0] as &[u8], shop_id.as_bytes()].concat())
+            }
+            Incoming::ShopBuy {
+                shop_id,
+                item_id,
+                quantity,
+            } => Ok([
+                &[22 as u8, 0] as &[u8],
+                shop_id.as_bytes(),
+                item_id.as_bytes(),
+                &quantity.to_le_bytes(),
+            ]
+            .concat()),
+            Incoming::ShopSell {
+                shop_id,
+                slot,
+                quantity,
+            } => Ok([
+                &[23 as u8, 0] as &[u8],
+                shop_id.as_bytes(),
+                &[*slot],
+                &quantity.to_le_bytes(),
+            ]
+            .concat()),
+            Incoming::Talk { npc_id } => Ok([&[24 as u8, 0] as &[u8], npc_id.as_bytes()].concat()),
+            Incoming::Interact => Ok(vec![25, 0]),
+            Incoming::Emote { emote } => Ok(vec![26, 0, *emote]),
+        }
+    }
+
     pub fn deserialize(buf: &[u8]) -> Result<Self, Box<dyn Error>> {
         if buf.len() < 2 {
             return Err(format!("buffer too short to deserialize, {buf:?}").into());
@@ -20,20 +231,123 @@ impl Incoming {
 
         match serial {
             1 => Ok(Self::Hello {
+                version: 1,
+                features: Vec::new(),
                 token: String::from_utf8_lossy(body).to_string(),
             }),
-            2 => {
-                let direction = match &body[0] {
-                    0 => Direction::Idle,
-                    1 => Direction::Up,
-                    2 => Direction::Right,
-                    3 => Direction::Down,
-                    4 => Direction::Left,
-                    _ => return Err("unknown direction".into()),
-                };
+            2 => Ok(Self::Move {
+                direction: Body::new(body).direction()?,
+            }),
+            3 => Ok(Self::Inventory),
+            4 => {
+                if body.len() < 2 {
+                    return Err("move item body too short".into());
+                }
+
+                Ok(Self::MoveItem {
+                    from: body[0],
+                    to: body[1],
+                })
+            }
+            5 => {
+                if body.is_empty() {
+                    return Err("use item body too short".into());
+                }
+
+                Ok(Self::UseItem { slot: body[0] })
+            }
+            6 => {
+                if body.len() < 3 {
+                    return Err("drop item body too short".into());
+                }
+
+                Ok(Self::DropItem {
+                    slot: body[0],
+                    quantity: u16::from_le_bytes([body[1], body[2]]),
+                })
+            }
+            7 => Ok(Self::Attack),
+            8 => {
+                let mut body = Body::new(body);
+
+                let version = body.u16()?;
+
+                let features = (0..body.u8()?)
+                    .map(|_| body.short_string())
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(Self::Hello {
+                    version,
+                    features,
+                    token: body.rest_string()?,
+                })
+            }
+            9 => Ok(Self::PartyInvite {
+                user_id: Body::new(body).string(ID_LENGTH)?,
+            }),
+            10 => Ok(Self::PartyAccept {
+                user_id: Body::new(body).string(ID_LENGTH)?,
+            }),
+            11 => Ok(Self::PartyLeave),
+            12 => Ok(Self::PartyKick {
+                user_id: Body::new(body).string(ID_LENGTH)?,
+            }),
+            13 => Ok(Self::FriendRequest {
+                user_id: Body::new(body).string(ID_LENGTH)?,
+            }),
+            14 => Ok(Self::FriendAccept {
+                user_id: Body::new(body).string(ID_LENGTH)?,
+            }),
+            15 => Ok(Self::TradeRequest {
+                user_id: Body::new(body).string(ID_LENGTH)?,
+            }),
+            16 => Ok(Self::TradeAccept {
+                user_id: Body::new(body).string(ID_LENGTH)?,
+            }),
+            17 => {
+                let mut body = Body::new(body);
+
+                let coins = body.u64()?;
+
+                let mut slots = Vec::new();
+
+                while !body.is_empty() {
+                    slots.push((body.u8()?, body.u16()?));
+                }
+
+                Ok(Self::TradeOffer { slots, coins })
+            }
+            18 => Ok(Self::TradeLock),
+            19 => Ok(Self::TradeConfirm),
+            20 => Ok(Self::TradeCancel),
+            21 => Ok(Self::ShopOpen {
+                shop_id: Body::new(body).string(ID_LENGTH)?,
+            }),
+            22 => {
+                let mut body = Body::new(body);
+
+                Ok(Self::ShopBuy {
+                    shop_id: body.string(ID_LENGTH)?,
+                    item_id: body.string(ITEM_ID_LENGTH)?,
+                    quantity: body.u16()?,
+                })
+            }
+            23 => {
+                let mut body = Body::new(body);
 
-                Ok(Self::Move { direction })
+                Ok(Self::ShopSell {
+                    shop_id: body.string(ID_LENGTH)?,
+                    slot: body.u8()?,
+                    quantity: body.u16()?,
+                })
             }
+            24 => Ok(Self::Talk {
+                npc_id: Body::new(body).string(ID_LENGTH)?,
+            }),
+            25 => Ok(Self::Interact),
+            26 => Ok(Self::Emote {
+                emote: Body::new(body).u8()?,
+            }),
             n => Err(format!("unexpected packet arrived, {n:?}").into()),
         }
     }
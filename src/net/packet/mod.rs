@@ -1,7 +1,13 @@
+mod body;
+
+pub use body::{
+    direction_byte, short_string_bytes, Body, ID_LENGTH, ITEM_ID_LENGTH, MAP_ID_LENGTH,
+};
+
 mod incoming;
 
 pub use incoming::Incoming;
 
 mod outgoing;
 
-pub use outgoing::Outgoing;
+pub use outgoing::{ActorSnapshot, Outgoing, TradeSide};
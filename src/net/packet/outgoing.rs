@@ -2,14 +2,85 @@ use std::error::Error;
 
 use tokio::time;
 
-use east_online_core::model::Vector3;
+use east_online_core::model::{Direction, Vector3};
 
-#[derive(Debug)]
+use crate::{
+    item,
+    map::{ObjectKind, Profile},
+    net::protocol,
+    shop,
+};
+
+use super::{direction_byte, short_string_bytes, Body, ID_LENGTH, ITEM_ID_LENGTH, MAP_ID_LENGTH};
+
+/**
+ * An actor as shown to a player entering the map.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActorSnapshot {
+    pub id: String,
+    pub position: Vector3,
+    pub facing: Direction,
+    pub profile: Profile,
+}
+
+impl ActorSnapshot {
+    fn to_bytes(&self, version: u16) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut buf = [self.id.as_bytes(), &self.position.to_bytes()].concat();
+
+        if version >= protocol::FACING_VERSION {
+            buf.push(direction_byte(self.facing));
+        }
+
+        if version >= protocol::PROFILE_VERSION {
+            buf.extend(profile_bytes(&self.profile)?);
+        }
+
+        Ok(buf)
+    }
+
+    fn read(body: &mut Body, version: u16) -> Result<Self, Box<dyn Error>> {
+        let id = body.string(ID_LENGTH)?;
+
+        let position = body.vector3()?;
+
+        let facing = match version >= protocol::FACING_VERSION {
+            true => body.direction()?,
+            false => Direction::Down,
+        };
+
+        let profile = match version >= protocol::PROFILE_VERSION {
+            true => read_profile(body)?,
+            false => Profile::default(),
+        };
+
+        Ok(ActorSnapshot {
+            id,
+            position,
+            facing,
+            profile,
+        })
+    }
+}
+
+/**
+ * What one side of a trade offers, as seen by both.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeSide {
+    pub id: String,
+    pub items: Vec<item::Stack>,
+    pub coins: u64,
+    pub locked: bool,
+    pub confirmed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Outgoing {
     Hello {
         id: String,
         map_id: String,
-        actors: Vec<(String, Vector3)>,
+        actors: Vec<ActorSnapshot>,
     },
     Move {
         id: String,
@@ -19,19 +90,129 @@ pub enum Outgoing {
     Stop {
         id: String,
         position: Vector3,
+        facing: Direction,
+    },
+    Inventory {
+        slots: Vec<(u8, item::Stack)>,
+    },
+    TileItems {
+        position: Vector3,
+        items: Vec<item::Stack>,
+    },
+    UseItem {
+        id: String,
+        item_id: String,
+    },
+    Damage {
+        id: String,
+        target: String,
+        amount: u32,
+        health: u32,
+    },
+    Die {
+        id: String,
+    },
+    Respawn {
+        id: String,
+        position: Vector3,
+        health: u32,
+    },
+    Error {
+        code: u16,
+        message: String,
+    },
+    Capabilities {
+        version: u16,
+        features: Vec<String>,
+    },
+    Queue {
+        position: u32,
+    },
+    Notice {
+        message: String,
+    },
+    MapUpdate {
+        tiles: Vec<(Vector3, Option<(String, u8)>)>,
+    },
+    PartyInvite {
+        id: String,
+    },
+    /**
+     * Members of the party led by the first one, empty once out of it.
+     */
+    Party {
+        members: Vec<String>,
+    },
+    PartyMember {
+        id: String,
+        map_id: String,
+        position: Vector3,
+    },
+    FriendRequest {
+        id: String,
+    },
+    /**
+     * The map a friend is on, or `None` once it went offline.
+     */
+    Presence {
+        id: String,
+        map_id: Option<String>,
+    },
+    TradeRequest {
+        id: String,
+    },
+    Trade {
+        sides: Vec<TradeSide>,
+    },
+    TradeClosed {
+        message: String,
+    },
+    Wallet {
+        coins: u64,
+    },
+    Shop {
+        id: String,
+        goods: Vec<shop::Goods>,
+    },
+    Quest {
+        id: String,
+        counts: Vec<u16>,
+        completed: bool,
+    },
+    Object {
+        id: String,
+        position: Vector3,
+        kind: ObjectKind,
+        open: bool,
+    },
+    Emote {
+        id: String,
+        emote: u8,
+    },
+    Profile {
+        id: String,
+        profile: Profile,
     },
 }
 
 impl Outgoing {
     pub fn serialize(self) -> Result<Vec<u8>, Box<dyn Error>> {
+        self.serialize_for(protocol::PROTOCOL_VERSION)
+    }
+
+    /**
+     * Write a packet in the layout a version of the protocol reads.
+     *
+     * Older clients get actors without the parts they don't know about.
+     */
+    pub fn serialize_for(self, version: u16) -> Result<Vec<u8>, Box<dyn Error>> {
         match self {
             Outgoing::Hello { id, map_id, actors } => {
-                let users: Vec<u8> = actors
+                let users = actors
                     .iter()
-                    .flat_map(|(user_id, position)| {
-                        [user_id.as_bytes(), &position.to_bytes()].concat()
-                    })
-                    .collect();
+                    .map(|actor| actor.to_bytes(version))
+                    .collect::<Result<Vec<_>, _>>()?
+                    .concat();
 
                 Ok([
                     &[1 as u8, 0] as &[u8],
@@ -52,9 +233,508 @@ impl Outgoing {
                 &i64::try_from(duration.as_millis())?.to_le_bytes(),
             ]
             .concat()),
-            Outgoing::Stop { id, position } => {
-                Ok([&[3 as u8, 0] as &[u8], id.as_bytes(), &position.to_bytes()].concat())
+            Outgoing::Stop {
+                id,
+                position,
+                facing,
+            } => {
+                let facing = match version >= protocol::FACING_VERSION {
+                    true => vec![direction_byte(facing)],
+                    false => vec![],
+                };
+
+                Ok([
+                    &[3 as u8, 0] as &[u8],
+                    id.as_bytes(),
+                    &position.to_bytes(),
+                    &facing,
+                ]
+                .concat())
+            }
+            Outgoing::Inventory { slots } => {
+                let slots: Vec<u8> = slots
+                    .iter()
+                    .flat_map(|(slot, stack)| {
+                        [
+                            &[*slot] as &[u8],
+                            stack.item_id.as_bytes(),
+                            &stack.quantity.to_le_bytes(),
+                        ]
+                        .concat()
+                    })
+                    .collect();
+
+                Ok([&[4 as u8, 0] as &[u8], &slots].concat())
+            }
+            Outgoing::TileItems { position, items } => {
+                let items: Vec<u8> = items
+                    .iter()
+                    .flat_map(|stack| {
+                        [stack.item_id.as_bytes(), &stack.quantity.to_le_bytes()].concat()
+                    })
+                    .collect();
+
+                Ok([&[5 as u8, 0] as &[u8], &position.to_bytes(), &items].concat())
+            }
+            Outgoing::UseItem { id, item_id } => {
+                Ok([&[6 as u8, 0] as &[u8], id.as_bytes(), item_id.as_bytes()].concat())
+            }
+            Outgoing::Damage {
+                id,
+                target,
+                amount,
+                health,
+            } => Ok([
+                &[7 as u8, 0] as &[u8],
+                id.as_bytes(),
+                target.as_bytes(),
+                &amount.to_le_bytes(),
+                &health.to_le_bytes(),
+            ]
+            .concat()),
+            Outgoing::Die { id } => Ok([&[8 as u8, 0] as &[u8], id.as_bytes()].concat()),
+            Outgoing::Respawn {
+                id,
+                position,
+                health,
+            } => Ok([
+                &[9 as u8, 0] as &[u8],
+                id.as_bytes(),
+                &position.to_bytes(),
+                &health.to_le_bytes(),
+            ]
+            .concat()),
+            Outgoing::Error { code, message } => Ok([
+                &[10 as u8, 0] as &[u8],
+                &code.to_le_bytes(),
+                message.as_bytes(),
+            ]
+            .concat()),
+            Outgoing::Capabilities { version, features } => {
+                let count = u8::try_from(features.len())?;
+
+                let features = features
+                    .iter()
+                    .map(|feature| short_string_bytes(feature))
+                    .collect::<Result<Vec<_>, _>>()?
+                    .concat();
+
+                Ok([
+                    &[11 as u8, 0] as &[u8],
+                    &version.to_le_bytes(),
+                    &[count],
+                    &features,
+                ]
+                .concat())
+            }
+            Outgoing::Queue { position } => {
+                Ok([&[12 as u8, 0] as &[u8], &position.to_le_bytes()].concat())
+            }
+            Outgoing::Notice { message } => {
+                Ok([&[13 as u8, 0] as &[u8], message.as_bytes()].concat())
+            }
+            Outgoing::MapUpdate { tiles } => {
+                let tiles = tiles
+                    .iter()
+                    .map(|(position, tile)| match tile {
+                        Some((model_id, rotation)) => Ok([
+                            &position.to_bytes() as &[u8],
+                            &[1],
+                            &short_string_bytes(model_id)?,
+                            &[*rotation],
+                        ]
+                        .concat()),
+                        None => Ok([&position.to_bytes() as &[u8], &[0]].concat()),
+                    })
+                    .collect::<Result<Vec<_>, Box<dyn Error>>>()?
+                    .concat();
+
+                Ok([&[14 as u8, 0] as &[u8], &tiles].concat())
+            }
+            Outgoing::PartyInvite { id } => Ok([&[15 as u8, 0] as &[u8], id.as_bytes()].concat()),
+            Outgoing::Party { members } => {
+                let count = u8::try_from(members.len()).map_err(|_| "too many members")?;
+
+                Ok([&[16 as u8, 0, count] as &[u8], members.concat().as_bytes()].concat())
+            }
+            Outgoing::PartyMember {
+                id,
+                map_id,
+                position,
+            } => Ok([
+                &[17 as u8, 0] as &[u8],
+                id.as_bytes(),
+                map_id.as_bytes(),
+                &position.to_bytes(),
+            ]
+            .concat()),
+            Outgoing::FriendRequest { id } => Ok([&[18 as u8, 0] as &[u8], id.as_bytes()].concat()),
+            Outgoing::Presence { id, map_id } => Ok([
+                &[19 as u8, 0] as &[u8],
+                id.as_bytes(),
+                &short_string_bytes(map_id.as_deref().unwrap_or_default())?,
+            ]
+            .concat()),
+            Outgoing::TradeRequest { id } => Ok([&[20 as u8, 0] as &[u8], id.as_bytes()].concat()),
+            Outgoing::Trade { sides } => {
+                let count = u8::try_from(sides.len()).map_err(|_| "too many sides")?;
+
+                let sides = sides
+                    .iter()
+                    .map(|side| {
+                        let count = u8::try_from(side.items.len()).map_err(|_| "too many items")?;
+
+                        let flags = u8::from(side.locked) | u8::from(side.confirmed) << 1;
+
+                        let items: Vec<u8> = side
+                            .items
+                            .iter()
+                            .flat_map(|stack| {
+                                [stack.item_id.as_bytes(), &stack.quantity.to_le_bytes()].concat()
+                            })
+                            .collect();
+
+                        Ok([
+                            side.id.as_bytes(),
+                            &side.coins.to_le_bytes(),
+                            &[flags, count],
+                            &items,
+                        ]
+                        .concat())
+                    })
+                    .collect::<Result<Vec<_>, Box<dyn Error>>>()?
+                    .concat();
+
+                Ok([&[21 as u8, 0, count] as &[u8], &sides].concat())
+            }
+            Outgoing::TradeClosed { message } => {
+                Ok([&[22 as u8, 0] as &[u8], message.as_bytes()].concat())
+            }
+            Outgoing::Wallet { coins } => {
+                Ok([&[23 as u8, 0] as &[u8], &coins.to_le_bytes()].concat())
+            }
+            Outgoing::Shop { id, goods } => {
+                let goods: Vec<u8> = goods
+                    .iter()
+                    .flat_map(|goods| {
+                        let flags = u8::from(goods.sell_price.is_some())
+                            | u8::from(goods.stock.is_some()) << 1;
+
+                        [
+                            goods.item_id.as_bytes(),
+                            &goods.price.to_le_bytes(),
+                            &[flags],
+                            &goods.sell_price.unwrap_or_default().to_le_bytes(),
+                            &goods.stock.unwrap_or_default().to_le_bytes(),
+                        ]
+                        .concat()
+                    })
+                    .collect();
+
+                Ok([&[24 as u8, 0] as &[u8], id.as_bytes(), &goods].concat())
+            }
+            Outgoing::Quest {
+                id,
+                counts,
+                completed,
+            } => {
+                let counts: Vec<u8> = counts
+                    .iter()
+                    .flat_map(|count| count.to_le_bytes())
+                    .collect();
+
+                Ok([
+                    &[25 as u8, 0] as &[u8],
+                    &short_string_bytes(&id)?,
+                    &[u8::from(completed)],
+                    &counts,
+                ]
+                .concat())
+            }
+            Outgoing::Object {
+                id,
+                position,
+                kind,
+                open,
+            } => Ok([
+                &[26 as u8, 0] as &[u8],
+                &position.to_bytes(),
+                &[kind.to_byte(), u8::from(open)],
+                id.as_bytes(),
+            ]
+            .concat()),
+            Outgoing::Emote { id, emote } => {
+                Ok([&[27 as u8, 0] as &[u8], id.as_bytes(), &[emote]].concat())
+            }
+            Outgoing::Profile { id, profile } => Ok([
+                &[28 as u8, 0] as &[u8],
+                id.as_bytes(),
+                &profile_bytes(&profile)?,
+            ]
+            .concat()),
+        }
+    }
+
+    pub fn deserialize(buf: &[u8]) -> Result<Self, Box<dyn Error>> {
+        Outgoing::deserialize_for(buf, protocol::PROTOCOL_VERSION)
+    }
+
+    /**
+     * Read a packet in the layout a version of the protocol writes.
+     *
+     * Actors sent to older clients face down and have a default profile.
+     */
+    pub fn deserialize_for(buf: &[u8], version: u16) -> Result<Self, Box<dyn Error>> {
+        let mut body = Body::new(buf);
+
+        let serial = body.u16()?;
+
+        match serial {
+            1 => {
+                let id = body.string(ID_LENGTH)?;
+
+                let map_id = body.string(MAP_ID_LENGTH)?;
+
+                let mut actors = Vec::new();
+
+                while !body.is_empty() {
+                    actors.push(ActorSnapshot::read(&mut body, version)?);
+                }
+
+                Ok(Outgoing::Hello { id, map_id, actors })
+            }
+            2 => Ok(Outgoing::Move {
+                id: body.string(ID_LENGTH)?,
+                position: body.vector3()?,
+                duration: time::Duration::from_millis(u64::try_from(body.i64()?)?),
+            }),
+            3 => Ok(Outgoing::Stop {
+                id: body.string(ID_LENGTH)?,
+                position: body.vector3()?,
+                facing: match version >= protocol::FACING_VERSION {
+                    true => body.direction()?,
+                    false => Direction::Down,
+                },
+            }),
+            4 => {
+                let mut slots = Vec::new();
+
+                while !body.is_empty() {
+                    let slot = body.u8()?;
+
+                    let stack = item::Stack::new(body.string(ITEM_ID_LENGTH)?, body.u16()?);
+
+                    slots.push((slot, stack));
+                }
+
+                Ok(Outgoing::Inventory { slots })
+            }
+            5 => {
+                let position = body.vector3()?;
+
+                let mut items = Vec::new();
+
+                while !body.is_empty() {
+                    items.push(item::Stack::new(body.string(ITEM_ID_LENGTH)?, body.u16()?));
+                }
+
+                Ok(Outgoing::TileItems { position, items })
+            }
+            6 => Ok(Outgoing::UseItem {
+                id: body.string(ID_LENGTH)?,
+                item_id: body.string(ITEM_ID_LENGTH)?,
+            }),
+            7 => Ok(Outgoing::Damage {
+                id: body.string(ID_LENGTH)?,
+                target: body.string(ID_LENGTH)?,
+                amount: body.u32()?,
+                health: body.u32()?,
+            }),
+            8 => Ok(Outgoing::Die {
+                id: body.string(ID_LENGTH)?,
+            }),
+            9 => Ok(Outgoing::Respawn {
+                id: body.string(ID_LENGTH)?,
+                position: body.vector3()?,
+                health: body.u32()?,
+            }),
+            10 => Ok(Outgoing::Error {
+                code: body.u16()?,
+                message: body.rest_string()?,
+            }),
+            11 => {
+                let version = body.u16()?;
+
+                let features = (0..body.u8()?)
+                    .map(|_| body.short_string())
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(Outgoing::Capabilities { version, features })
+            }
+            12 => Ok(Outgoing::Queue {
+                position: body.u32()?,
+            }),
+            13 => Ok(Outgoing::Notice {
+                message: body.rest_string()?,
+            }),
+            14 => {
+                let mut tiles = Vec::new();
+
+                while !body.is_empty() {
+                    let position = body.vector3()?;
+
+                    let tile = match body.u8()? {
+                        0 => None,
+                        _ => Some((body.short_string()?, body.u8()?)),
+                    };
+
+                    tiles.push((position, tile));
+                }
+
+                Ok(Outgoing::MapUpdate { tiles })
+            }
+            15 => Ok(Outgoing::PartyInvite {
+                id: body.string(ID_LENGTH)?,
+            }),
+            16 => {
+                let members = (0..body.u8()?)
+                    .map(|_| body.string(ID_LENGTH))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(Outgoing::Party { members })
+            }
+            17 => Ok(Outgoing::PartyMember {
+                id: body.string(ID_LENGTH)?,
+                map_id: body.string(MAP_ID_LENGTH)?,
+                position: body.vector3()?,
+            }),
+            18 => Ok(Outgoing::FriendRequest {
+                id: body.string(ID_LENGTH)?,
+            }),
+            19 => {
+                let id = body.string(ID_LENGTH)?;
+
+                let map_id = Some(body.short_string()?).filter(|map_id| !map_id.is_empty());
+
+                Ok(Outgoing::Presence { id, map_id })
+            }
+            20 => Ok(Outgoing::TradeRequest {
+                id: body.string(ID_LENGTH)?,
+            }),
+            21 => {
+                let mut sides = Vec::new();
+
+                for _ in 0..body.u8()? {
+                    let id = body.string(ID_LENGTH)?;
+
+                    let coins = body.u64()?;
+
+                    let flags = body.u8()?;
+
+                    let items = (0..body.u8()?)
+                        .map(|_| Ok(item::Stack::new(body.string(ITEM_ID_LENGTH)?, body.u16()?)))
+                        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+
+                    sides.push(TradeSide {
+                        id,
+                        items,
+                        coins,
+                        locked: flags & 1 != 0,
+                        confirmed: flags & 2 != 0,
+                    });
+                }
+
+                Ok(Outgoing::Trade { sides })
+            }
+            22 => Ok(Outgoing::TradeClosed {
+                message: body.rest_string()?,
+            }),
+            23 => Ok(Outgoing::Wallet { coins: body.u64()? }),
+            24 => {
+                let id = body.string(ID_LENGTH)?;
+
+                let mut goods = Vec::new();
+
+                while !body.is_empty() {
+                    let item_id = body.string(ITEM_ID_LENGTH)?;
+
+                    let price = body.u64()?;
+
+                    let flags = body.u8()?;
+
+                    let sell_price = body.u64()?;
+
+                    let stock = body.u16()?;
+
+                    goods.push(shop::Goods {
+                        item_id,
+                        price,
+                        sell_price: (flags & 1 != 0).then_some(sell_price),
+                        stock: (flags & 2 != 0).then_some(stock),
+                    });
+                }
+
+                Ok(Outgoing::Shop { id, goods })
             }
+            25 => {
+                let id = body.short_string()?;
+
+                let completed = body.u8()? != 0;
+
+                let mut counts = Vec::new();
+
+                while !body.is_empty() {
+                    counts.push(body.u16()?);
+                }
+
+                Ok(Outgoing::Quest {
+                    id,
+                    counts,
+                    completed,
+                })
+            }
+            26 => {
+                let position = body.vector3()?;
+
+                let kind = ObjectKind::from_byte(body.u8()?).ok_or("unknown object kind")?;
+
+                let open = body.u8()? != 0;
+
+                Ok(Outgoing::Object {
+                    id: body.rest_string()?,
+                    position,
+                    kind,
+                    open,
+                })
+            }
+            27 => Ok(Outgoing::Emote {
+                id: body.string(ID_LENGTH)?,
+                emote: body.u8()?,
+            }),
+            28 => Ok(Outgoing::Profile {
+                id: body.string(ID_LENGTH)?,
+                profile: read_profile(&mut body)?,
+            }),
+            n => Err(format!("unexpected packet arrived, {n:?}").into()),
         }
     }
 }
+
+fn profile_bytes(profile: &Profile) -> Result<Vec<u8>, Box<dyn Error>> {
+    Ok([
+        &profile.level.to_le_bytes() as &[u8],
+        &short_string_bytes(&profile.name)?,
+        &short_string_bytes(&profile.appearance)?,
+    ]
+    .concat())
+}
+
+fn read_profile(body: &mut Body) -> Result<Profile, Box<dyn Error>> {
+    let level = body.u16()?;
+
+    Ok(Profile::new(
+        body.short_string()?,
+        body.short_string()?,
+        level,
+    ))
+}
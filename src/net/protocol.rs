@@ -0,0 +1,142 @@
+use super::packet;
+
+/**
+ * Version of the protocol this server speaks.
+ */
+pub const PROTOCOL_VERSION: u16 = 4;
+
+/**
+ * Oldest version of the protocol still accepted.
+ */
+pub const MIN_PROTOCOL_VERSION: u16 = 1;
+
+/**
+ * First version that tells which way actors face.
+ */
+pub const FACING_VERSION: u16 = 3;
+
+/**
+ * First version that shows the profiles of actors.
+ */
+pub const PROFILE_VERSION: u16 = 4;
+
+pub const FEATURE_INVENTORY: &str = "inventory";
+
+pub const FEATURE_COMBAT: &str = "combat";
+
+pub const FEATURE_PARTY: &str = "party";
+
+pub const FEATURE_FRIENDS: &str = "friends";
+
+pub const FEATURE_TRADE: &str = "trade";
+
+pub const FEATURE_SHOP: &str = "shop";
+
+pub const FEATURE_QUESTS: &str = "quests";
+
+pub const FEATURE_OBJECTS: &str = "objects";
+
+pub const FEATURE_EMOTES: &str = "emotes";
+
+/**
+ * Every feature a client may ask for.
+ */
+pub const FEATURES: &[&str] = &[
+    FEATURE_INVENTORY,
+    FEATURE_COMBAT,
+    FEATURE_PARTY,
+    FEATURE_FRIENDS,
+    FEATURE_TRADE,
+    FEATURE_SHOP,
+    FEATURE_QUESTS,
+    FEATURE_OBJECTS,
+    FEATURE_EMOTES,
+];
+
+pub const ERROR_INCOMPATIBLE_VERSION: u16 = 1;
+
+pub const ERROR_PARTY: u16 = 2;
+
+pub const ERROR_FRIEND: u16 = 3;
+
+pub const ERROR_TRADE: u16 = 4;
+
+pub const ERROR_SHOP: u16 = 5;
+
+pub const ERROR_EMOTE: u16 = 6;
+
+/**
+ * What a connection agreed on during the handshake.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    pub version: u16,
+    pub features: Vec<String>,
+}
+
+impl Capabilities {
+    /**
+     * Agree on the features both sides know.
+     *
+     * Throw an error if the version can't be served.
+     */
+    pub fn negotiate(version: u16, features: &[String]) -> Result<Self, String> {
+        if !(MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&version) {
+            return Err(format!(
+                "protocol version {} is not supported, expected {} to {}",
+                version, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION
+            ));
+        }
+
+        let features = features
+            .iter()
+            .filter(|feature| FEATURES.contains(&feature.as_str()))
+            .cloned()
+            .collect();
+
+        Ok(Capabilities { version, features })
+    }
+
+    pub fn has(&self, feature: &str) -> bool {
+        self.features.iter().any(|known| known == feature)
+    }
+
+    /**
+     * Check if the client understands a packet.
+     *
+     * Clients of the first version only know the packets they shipped with.
+     */
+    pub fn supports(&self, packet: &packet::Outgoing) -> bool {
+        match packet {
+            packet::Outgoing::Hello { .. }
+            | packet::Outgoing::Move { .. }
+            | packet::Outgoing::Stop { .. } => true,
+            packet::Outgoing::Error { .. }
+            | packet::Outgoing::Capabilities { .. }
+            | packet::Outgoing::Queue { .. }
+            | packet::Outgoing::Notice { .. }
+            | packet::Outgoing::MapUpdate { .. } => self.version >= 2,
+            packet::Outgoing::Inventory { .. }
+            | packet::Outgoing::TileItems { .. }
+            | packet::Outgoing::UseItem { .. }
+            | packet::Outgoing::Wallet { .. } => self.has(FEATURE_INVENTORY),
+            packet::Outgoing::Damage { .. }
+            | packet::Outgoing::Die { .. }
+            | packet::Outgoing::Respawn { .. } => self.has(FEATURE_COMBAT),
+            packet::Outgoing::PartyInvite { .. }
+            | packet::Outgoing::Party { .. }
+            | packet::Outgoing::PartyMember { .. } => self.has(FEATURE_PARTY),
+            packet::Outgoing::FriendRequest { .. } | packet::Outgoing::Presence { .. } => {
+                self.has(FEATURE_FRIENDS)
+            }
+            packet::Outgoing::TradeRequest { .. }
+            | packet::Outgoing::Trade { .. }
+            | packet::Outgoing::TradeClosed { .. } => self.has(FEATURE_TRADE),
+            packet::Outgoing::Shop { .. } => self.has(FEATURE_SHOP),
+            packet::Outgoing::Quest { .. } => self.has(FEATURE_QUESTS),
+            packet::Outgoing::Object { .. } => self.has(FEATURE_OBJECTS),
+            packet::Outgoing::Emote { .. } => self.has(FEATURE_EMOTES),
+            packet::Outgoing::Profile { .. } => self.version >= PROFILE_VERSION,
+        }
+    }
+}
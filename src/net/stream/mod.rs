@@ -0,0 +1,63 @@
+use std::{fmt, io, net::SocketAddr};
+
+use tokio::net::TcpStream;
+
+mod tunnel;
+
+pub use tunnel::Tunnel;
+
+/**
+ * A connection accepted by the listener.
+ *
+ * Plain TCP is read and written directly, everything else goes through a
+ * tunnel, so workers handle both the same way.
+ */
+pub enum Stream {
+    Tcp(TcpStream),
+    Tunnel(Tunnel),
+}
+
+impl Stream {
+    pub async fn readable(&self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.readable().await,
+            Stream::Tunnel(tunnel) => tunnel.readable().await,
+        }
+    }
+
+    pub fn try_read_buf(&self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.try_read_buf(buf),
+            Stream::Tunnel(tunnel) => tunnel.try_read_buf(buf),
+        }
+    }
+
+    pub fn try_write(&self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.try_write(buf),
+            Stream::Tunnel(tunnel) => tunnel.try_write(buf),
+        }
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            Stream::Tcp(stream) => stream.peer_addr(),
+            Stream::Tunnel(tunnel) => Ok(tunnel.peer_addr()),
+        }
+    }
+}
+
+impl fmt::Debug for Stream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stream::Tcp(stream) => f.debug_tuple("Tcp").field(stream).finish(),
+            Stream::Tunnel(tunnel) => f.debug_tuple("Tunnel").field(&tunnel.peer_addr()).finish(),
+        }
+    }
+}
+
+impl From<TcpStream> for Stream {
+    fn from(stream: TcpStream) -> Self {
+        Stream::Tcp(stream)
+    }
+}
@@ -0,0 +1,276 @@
+use std::{
+    collections::VecDeque,
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use futures::{SinkExt, StreamExt};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    sync::{mpsc, Notify},
+    task::JoinHandle,
+};
+use tokio_tungstenite::{
+    tungstenite::{protocol::WebSocketConfig, Message},
+    WebSocketStream,
+};
+
+const READ_BUF_SIZE: usize = 4096;
+
+/**
+ * Bytes a tunnel holds for the worker before it stops reading.
+ */
+const INBOUND_LIMIT: usize = 64 * 1024;
+
+/**
+ * Writes a tunnel queues for its writing task, as a socket has a send
+ * buffer. Writes past it fail with `WouldBlock`, as they do on a full socket.
+ */
+const OUTBOUND_CAPACITY: usize = 256;
+
+#[derive(Default)]
+struct Inbound {
+    buf: VecDeque<u8>,
+    closed: bool,
+}
+
+/**
+ * Bytes that arrived on a tunnel, waiting for the worker to take them.
+ */
+#[derive(Default)]
+struct Queue {
+    inbound: Mutex<Inbound>,
+    notify: Notify,
+    drained: Notify,
+}
+
+impl Queue {
+    /**
+     * Wait until the worker took enough bytes for these to fit in the limit,
+     * so a worker that throttles or pauses a stream holds its peer back.
+     *
+     * Return false without taking the bytes if they could never fit.
+     */
+    async fn push(&self, bytes: &[u8]) -> bool {
+        if bytes.len() > INBOUND_LIMIT {
+            return false;
+        }
+
+        loop {
+            let drained = self.drained.notified();
+
+            {
+                let mut inbound = self.inbound.lock().unwrap();
+
+                if inbound.buf.len() + bytes.len() <= INBOUND_LIMIT {
+                    inbound.buf.extend(bytes);
+
+                    self.notify.notify_one();
+
+                    return true;
+                }
+            }
+
+            drained.await;
+        }
+    }
+
+    fn close(&self) {
+        self.inbound.lock().unwrap().closed = true;
+
+        self.notify.notify_one();
+    }
+}
+
+/**
+ * A stream driven by its own tasks, so it can be used without awaiting.
+ *
+ * Arrived bytes wait in a queue until the worker takes them,
+ * and written bytes are queued for the writing task.
+ * Both queues are bounded, and nothing more is read from the peer until
+ * the worker takes what arrived.
+ */
+pub struct Tunnel {
+    peer_addr: SocketAddr,
+    queue: Arc<Queue>,
+    outbound: mpsc::Sender<Vec<u8>>,
+    reader: JoinHandle<()>,
+}
+
+impl Tunnel {
+    /**
+     * Tunnel a byte stream such as TLS.
+     */
+    pub fn spawn<S>(stream: S, peer_addr: SocketAddr) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (mut read_half, mut write_half) = tokio::io::split(stream);
+
+        let queue = Arc::new(Queue::default());
+
+        let (outbound, mut receiver) = mpsc::channel::<Vec<u8>>(OUTBOUND_CAPACITY);
+
+        let reader = {
+            let queue = queue.clone();
+
+            tokio::spawn(async move {
+                let mut buf = [0; READ_BUF_SIZE];
+
+                loop {
+                    match read_half.read(&mut buf).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(size) => {
+                            if !queue.push(&buf[..size]).await {
+                                break;
+                            }
+                        }
+                    }
+                }
+
+                queue.close();
+            })
+        };
+
+        tokio::spawn(async move {
+            while let Some(buf) = receiver.recv().await {
+                if write_half.write_all(&buf).await.is_err() {
+                    return;
+                }
+            }
+
+            write_half.shutdown().await.ok();
+        });
+
+        Tunnel {
+            peer_addr,
+            queue,
+            outbound,
+            reader,
+        }
+    }
+
+    /**
+     * Tunnel a WebSocket, where every binary frame carries framed packets.
+     */
+    pub fn spawn_websocket<S>(stream: WebSocketStream<S>, peer_addr: SocketAddr) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let (mut sink, mut source) = stream.split();
+
+        let queue = Arc::new(Queue::default());
+
+        let (outbound, mut receiver) = mpsc::channel::<Vec<u8>>(OUTBOUND_CAPACITY);
+
+        let reader = {
+            let queue = queue.clone();
+
+            tokio::spawn(async move {
+                while let Some(Ok(message)) = source.next().await {
+                    let is_open = match message {
+                        Message::Binary(buf) => queue.push(&buf).await,
+                        Message::Close(_) => false,
+                        _ => true,
+                    };
+
+                    if !is_open {
+                        break;
+                    }
+                }
+
+                queue.close();
+            })
+        };
+
+        tokio::spawn(async move {
+            while let Some(buf) = receiver.recv().await {
+                if sink.send(Message::Binary(buf)).await.is_err() {
+                    return;
+                }
+            }
+
+            sink.close().await.ok();
+        });
+
+        Tunnel {
+            peer_addr,
+            queue,
+            outbound,
+            reader,
+        }
+    }
+
+    pub fn peer_addr(&self) -> SocketAddr {
+        self.peer_addr
+    }
+
+    /**
+     * Wait until there are bytes to take or the stream was closed.
+     */
+    pub async fn readable(&self) -> io::Result<()> {
+        loop {
+            {
+                let inbound = self.queue.inbound.lock().unwrap();
+
+                if !inbound.buf.is_empty() || inbound.closed {
+                    return Ok(());
+                }
+            }
+
+            self.queue.notify.notified().await;
+        }
+    }
+
+    /**
+     * Take as many queued bytes as the spare capacity of the buffer holds.
+     *
+     * Return zero once the stream was closed, like a socket does.
+     */
+    pub fn try_read_buf(&self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        let mut inbound = self.queue.inbound.lock().unwrap();
+
+        if inbound.buf.is_empty() {
+            if inbound.closed {
+                return Ok(0);
+            }
+
+            return Err(io::Error::from(io::ErrorKind::WouldBlock));
+        }
+
+        let size = (buf.capacity() - buf.len()).min(inbound.buf.len());
+
+        buf.extend(inbound.buf.drain(..size));
+
+        self.queue.drained.notify_one();
+
+        Ok(size)
+    }
+
+    pub fn try_write(&self, buf: &[u8]) -> io::Result<usize> {
+        self.outbound.try_send(buf.to_vec()).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(_) => io::Error::from(io::ErrorKind::WouldBlock),
+            mpsc::error::TrySendError::Closed(_) => io::Error::from(io::ErrorKind::BrokenPipe),
+        })?;
+
+        Ok(buf.len())
+    }
+
+    /**
+     * Get how WebSockets are read, so no message outgrows the inbound limit.
+     */
+    pub fn websocket_config() -> WebSocketConfig {
+        WebSocketConfig {
+            max_message_size: Some(INBOUND_LIMIT),
+            max_frame_size: Some(INBOUND_LIMIT),
+            ..Default::default()
+        }
+    }
+}
+
+impl Drop for Tunnel {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
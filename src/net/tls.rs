@@ -0,0 +1,41 @@
+use std::{error::Error, fs, sync::Arc};
+
+use tokio_rustls::{
+    rustls::{Certificate, PrivateKey, ServerConfig},
+    TlsAcceptor,
+};
+
+/**
+ * Load a certificate chain and its private key from PEM files.
+ */
+pub fn acceptor(cert_path: &str, key_path: &str) -> Result<TlsAcceptor, Box<dyn Error>> {
+    let cert = fs::read(cert_path)?;
+
+    let key = fs::read(key_path)?;
+
+    acceptor_from_pem(&cert, &key)
+}
+
+pub fn acceptor_from_pem(cert: &[u8], key: &[u8]) -> Result<TlsAcceptor, Box<dyn Error>> {
+    let certs = rustls_pemfile::certs(&mut &cert[..])?
+        .into_iter()
+        .map(Certificate)
+        .collect();
+
+    let key = rustls_pemfile::read_all(&mut &key[..])?
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or("private key not found")?;
+
+    let config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)?;
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
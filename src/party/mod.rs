@@ -0,0 +1,3 @@
+mod registry;
+
+pub use registry::{Location, Parties, Party, MAX_MEMBERS};
@@ -0,0 +1,252 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
+
+use east_online_core::model::Vector3;
+
+pub const MAX_MEMBERS: usize = 4;
+
+/**
+ * Players grouped together, led by the first member.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Party {
+    pub members: Vec<String>,
+}
+
+impl Party {
+    pub fn leader(&self) -> &str {
+        &self.members[0]
+    }
+
+    /**
+     * Check if the party still groups anyone, which takes two players.
+     */
+    pub fn is_active(&self) -> bool {
+        self.members.len() > 1
+    }
+}
+
+/**
+ * Where a player was last seen.
+ */
+#[derive(Debug, Clone)]
+pub struct Location {
+    /**
+     * Id of the worker the player is on.
+     */
+    pub map: String,
+    /**
+     * Id of the map clients show, which differs for instances.
+     */
+    pub model_id: String,
+    pub position: Vector3,
+}
+
+#[derive(Default)]
+struct State {
+    next_id: usize,
+    parties: HashMap<usize, Party>,
+    memberships: HashMap<String, usize>,
+    invites: HashMap<String, HashSet<String>>,
+    locations: HashMap<String, Location>,
+}
+
+/**
+ * Parties of every map, kept apart from the workers so they outlive
+ * transfers between maps.
+ */
+#[derive(Default)]
+pub struct Parties {
+    state: Mutex<State>,
+}
+
+impl Parties {
+    pub fn new() -> Self {
+        Parties::default()
+    }
+
+    /**
+     * Record where a player is, whenever it enters a map or stops.
+     */
+    pub fn locate(&self, user_id: &str, location: Location) {
+        self.state
+            .lock()
+            .unwrap()
+            .locations
+            .insert(user_id.to_string(), location);
+    }
+
+    /**
+     * Forget where a player is, along with the invites to and from it.
+     */
+    pub fn unlocate(&self, user_id: &str) {
+        let mut state = self.state.lock().unwrap();
+
+        state.locations.remove(user_id);
+
+        state.invites.remove(user_id);
+
+        for inviters in state.invites.values_mut() {
+            inviters.remove(user_id);
+        }
+    }
+
+    pub fn location(&self, user_id: &str) -> Option<Location> {
+        self.state.lock().unwrap().locations.get(user_id).cloned()
+    }
+
+    pub fn find(&self, user_id: &str) -> Option<Party> {
+        let state = self.state.lock().unwrap();
+
+        let id = state.memberships.get(user_id)?;
+
+        state.parties.get(id).cloned()
+    }
+
+    /**
+     * Invite a player to the party of another, creating it on acceptance.
+     */
+    pub fn invite(&self, from: &str, to: &str) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+
+        if from == to {
+            return Err(String::from("can't invite yourself"));
+        }
+
+        if !state.locations.contains_key(to) {
+            return Err(format!("{} is not connected", to));
+        }
+
+        if state.memberships.contains_key(to) {
+            return Err(format!("{} is already in a party", to));
+        }
+
+        if let Some(party) = state
+            .memberships
+            .get(from)
+            .and_then(|id| state.parties.get(id))
+        {
+            if party.leader() != from {
+                return Err(String::from("only the leader invites"));
+            }
+
+            if party.members.len() >= MAX_MEMBERS {
+                return Err(String::from("party is full"));
+            }
+        }
+
+        state
+            .invites
+            .entry(to.to_string())
+            .or_default()
+            .insert(from.to_string());
+
+        Ok(())
+    }
+
+    /**
+     * Join the party of a player who invited us.
+     */
+    pub fn accept(&self, user_id: &str, inviter: &str) -> Result<Party, String> {
+        let mut state = self.state.lock().unwrap();
+
+        let invited = state
+            .invites
+            .get(user_id)
+            .is_some_and(|inviters| inviters.contains(inviter));
+
+        if !invited {
+            return Err(format!("no invite from {}", inviter));
+        }
+
+        if state.memberships.contains_key(user_id) {
+            return Err(String::from("already in a party"));
+        }
+
+        let id = match state.memberships.get(inviter) {
+            Some(id) => *id,
+            None => {
+                let id = state.next_id;
+
+                state.next_id = state.next_id.wrapping_add(1);
+
+                state.parties.insert(
+                    id,
+                    Party {
+                        members: vec![inviter.to_string()],
+                    },
+                );
+
+                state.memberships.insert(inviter.to_string(), id);
+
+                id
+            }
+        };
+
+        let party = state.parties.get_mut(&id).unwrap();
+
+        if party.leader() != inviter {
+            return Err(format!("{} doesn't lead the party anymore", inviter));
+        }
+
+        if party.members.len() >= MAX_MEMBERS {
+            return Err(String::from("party is full"));
+        }
+
+        party.members.push(user_id.to_string());
+
+        let party = party.clone();
+
+        state.memberships.insert(user_id.to_string(), id);
+
+        state.invites.remove(user_id);
+
+        Ok(party)
+    }
+
+    /**
+     * Leave a party, handing the lead to the next member.
+     *
+     * Return what is left of the party. A party left with a single member
+     * is disbanded.
+     */
+    pub fn leave(&self, user_id: &str) -> Option<Party> {
+        let mut state = self.state.lock().unwrap();
+
+        let id = state.memberships.remove(user_id)?;
+
+        let party = state.parties.get_mut(&id)?;
+
+        party.members.retain(|member| member != user_id);
+
+        let party = party.clone();
+
+        if !party.is_active() {
+            state.parties.remove(&id);
+
+            for member in &party.members {
+                state.memberships.remove(member);
+            }
+        }
+
+        Some(party)
+    }
+
+    /**
+     * Remove a member on behalf of the leader.
+     */
+    pub fn kick(&self, leader: &str, user_id: &str) -> Result<Party, String> {
+        let party = self
+            .find(user_id)
+            .ok_or(format!("{} is not in a party", user_id))?;
+
+        if party.leader() != leader || leader == user_id {
+            return Err(String::from("only the leader kicks others"));
+        }
+
+        self.leave(user_id)
+            .ok_or(format!("{} already left", user_id))
+    }
+}
@@ -0,0 +1,84 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use tokio::sync::mpsc;
+
+use crate::{map::Command, net::packet};
+
+#[derive(Default)]
+struct State {
+    maps: HashMap<String, mpsc::Sender<Command>>,
+    online: HashMap<String, String>,
+}
+
+/**
+ * The maps that are running and the one each online player is on.
+ *
+ * Lets any worker reach a player wherever it is.
+ */
+#[derive(Default)]
+pub struct Directory {
+    state: Mutex<State>,
+}
+
+impl Directory {
+    pub fn new() -> Self {
+        Directory::default()
+    }
+
+    pub fn add_map(&self, id: &str, control: mpsc::Sender<Command>) {
+        self.state
+            .lock()
+            .unwrap()
+            .maps
+            .insert(id.to_string(), control);
+    }
+
+    pub fn remove_map(&self, id: &str) {
+        self.state.lock().unwrap().maps.remove(id);
+    }
+
+    /**
+     * Record the map a player was sent to.
+     */
+    pub fn online(&self, user_id: &str, map_id: &str) {
+        self.state
+            .lock()
+            .unwrap()
+            .online
+            .insert(user_id.to_string(), map_id.to_string());
+    }
+
+    pub fn offline(&self, user_id: &str) {
+        self.state.lock().unwrap().online.remove(user_id);
+    }
+
+    pub fn map_of(&self, user_id: &str) -> Option<String> {
+        self.state.lock().unwrap().online.get(user_id).cloned()
+    }
+
+    /**
+     * Write a packet to a player through the map it is on.
+     *
+     * Nothing happens if the player is offline. The packet is sent from a
+     * task of its own, so a worker never waits on a busy map, or on itself.
+     */
+    pub fn deliver(&self, user_id: &str, packet: packet::Outgoing) {
+        let control = {
+            let state = self.state.lock().unwrap();
+
+            state
+                .online
+                .get(user_id)
+                .and_then(|map_id| state.maps.get(map_id))
+                .cloned()
+        };
+
+        if let Some(control) = control {
+            let command = Command::Deliver(user_id.to_owned(), packet);
+
+            tokio::spawn(async move {
+                control.send(command).await.ok();
+            });
+        }
+    }
+}
@@ -0,0 +1,29 @@
+mod directory;
+
+pub use directory::Directory;
+
+use std::error::Error;
+
+use crate::{db::Storage, net::packet};
+
+/**
+ * Tell the online friends of a user where it is now, or that it is gone.
+ */
+pub fn publish(
+    directory: &Directory,
+    db: &dyn Storage,
+    user_id: &str,
+) -> Result<(), Box<dyn Error>> {
+    let map_id = directory.map_of(user_id);
+
+    for friend in db.find_friends(user_id)? {
+        let packet = packet::Outgoing::Presence {
+            id: user_id.to_owned(),
+            map_id: map_id.to_owned(),
+        };
+
+        directory.deliver(&friend, packet);
+    }
+
+    Ok(())
+}
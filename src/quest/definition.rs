@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use east_online_core::model::Vector3;
+use serde::Deserialize;
+
+use super::Trigger;
+
+pub type Registry = HashMap<String, Definition>;
+
+#[derive(Debug, Deserialize)]
+pub struct Manifest {
+    pub items: Vec<ManifestItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ManifestItem {
+    pub id: String,
+}
+
+/**
+ * A quest and everything it takes to complete it.
+ *
+ * Quests with a giver start when a player talks to it, the others as soon
+ * as the player enters the world.
+ */
+#[derive(Debug, Clone, Deserialize)]
+pub struct Definition {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub giver: Option<String>,
+    pub objectives: Vec<Objective>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Objective {
+    Reach { map_id: String, position: Vector3 },
+    Talk { npc_id: String },
+    Collect { item_id: String, quantity: u16 },
+    Defeat { species: String, count: u16 },
+}
+
+impl Objective {
+    /**
+     * Get the count that fulfills the objective.
+     */
+    pub fn goal(&self) -> u16 {
+        match self {
+            Objective::Reach { .. } | Objective::Talk { .. } => 1,
+            Objective::Collect { quantity, .. } => *quantity,
+            Objective::Defeat { count, .. } => *count,
+        }
+    }
+
+    /**
+     * Get the count once something happened to the player.
+     *
+     * Items collected once stay counted, even if they are gone later.
+     */
+    pub fn advance(&self, count: u16, trigger: &Trigger) -> u16 {
+        let count = match (self, trigger) {
+            (
+                Objective::Reach { map_id, position },
+                Trigger::Reach {
+                    map_id: reached_map_id,
+                    position: reached,
+                },
+            ) if map_id == reached_map_id && position == reached => 1,
+            (Objective::Talk { npc_id }, Trigger::Talk { npc_id: talked }) if npc_id == talked => 1,
+            (
+                Objective::Collect { item_id, .. },
+                Trigger::Hold {
+                    item_id: held,
+                    quantity,
+                },
+            ) if item_id == held => count.max(*quantity),
+            (Objective::Defeat { species, .. }, Trigger::Defeat { species: defeated })
+                if species == defeated =>
+            {
+                count.saturating_add(1)
+            }
+            _ => count,
+        };
+
+        count.min(self.goal())
+    }
+}
@@ -0,0 +1,133 @@
+use std::{collections::BTreeMap, error::Error, fmt, str::FromStr};
+
+use crate::map::Component;
+
+use super::{Definition, Registry, Trigger};
+
+/**
+ * How far a player got in a quest, as a count per objective.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Progress {
+    pub counts: Vec<u16>,
+}
+
+impl Progress {
+    pub fn is_complete(&self, definition: &Definition) -> bool {
+        definition
+            .objectives
+            .iter()
+            .enumerate()
+            .all(|(index, objective)| self.count(index) >= objective.goal())
+    }
+
+    pub fn count(&self, index: usize) -> u16 {
+        self.counts.get(index).copied().unwrap_or_default()
+    }
+}
+
+/**
+ * Written as the counts separated by commas, as it is stored.
+ */
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts: Vec<String> = self.counts.iter().map(u16::to_string).collect();
+
+        write!(f, "{}", counts.join(","))
+    }
+}
+
+impl FromStr for Progress {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let counts = s
+            .split(',')
+            .filter(|count| !count.is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Progress { counts })
+    }
+}
+
+/**
+ * The quests a player started.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Journal {
+    quests: BTreeMap<String, Progress>,
+}
+
+impl Journal {
+    pub fn new() -> Self {
+        Journal::default()
+    }
+
+    pub fn from_quests(quests: Vec<(String, Progress)>) -> Self {
+        Journal {
+            quests: quests.into_iter().collect(),
+        }
+    }
+
+    pub fn quests(&self) -> &BTreeMap<String, Progress> {
+        &self.quests
+    }
+
+    pub fn get(&self, quest_id: &str) -> Option<&Progress> {
+        self.quests.get(quest_id)
+    }
+
+    /**
+     * Start a quest.
+     *
+     * Return whether it wasn't started before.
+     */
+    pub fn start(&mut self, quest_id: &str) -> bool {
+        if self.quests.contains_key(quest_id) {
+            return false;
+        }
+
+        self.quests.insert(quest_id.to_owned(), Progress::default());
+
+        true
+    }
+
+    /**
+     * Count something that happened toward every unfinished quest.
+     *
+     * Return the ids of the quests that made progress.
+     */
+    pub fn record(&mut self, registry: &Registry, trigger: &Trigger) -> Vec<String> {
+        let mut changed = Vec::new();
+
+        for (quest_id, progress) in &mut self.quests {
+            let definition = match registry.get(quest_id) {
+                Some(definition) if !progress.is_complete(definition) => definition,
+                _ => continue,
+            };
+
+            let counts: Vec<u16> = definition
+                .objectives
+                .iter()
+                .enumerate()
+                .map(|(index, objective)| objective.advance(progress.count(index), trigger))
+                .collect();
+
+            let advanced = counts
+                .iter()
+                .enumerate()
+                .any(|(index, count)| *count != progress.count(index));
+
+            if advanced {
+                progress.counts = counts;
+
+                changed.push(quest_id.to_owned());
+            }
+        }
+
+        changed
+    }
+}
+
+impl Component for Journal {}
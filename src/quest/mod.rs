@@ -0,0 +1,11 @@
+mod definition;
+
+pub use definition::{Definition, Manifest, Objective, Registry};
+
+mod trigger;
+
+pub use trigger::Trigger;
+
+mod journal;
+
+pub use journal::{Journal, Progress};
@@ -0,0 +1,12 @@
+use east_online_core::model::Vector3;
+
+/**
+ * Something a player did on a map that may count toward a quest.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Trigger {
+    Reach { map_id: String, position: Vector3 },
+    Talk { npc_id: String },
+    Hold { item_id: String, quantity: u16 },
+    Defeat { species: String },
+}
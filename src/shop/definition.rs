@@ -0,0 +1,38 @@
+use east_online_core::model::Vector3;
+use serde::Deserialize;
+
+/**
+ * The shops of a map, loaded next to its tiles.
+ */
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Manifest {
+    #[serde(default)]
+    pub shops: Vec<Definition>,
+}
+
+/**
+ * A shopkeeper standing on a tile, identified like any other actor.
+ */
+#[derive(Debug, Clone, Deserialize)]
+pub struct Definition {
+    pub id: String,
+    pub name: String,
+    pub position: Vector3,
+    pub goods: Vec<Goods>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Goods {
+    pub item_id: String,
+    pub price: u64,
+    /**
+     * What the shop pays for the item, if it buys it at all.
+     */
+    #[serde(default)]
+    pub sell_price: Option<u64>,
+    /**
+     * How many are left, or `None` for an endless supply.
+     */
+    #[serde(default)]
+    pub stock: Option<u16>,
+}
@@ -0,0 +1,7 @@
+mod definition;
+
+pub use definition::{Definition, Goods, Manifest};
+
+mod stall;
+
+pub use stall::Shop;
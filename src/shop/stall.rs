@@ -0,0 +1,91 @@
+use std::error::Error;
+
+use crate::map::Component;
+
+use super::Goods;
+
+/**
+ * The goods a shopkeeper deals in.
+ *
+ * Stock only changes once a deal is done, so quoting a price is free.
+ */
+#[derive(Debug, Clone)]
+pub struct Shop {
+    goods: Vec<Goods>,
+}
+
+impl Shop {
+    pub fn new(goods: Vec<Goods>) -> Self {
+        Shop { goods }
+    }
+
+    pub fn goods(&self) -> &[Goods] {
+        &self.goods
+    }
+
+    /**
+     * Get what a player pays for some quantity of an item.
+     *
+     * Throw an error if the shop doesn't have that many.
+     */
+    pub fn buy_price(&self, item_id: &str, quantity: u16) -> Result<u64, Box<dyn Error>> {
+        let goods = self.find(item_id).ok_or("not for sale")?;
+
+        if quantity == 0 || goods.stock.is_some_and(|stock| stock < quantity) {
+            return Err("out of stock".into());
+        }
+
+        Ok(goods
+            .price
+            .checked_mul(u64::from(quantity))
+            .ok_or("too expensive")?)
+    }
+
+    /**
+     * Get what a player earns for some quantity of an item.
+     */
+    pub fn sell_price(&self, item_id: &str, quantity: u16) -> Result<u64, Box<dyn Error>> {
+        let price = self
+            .find(item_id)
+            .and_then(|goods| goods.sell_price)
+            .ok_or("not bought here")?;
+
+        Ok(price
+            .checked_mul(u64::from(quantity))
+            .ok_or("too expensive")?)
+    }
+
+    /**
+     * Hand items over to a player.
+     */
+    pub fn remove_stock(&mut self, item_id: &str, quantity: u16) {
+        if let Some(stock) = self
+            .find_mut(item_id)
+            .and_then(|goods| goods.stock.as_mut())
+        {
+            *stock = stock.saturating_sub(quantity);
+        }
+    }
+
+    /**
+     * Take items back from a player.
+     */
+    pub fn add_stock(&mut self, item_id: &str, quantity: u16) {
+        if let Some(stock) = self
+            .find_mut(item_id)
+            .and_then(|goods| goods.stock.as_mut())
+        {
+            *stock = stock.saturating_add(quantity);
+        }
+    }
+
+    fn find(&self, item_id: &str) -> Option<&Goods> {
+        self.goods.iter().find(|goods| goods.item_id == item_id)
+    }
+
+    fn find_mut(&mut self, item_id: &str) -> Option<&mut Goods> {
+        self.goods.iter_mut().find(|goods| goods.item_id == item_id)
+    }
+}
+
+impl Component for Shop {}
@@ -0,0 +1,52 @@
+use std::error::Error;
+
+use crate::item::{Inventory, Registry, Wallet};
+
+use super::Offer;
+
+/**
+ * Work out what both sides own once their offers change hands.
+ *
+ * Nothing is changed in place, so a trade that doesn't add up, such as one
+ * that doesn't fit in an inventory or whose slots no longer hold what was
+ * offered, leaves both sides as they were.
+ */
+pub fn exchange(
+    sides: [(&Inventory, &Wallet, &Offer); 2],
+    registry: &Registry,
+) -> Result<[(Inventory, Wallet); 2], Box<dyn Error>> {
+    let mut inventories = [sides[0].0.clone(), sides[1].0.clone()];
+
+    let mut wallets = [*sides[0].1, *sides[1].1];
+
+    let mut taken = [Vec::new(), Vec::new()];
+
+    for (side, (_, _, offer)) in sides.iter().enumerate() {
+        for (slot, stack) in &offer.slots {
+            let slot = usize::from(*slot);
+
+            match inventories[side].get(slot) {
+                Some(held) if held.item_id == stack.item_id => {}
+                _ => return Err("offer changed".into()),
+            }
+
+            taken[side].push(inventories[side].take(slot, stack.quantity)?);
+        }
+
+        wallets[side].spend(offer.coins)?;
+    }
+
+    for (side, (_, _, offer)) in sides.iter().enumerate() {
+        let other = 1 - side;
+
+        for stack in &taken[side] {
+            inventories[other].add(stack.to_owned(), registry)?;
+        }
+
+        wallets[other].earn(offer.coins)?;
+    }
+
+    let [first, second] = inventories;
+
+    Ok([(first, wallets[0]), (second, wallets[1])])
+}
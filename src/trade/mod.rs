@@ -0,0 +1,7 @@
+mod session;
+
+pub use session::{Offer, Session};
+
+mod exchange;
+
+pub use exchange::exchange;
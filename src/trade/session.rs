@@ -0,0 +1,108 @@
+use std::error::Error;
+
+use crate::item;
+
+/**
+ * What one side puts on the table, as stacks out of inventory slots and
+ * coins.
+ *
+ * Each slot keeps the item it held when offered, so it can't be swapped
+ * for another item before the exchange.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Offer {
+    pub slots: Vec<(u8, item::Stack)>,
+    pub coins: u64,
+}
+
+/**
+ * A trade between two players.
+ *
+ * Both sides lock their offers before either can confirm, and changing an
+ * offer is only allowed while it is unlocked, so nobody confirms a trade
+ * that changed under them.
+ */
+#[derive(Debug, Clone)]
+pub struct Session {
+    users: [String; 2],
+    offers: [Offer; 2],
+    locked: [bool; 2],
+    confirmed: [bool; 2],
+}
+
+impl Session {
+    pub fn new(from: String, to: String) -> Self {
+        Session {
+            users: [from, to],
+            offers: Default::default(),
+            locked: [false; 2],
+            confirmed: [false; 2],
+        }
+    }
+
+    pub fn users(&self) -> &[String; 2] {
+        &self.users
+    }
+
+    pub fn offers(&self) -> &[Offer; 2] {
+        &self.offers
+    }
+
+    pub fn is_locked(&self, side: usize) -> bool {
+        self.locked[side]
+    }
+
+    pub fn is_confirmed(&self, side: usize) -> bool {
+        self.confirmed[side]
+    }
+
+    /**
+     * Get the user on the other side.
+     */
+    pub fn partner(&self, user_id: &str) -> Option<&str> {
+        let side = self.side(user_id)?;
+
+        Some(&self.users[1 - side])
+    }
+
+    pub fn offer(&mut self, user_id: &str, offer: Offer) -> Result<(), Box<dyn Error>> {
+        let side = self.side(user_id).ok_or("not in the trade")?;
+
+        if self.locked[side] {
+            return Err("offer is locked".into());
+        }
+
+        self.offers[side] = offer;
+
+        Ok(())
+    }
+
+    pub fn lock(&mut self, user_id: &str) -> Result<(), Box<dyn Error>> {
+        let side = self.side(user_id).ok_or("not in the trade")?;
+
+        self.locked[side] = true;
+
+        Ok(())
+    }
+
+    /**
+     * Agree to the locked offers.
+     *
+     * Return whether both sides agreed, which completes the trade.
+     */
+    pub fn confirm(&mut self, user_id: &str) -> Result<bool, Box<dyn Error>> {
+        let side = self.side(user_id).ok_or("not in the trade")?;
+
+        if !self.locked.iter().all(|locked| *locked) {
+            return Err("both offers must be locked first".into());
+        }
+
+        self.confirmed[side] = true;
+
+        Ok(self.confirmed.iter().all(|confirmed| *confirmed))
+    }
+
+    fn side(&self, user_id: &str) -> Option<usize> {
+        self.users.iter().position(|user| user == user_id)
+    }
+}
@@ -0,0 +1,212 @@
+mod common;
+
+use std::{collections::HashMap, error::Error, process, sync::Arc};
+
+use east_online_core::model::{Direction, Vector3};
+use east_online_server::{
+    map::{self, Tile},
+    net::packet::Outgoing,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::UnixStream,
+};
+
+use common::{flat_tiles, user_id, Harness, Options};
+
+/**
+ * A loader that shrinks every map to a single ring of floor.
+ */
+struct Shrink;
+
+#[async_trait::async_trait]
+impl map::Loader for Shrink {
+    async fn load(&self, _: &str) -> Result<HashMap<Vector3, Tile>, Box<dyn Error + Send + Sync>> {
+        Ok(flat_tiles(1))
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn console_lists_maps_and_users() {
+    let harness = Harness::start(vec![
+        ("map_0000", flat_tiles(1)),
+        ("map_0001", flat_tiles(1)),
+    ])
+    .await;
+
+    let (_client, _) = harness.enter(&user_id(0)).await;
+
+    assert_eq!(
+        harness.console.execute("maps").await.unwrap(),
+        "map_0000 map_0000 1\nmap_0001 map_0001 0"
+    );
+
+    assert_eq!(
+        harness.console.execute("users").await.unwrap(),
+        format!("{} map_0000 0 0 0", user_id(0))
+    );
+
+    assert!(harness.console.execute("users map_9999").await.is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn console_kicks_users() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(1))]).await;
+
+    let (mut client, _) = harness.enter(&user_id(0)).await;
+
+    harness
+        .console
+        .execute(&format!("kick {}", user_id(0)))
+        .await
+        .unwrap();
+
+    while client.try_recv().await.is_some() {}
+
+    assert!(harness
+        .console
+        .execute(&format!("kick {}", user_id(0)))
+        .await
+        .is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn console_teleports_users() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(2))]).await;
+
+    let (mut client, _) = harness.enter(&user_id(0)).await;
+
+    harness
+        .console
+        .execute(&format!("teleport {} 2 0 -1", user_id(0)))
+        .await
+        .unwrap();
+
+    let stop = client
+        .recv_matching(|packet| matches!(packet, Outgoing::Stop { .. }))
+        .await;
+
+    assert_eq!(
+        stop,
+        Outgoing::Stop {
+            id: user_id(0),
+            position: Vector3 { x: 2, y: 0, z: -1 },
+            facing: Direction::Down,
+        }
+    );
+
+    assert!(harness
+        .console
+        .execute(&format!("teleport {} 9 0 9", user_id(0)))
+        .await
+        .is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn console_broadcasts_notices() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(1))]).await;
+
+    let (mut client, _) = harness.enter(&user_id(0)).await;
+
+    harness
+        .console
+        .execute("notice server restarts soon")
+        .await
+        .unwrap();
+
+    let notice = client
+        .recv_matching(|packet| matches!(packet, Outgoing::Notice { .. }))
+        .await;
+
+    assert_eq!(
+        notice,
+        Outgoing::Notice {
+            message: String::from("server restarts soon"),
+        }
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn console_reloads_maps_and_moves_stranded_users() {
+    let options = Options {
+        loader: Some(Arc::new(Shrink)),
+        ..Options::default()
+    };
+
+    let harness = Harness::start_with(vec![("map_0000", flat_tiles(2))], options).await;
+
+    let (mut client, _) = harness.enter(&user_id(0)).await;
+
+    harness
+        .console
+        .execute(&format!("teleport {} 2 0 2", user_id(0)))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        harness.console.execute("reload map_0000").await.unwrap(),
+        "reloaded map_0000, 1 actors displaced"
+    );
+
+    let update = client
+        .recv_matching(|packet| matches!(packet, Outgoing::MapUpdate { .. }))
+        .await;
+
+    match update {
+        Outgoing::MapUpdate { tiles } => {
+            assert_eq!(tiles.len(), 16);
+
+            assert!(tiles.iter().all(|(_, tile)| tile.is_none()));
+        }
+        _ => unreachable!(),
+    }
+
+    let stop = client
+        .recv_matching(|packet| matches!(packet, Outgoing::Stop { .. }))
+        .await;
+
+    assert_eq!(
+        stop,
+        Outgoing::Stop {
+            id: user_id(0),
+            position: Vector3 { x: 1, y: 0, z: 1 },
+            facing: Direction::Down,
+        }
+    );
+}
+
+#[tokio::test]
+async fn console_answers_on_its_socket() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(1))]).await;
+
+    let path = std::env::temp_dir().join(format!("east_online_admin_{}.sock", process::id()));
+
+    tokio::spawn(harness.console.clone().listen(path.clone()));
+
+    let stream = loop {
+        match UnixStream::connect(&path).await {
+            Ok(stream) => break stream,
+            Err(_) => tokio::task::yield_now().await,
+        }
+    };
+
+    let (reader, mut writer) = stream.into_split();
+
+    let mut lines = BufReader::new(reader).lines();
+
+    writer.write_all(b"maps\nwhat\n").await.unwrap();
+
+    assert_eq!(
+        lines.next_line().await.unwrap().unwrap(),
+        "map_0000 map_0000 0"
+    );
+
+    assert_eq!(lines.next_line().await.unwrap().unwrap(), "ok");
+
+    assert_eq!(
+        lines.next_line().await.unwrap().unwrap(),
+        "error: unknown command, what"
+    );
+
+    std::fs::remove_file(path).ok();
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use east_online_core::model::{Direction, Vector3};
+use east_online_server::{
+    db::{ActorState, StateStore},
+    map,
+    net::packet::Outgoing,
+};
+use tokio::time;
+
+use common::{flat_tiles, user_id, Harness};
+
+const STEP_DURATION: time::Duration = time::Duration::from_millis(300);
+
+/**
+ * Wait for a state of a player matching `matches` to be written off the
+ * event loop.
+ */
+async fn saved_state<F>(harness: &Harness, user_id: &str, matches: F) -> ActorState
+where
+    F: Fn(&ActorState) -> bool,
+{
+    let wait = async {
+        loop {
+            if let Some(state) = harness.storage.find_state(user_id).unwrap() {
+                if matches(&state) {
+                    return state;
+                }
+            }
+
+            time::sleep(time::Duration::from_millis(10)).await;
+        }
+    };
+
+    time::timeout(time::Duration::from_secs(1), wait)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn changed_players_are_saved_on_the_next_autosave() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(1))]).await;
+
+    let (mut client, _) = harness.enter(&user_id(0)).await;
+
+    time::sleep(STEP_DURATION).await;
+
+    client.walk(Direction::Up).await;
+
+    client
+        .recv_matching(|packet| matches!(packet, Outgoing::Stop { .. }))
+        .await;
+
+    time::pause();
+
+    time::advance(map::AUTOSAVE_INTERVAL).await;
+
+    time::resume();
+
+    let state = saved_state(&harness, &user_id(0), |_| true).await;
+
+    assert_eq!(
+        state,
+        ActorState {
+            user_id: user_id(0),
+            map_id: String::from("map_0000"),
+            position: Vector3 { x: 0, y: 0, z: 1 },
+            health: 100,
+        }
+    );
+
+    let wait = async {
+        loop {
+            let output = harness.console.execute("autosave").await.unwrap();
+
+            if output.starts_with("map_0000 dirty 0 pending 0 saves 1 failures 0 last 1 in ") {
+                return;
+            }
+
+            time::sleep(time::Duration::from_millis(10)).await;
+        }
+    };
+
+    time::timeout(time::Duration::from_secs(1), wait)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn leaving_players_are_saved_at_once() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(1))]).await;
+
+    let (client, _) = harness.enter(&user_id(0)).await;
+
+    drop(client);
+
+    let state = saved_state(&harness, &user_id(0), |_| true).await;
+
+    assert_eq!(state.position, Vector3 { x: 0, y: 0, z: 0 });
+}
+
+#[tokio::test]
+async fn players_enter_as_they_were_saved() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(2))]).await;
+
+    let saved = ActorState {
+        user_id: user_id(0),
+        map_id: String::from("map_0000"),
+        position: Vector3 { x: 0, y: 0, z: 1 },
+        health: 40,
+    };
+
+    harness.storage.save_states(&[saved]).unwrap();
+
+    let (mut client, hello) = harness.enter(&user_id(0)).await;
+
+    let position = match hello {
+        Outgoing::Hello { id, actors, .. } => actors
+            .into_iter()
+            .find(|actor| actor.id == id)
+            .map(|actor| actor.position),
+        _ => None,
+    };
+
+    assert_eq!(position, Some(Vector3 { x: 0, y: 0, z: 1 }));
+
+    time::sleep(STEP_DURATION).await;
+
+    client.walk(Direction::Up).await;
+
+    client
+        .recv_matching(|packet| matches!(packet, Outgoing::Stop { .. }))
+        .await;
+
+    drop(client);
+
+    let state = saved_state(&harness, &user_id(0), |state| {
+        state.position == Vector3 { x: 0, y: 0, z: 2 }
+    })
+    .await;
+
+    assert_eq!(state.health, 40);
+}
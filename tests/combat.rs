@@ -0,0 +1,82 @@
+mod common;
+
+use east_online_core::model::{Direction, Vector3};
+use east_online_server::{
+    map::{NpcDefinition, NpcManifest},
+    net::packet::{Incoming, Outgoing},
+};
+
+use common::{flat_tiles, user_id, Harness, Options};
+
+#[tokio::test(start_paused = true)]
+async fn npcs_fight_through_the_same_path_as_players() {
+    let wolf = NpcDefinition {
+        id: user_id(99),
+        name: String::from("Wolf"),
+        position: Vector3 { x: 1, y: 0, z: -1 },
+        facing: Direction::Up,
+        health: 10,
+        defense: 0,
+        power: 3,
+        species: None,
+    };
+
+    let manifest = NpcManifest {
+        spawn: Some(Vector3 { x: 1, y: 0, z: 0 }),
+        npcs: vec![wolf],
+    };
+
+    let options = Options {
+        npcs: vec![("map_0000", manifest)],
+        ..Default::default()
+    };
+
+    let harness = Harness::start_with(vec![("map_0000", flat_tiles(1))], options).await;
+
+    let (mut client, hello) = harness.enter(&user_id(0)).await;
+
+    let position = match hello {
+        Outgoing::Hello { id, actors, .. } => actors
+            .into_iter()
+            .find(|actor| actor.id == id)
+            .map(|actor| actor.position),
+        _ => None,
+    };
+
+    assert_eq!(position, Some(Vector3 { x: 1, y: 0, z: 0 }));
+
+    let bitten = client
+        .recv_matching(|packet| matches!(packet, Outgoing::Damage { .. }))
+        .await;
+
+    assert_eq!(
+        bitten,
+        Outgoing::Damage {
+            id: user_id(99),
+            target: user_id(0),
+            amount: 3,
+            health: 97,
+        }
+    );
+
+    client.send(Incoming::Attack).await;
+
+    let died = client
+        .recv_matching(|packet| matches!(packet, Outgoing::Die { .. }))
+        .await;
+
+    assert_eq!(died, Outgoing::Die { id: user_id(99) });
+
+    let respawned = client
+        .recv_matching(|packet| matches!(packet, Outgoing::Respawn { .. }))
+        .await;
+
+    assert_eq!(
+        respawned,
+        Outgoing::Respawn {
+            id: user_id(99),
+            position: Vector3 { x: 1, y: 0, z: -1 },
+            health: 10,
+        }
+    );
+}
@@ -0,0 +1,356 @@
+#![allow(dead_code)]
+
+use std::{collections::HashMap, error::Error, io, net::SocketAddr, sync::Arc, time::Duration};
+
+use east_online_core::model::{Direction, Rotation, Vector3};
+use east_online_server::{
+    admin, auth, db, gate, item,
+    limit::Limits,
+    map::{self, Kind, Tile},
+    net::{
+        client::{take_packet_for, write_packet},
+        packet::{Incoming, Outgoing},
+        protocol, Listener, Transport,
+    },
+    party, presence, quest, shop,
+};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    task,
+};
+use tokio_rustls::TlsAcceptor;
+
+const SPIN_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(100);
+
+/**
+ * A gate and its map workers running in the current runtime.
+ *
+ * Everything is stored in memory and users authenticate with stub tokens.
+ */
+pub struct Harness {
+    pub address: SocketAddr,
+    pub websocket_address: SocketAddr,
+    pub storage: Arc<db::Memory>,
+    pub console: admin::Console,
+}
+
+#[derive(Default)]
+pub struct Options {
+    pub acceptor: Option<TlsAcceptor>,
+    pub limits: Limits,
+    pub capacity: gate::Capacity,
+    pub loader: Option<Arc<dyn map::Loader>>,
+    pub templates: Vec<(&'static str, HashMap<Vector3, Tile>)>,
+    pub idle_timeout: Option<Duration>,
+    pub items: item::Registry,
+    pub shops: Vec<(&'static str, shop::Definition)>,
+    pub quests: quest::Registry,
+    pub objects: Vec<(&'static str, map::ObjectDefinition)>,
+    pub npcs: Vec<(&'static str, map::NpcManifest)>,
+}
+
+/**
+ * A loader for harnesses that never reload maps.
+ */
+struct NoLoader;
+
+#[async_trait::async_trait]
+impl map::Loader for NoLoader {
+    async fn load(&self, id: &str) -> Result<HashMap<Vector3, Tile>, Box<dyn Error + Send + Sync>> {
+        Err(format!("{} can't be loaded", id).into())
+    }
+}
+
+impl Harness {
+    pub async fn start(maps: Vec<(&str, HashMap<Vector3, Tile>)>) -> Self {
+        Harness::start_with(maps, Options::default()).await
+    }
+
+    pub async fn start_with(maps: Vec<(&str, HashMap<Vector3, Tile>)>, options: Options) -> Self {
+        let Options {
+            acceptor,
+            limits,
+            capacity,
+            loader,
+            templates,
+            idle_timeout,
+            items,
+            shops,
+            quests,
+            objects,
+            npcs,
+        } = options;
+
+        let storage = Arc::new(db::Memory::new());
+
+        let mut listener = Listener::new();
+
+        let address = listener
+            .listen(bind().await, acceptor.clone(), Transport::Raw)
+            .unwrap();
+
+        let websocket_address = listener
+            .listen(bind().await, acceptor, Transport::WebSocket)
+            .unwrap();
+
+        let mut gate_worker = gate::Worker::new(storage.clone(), Box::new(auth::Stub), listener);
+
+        gate_worker.set_limits(limits.clone());
+
+        gate_worker.set_capacity(capacity);
+
+        let items = Arc::new(items);
+
+        let parties = Arc::new(party::Parties::new());
+
+        let directory = Arc::new(presence::Directory::new());
+
+        let quests = Arc::new(quests);
+
+        let console = admin::Console::new(loader.unwrap_or_else(|| Arc::new(NoLoader)));
+
+        for (map_id, tiles) in maps {
+            let (enter_tx, enter_rx) = mpsc::channel(16);
+
+            let (exit_tx, exit_rx) = mpsc::unbounded_channel();
+
+            gate_worker.add_channel(map_id, (enter_tx, exit_rx));
+
+            let mut map_worker = map::Worker::new(
+                map_id.to_string(),
+                map_id.to_string(),
+                tiles,
+                items.clone(),
+                storage.clone(),
+                (exit_tx, enter_rx),
+            );
+
+            map_worker.set_limits(limits.clone());
+
+            for (_, shop) in shops
+                .iter()
+                .filter(|(shop_map_id, _)| *shop_map_id == map_id)
+            {
+                map_worker.add_shop(shop.to_owned()).unwrap();
+            }
+
+            let map_objects = objects
+                .iter()
+                .filter(|(object_map_id, _)| *object_map_id == map_id)
+                .map(|(_, object)| object.to_owned())
+                .collect();
+
+            map_worker.add_objects(map_objects).unwrap();
+
+            for (_, manifest) in npcs.iter().filter(|(npc_map_id, _)| *npc_map_id == map_id) {
+                map_worker.add_npcs(manifest.to_owned()).unwrap();
+            }
+
+            map_worker.set_parties(parties.clone());
+
+            map_worker.set_directory(directory.clone());
+
+            map_worker.set_quests(quests.clone());
+
+            console.add_map(map_id, map_worker.control());
+
+            tokio::spawn(async move {
+                map_worker.run().await.ok();
+            });
+        }
+
+        let mut instances = gate::Instances::new(items, storage.clone());
+
+        instances.set_limits(limits);
+
+        instances.set_parties(parties);
+
+        instances.set_directory(directory);
+
+        instances.set_quests(quests);
+
+        instances.set_console(console.clone());
+
+        if let Some(idle_timeout) = idle_timeout {
+            instances.set_idle_timeout(idle_timeout);
+        }
+
+        for (template_id, tiles) in templates {
+            let template =
+                map::Template::new(template_id.to_string(), template_id.to_string(), tiles);
+
+            instances.add_template(template);
+        }
+
+        gate_worker.set_instances(instances);
+
+        tokio::spawn(async move {
+            gate_worker.run().await.ok();
+        });
+
+        Harness {
+            address,
+            websocket_address,
+            storage,
+            console,
+        }
+    }
+
+    /**
+     * Connect a registered user and wait until a map greets it.
+     */
+    pub async fn enter(&self, user_id: &str) -> (Client, Outgoing) {
+        self.storage.add_user(user_id);
+
+        let mut client = Client::connect(self.address).await;
+
+        client.hello(user_id).await;
+
+        let hello = client
+            .recv_matching(|packet| matches!(packet, Outgoing::Hello { .. }))
+            .await;
+
+        (client, hello)
+    }
+}
+
+async fn bind() -> TcpListener {
+    TcpListener::bind("127.0.0.1:0").await.unwrap()
+}
+
+/**
+ * A flat square of floor tiles around the origin.
+ */
+pub fn flat_tiles(radius: i32) -> HashMap<Vector3, Tile> {
+    let mut tiles = HashMap::new();
+
+    for x in -radius..=radius {
+        for z in -radius..=radius {
+            tiles.insert(Vector3 { x, y: 0, z }, Tile::new(Kind::Floor, Rotation::Up));
+        }
+    }
+
+    tiles
+}
+
+pub fn user_id(index: usize) -> String {
+    format!("00000000-0000-4000-8000-{:012}", index)
+}
+
+pub struct Client {
+    stream: TcpStream,
+    buf: Vec<u8>,
+    version: u16,
+}
+
+impl Client {
+    pub async fn connect(address: SocketAddr) -> Self {
+        Client {
+            stream: TcpStream::connect(address).await.unwrap(),
+            buf: Vec::new(),
+            version: protocol::PROTOCOL_VERSION,
+        }
+    }
+
+    pub async fn send(&mut self, packet: Incoming) {
+        write_packet(&mut self.stream, &packet).await.unwrap();
+    }
+
+    pub async fn hello(&mut self, user_id: &str) {
+        let features = protocol::FEATURES.iter().map(|f| f.to_string()).collect();
+
+        self.hello_with(user_id, protocol::PROTOCOL_VERSION, features)
+            .await;
+    }
+
+    pub async fn hello_with(&mut self, user_id: &str, version: u16, features: Vec<String>) {
+        let token = auth::Stub::token(user_id);
+
+        self.version = version;
+
+        self.send(Incoming::Hello {
+            version,
+            features,
+            token,
+        })
+        .await;
+    }
+
+    pub async fn walk(&mut self, direction: Direction) {
+        self.send(Incoming::Move { direction }).await;
+    }
+
+    /**
+     * Read the next packet, or `None` once the server closed the stream.
+     *
+     * The paused clock may advance while this waits.
+     */
+    pub async fn try_recv(&mut self) -> Option<Outgoing> {
+        loop {
+            if let Some(packet) = take_packet_for(&mut self.buf, self.version).unwrap() {
+                return Some(packet);
+            }
+
+            self.stream.readable().await.ok()?;
+
+            match self.stream.try_read_buf(&mut self.buf) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(_) => return None,
+            }
+        }
+    }
+
+    pub async fn recv(&mut self) -> Outgoing {
+        self.try_recv().await.expect("stream closed")
+    }
+
+    /**
+     * Read until a packet matches, skipping the others.
+     */
+    pub async fn recv_matching<F>(&mut self, matches: F) -> Outgoing
+    where
+        F: Fn(&Outgoing) -> bool,
+    {
+        loop {
+            let packet = self.recv().await;
+
+            if matches(&packet) {
+                return packet;
+            }
+        }
+    }
+
+    /**
+     * Read a matching packet that is due without letting time pass.
+     *
+     * The runtime is kept busy while waiting, so the paused clock only
+     * moves by `time::advance`. `None` means nothing arrived within a
+     * short while of real time.
+     */
+    pub async fn recv_now_matching<F>(&mut self, matches: F) -> Option<Outgoing>
+    where
+        F: Fn(&Outgoing) -> bool,
+    {
+        let deadline = std::time::Instant::now() + SPIN_TIMEOUT;
+
+        while std::time::Instant::now() < deadline {
+            while let Some(packet) = take_packet_for(&mut self.buf, self.version).unwrap() {
+                if matches(&packet) {
+                    return Some(packet);
+                }
+            }
+
+            match self.stream.try_read_buf(&mut self.buf) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => task::yield_now().await,
+                Err(_) => return None,
+            }
+        }
+
+        None
+    }
+}
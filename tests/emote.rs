@@ -0,0 +1,68 @@
+mod common;
+
+use east_online_server::net::packet::{Incoming, Outgoing};
+use tokio::time;
+
+use common::{flat_tiles, user_id, Harness};
+
+fn is_emote(packet: &Outgoing) -> bool {
+    matches!(packet, Outgoing::Emote { .. })
+}
+
+#[tokio::test(start_paused = true)]
+async fn emotes_cool_down_between_plays() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(1))]).await;
+
+    let (mut first, _) = harness.enter(&user_id(0)).await;
+
+    let (mut second, _) = harness.enter(&user_id(1)).await;
+
+    first.send(Incoming::Emote { emote: 1 }).await;
+
+    first.send(Incoming::Emote { emote: 2 }).await;
+
+    let emote = second.recv_matching(is_emote).await;
+
+    assert_eq!(
+        emote,
+        Outgoing::Emote {
+            id: user_id(0),
+            emote: 1,
+        }
+    );
+
+    assert!(second.recv_now_matching(is_emote).await.is_none());
+
+    time::advance(time::Duration::from_secs(2)).await;
+
+    first.send(Incoming::Emote { emote: 3 }).await;
+
+    let emote = second.recv_matching(is_emote).await;
+
+    assert_eq!(
+        emote,
+        Outgoing::Emote {
+            id: user_id(0),
+            emote: 3,
+        }
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn unknown_emotes_are_refused() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(1))]).await;
+
+    let (mut first, _) = harness.enter(&user_id(0)).await;
+
+    let (mut second, _) = harness.enter(&user_id(1)).await;
+
+    first.send(Incoming::Emote { emote: 255 }).await;
+
+    let error = first
+        .recv_matching(|packet| matches!(packet, Outgoing::Error { .. }))
+        .await;
+
+    assert!(matches!(error, Outgoing::Error { code: 6, .. }));
+
+    assert!(second.recv_now_matching(is_emote).await.is_none());
+}
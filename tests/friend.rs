@@ -0,0 +1,160 @@
+mod common;
+
+use east_online_server::net::packet::{Incoming, Outgoing};
+
+use common::{flat_tiles, user_id, Client, Harness};
+
+async fn recv_presence(client: &mut Client) -> Outgoing {
+    client
+        .recv_matching(|packet| matches!(packet, Outgoing::Presence { .. }))
+        .await
+}
+
+fn presence(index: usize, map_id: Option<&str>) -> Outgoing {
+    Outgoing::Presence {
+        id: user_id(index),
+        map_id: map_id.map(String::from),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn friends_follow_each_other_across_maps() {
+    let harness = Harness::start(vec![
+        ("map_0000", flat_tiles(1)),
+        ("map_0001", flat_tiles(1)),
+    ])
+    .await;
+
+    harness.storage.set_map_id(&user_id(1), "map_0001");
+
+    let (mut first, _) = harness.enter(&user_id(0)).await;
+
+    let (mut second, _) = harness.enter(&user_id(1)).await;
+
+    first
+        .send(Incoming::FriendRequest {
+            user_id: user_id(1),
+        })
+        .await;
+
+    let request = second
+        .recv_matching(|packet| matches!(packet, Outgoing::FriendRequest { .. }))
+        .await;
+
+    assert_eq!(request, Outgoing::FriendRequest { id: user_id(0) });
+
+    second
+        .send(Incoming::FriendAccept {
+            user_id: user_id(0),
+        })
+        .await;
+
+    assert_eq!(
+        recv_presence(&mut first).await,
+        presence(1, Some("map_0001"))
+    );
+
+    assert_eq!(
+        recv_presence(&mut second).await,
+        presence(0, Some("map_0000"))
+    );
+
+    drop(second);
+
+    assert_eq!(recv_presence(&mut first).await, presence(1, None));
+
+    let mut second = Client::connect(harness.address).await;
+
+    second.hello(&user_id(1)).await;
+
+    assert_eq!(
+        recv_presence(&mut first).await,
+        presence(1, Some("map_0001"))
+    );
+
+    assert_eq!(
+        recv_presence(&mut second).await,
+        presence(0, Some("map_0000"))
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn requests_wait_for_offline_players() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(1))]).await;
+
+    harness.storage.add_user(&user_id(1));
+
+    let (mut first, _) = harness.enter(&user_id(0)).await;
+
+    first
+        .send(Incoming::FriendRequest {
+            user_id: user_id(1),
+        })
+        .await;
+
+    first
+        .send(Incoming::FriendRequest {
+            user_id: user_id(9),
+        })
+        .await;
+
+    let error = first
+        .recv_matching(|packet| matches!(packet, Outgoing::Error { .. }))
+        .await;
+
+    assert!(matches!(error, Outgoing::Error { code: 3, .. }));
+
+    let mut second = Client::connect(harness.address).await;
+
+    second.hello(&user_id(1)).await;
+
+    let request = second
+        .recv_matching(|packet| matches!(packet, Outgoing::FriendRequest { .. }))
+        .await;
+
+    assert_eq!(request, Outgoing::FriendRequest { id: user_id(0) });
+}
+
+#[tokio::test(start_paused = true)]
+async fn failed_entries_stay_offline() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(1))]).await;
+
+    let (mut first, _) = harness.enter(&user_id(0)).await;
+
+    let (mut second, _) = harness.enter(&user_id(1)).await;
+
+    first
+        .send(Incoming::FriendRequest {
+            user_id: user_id(1),
+        })
+        .await;
+
+    second
+        .recv_matching(|packet| matches!(packet, Outgoing::FriendRequest { .. }))
+        .await;
+
+    second
+        .send(Incoming::FriendAccept {
+            user_id: user_id(0),
+        })
+        .await;
+
+    assert_eq!(
+        recv_presence(&mut first).await,
+        presence(1, Some("map_0000"))
+    );
+
+    drop(second);
+
+    assert_eq!(recv_presence(&mut first).await, presence(1, None));
+
+    harness.storage.break_wallet(&user_id(1));
+
+    let mut second = Client::connect(harness.address).await;
+
+    second.hello(&user_id(1)).await;
+
+    while second.try_recv().await.is_some() {}
+
+    assert_eq!(recv_presence(&mut first).await, presence(1, None));
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use east_online_server::net::{
+    packet::{Incoming, Outgoing},
+    protocol,
+};
+
+use common::{flat_tiles, user_id, Client, Harness};
+
+#[tokio::test(start_paused = true)]
+async fn gate_drops_unknown_users() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(1))]).await;
+
+    let mut client = Client::connect(harness.address).await;
+
+    client.hello(&user_id(0)).await;
+
+    assert!(client.try_recv().await.is_none());
+}
+
+#[tokio::test(start_paused = true)]
+async fn gate_sends_users_to_their_map() {
+    let harness = Harness::start(vec![
+        ("map_0000", flat_tiles(1)),
+        ("map_0001", flat_tiles(1)),
+    ])
+    .await;
+
+    harness.storage.set_map_id(&user_id(0), "map_0001");
+
+    let (_, hello) = harness.enter(&user_id(0)).await;
+
+    assert!(matches!(hello, Outgoing::Hello { map_id, .. } if map_id == "map_0001"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn gate_rejects_newer_protocol_versions() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(1))]).await;
+
+    harness.storage.add_user(&user_id(0));
+
+    let mut client = Client::connect(harness.address).await;
+
+    client
+        .hello_with(&user_id(0), protocol::PROTOCOL_VERSION + 1, Vec::new())
+        .await;
+
+    let error = client.recv().await;
+
+    assert!(matches!(
+        error,
+        Outgoing::Error { code, .. } if code == protocol::ERROR_INCOMPATIBLE_VERSION
+    ));
+
+    assert!(client.try_recv().await.is_none());
+}
+
+#[tokio::test(start_paused = true)]
+async fn gate_negotiates_known_features() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(1))]).await;
+
+    harness.storage.add_user(&user_id(0));
+
+    let mut client = Client::connect(harness.address).await;
+
+    let features = vec![String::from("combat"), String::from("unknown")];
+
+    client
+        .hello_with(&user_id(0), protocol::PROTOCOL_VERSION, features)
+        .await;
+
+    let capabilities = client.recv().await;
+
+    assert_eq!(
+        capabilities,
+        Outgoing::Capabilities {
+            version: protocol::PROTOCOL_VERSION,
+            features: vec![String::from("combat")],
+        }
+    );
+
+    let hello = client.recv().await;
+
+    assert!(matches!(hello, Outgoing::Hello { .. }));
+}
+
+#[tokio::test(start_paused = true)]
+async fn legacy_clients_only_receive_known_packets() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(1))]).await;
+
+    harness.storage.add_user(&user_id(0));
+
+    let mut client = Client::connect(harness.address).await;
+
+    client.hello_with(&user_id(0), 1, Vec::new()).await;
+
+    let hello = client.recv().await;
+
+    assert!(matches!(hello, Outgoing::Hello { .. }));
+
+    client.send(Incoming::Inventory).await;
+
+    assert!(client.recv_now_matching(|_| true).await.is_none());
+}
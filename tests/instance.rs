@@ -0,0 +1,83 @@
+mod common;
+
+use std::time::Duration;
+
+use east_online_server::net::packet::Outgoing;
+
+use common::{flat_tiles, user_id, Harness, Options};
+
+#[tokio::test(start_paused = true)]
+async fn parties_enter_instances_that_close_when_idle() {
+    let options = Options {
+        templates: vec![("map_0001", flat_tiles(1))],
+        idle_timeout: Some(Duration::from_secs(30)),
+        ..Options::default()
+    };
+
+    let harness = Harness::start_with(vec![("map_0000", flat_tiles(1))], options).await;
+
+    let (mut first, _) = harness.enter(&user_id(0)).await;
+
+    let (mut second, _) = harness.enter(&user_id(1)).await;
+
+    let output = harness
+        .console
+        .execute(&format!("instance map_0001 {} {}", user_id(0), user_id(1)))
+        .await
+        .unwrap();
+
+    assert_eq!(output, "sent 2 users from map_0000 to map_0001");
+
+    for client in [&mut first, &mut second] {
+        let hello = client
+            .recv_matching(|packet| matches!(packet, Outgoing::Hello { .. }))
+            .await;
+
+        match hello {
+            Outgoing::Hello { map_id, actors, .. } => {
+                assert_eq!(map_id, "map_0001");
+
+                assert!(!actors.is_empty());
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    assert_eq!(
+        harness.console.execute("maps").await.unwrap(),
+        "map_0000 map_0000 0\nmap_0001#0 map_0001 2"
+    );
+
+    drop(first);
+
+    drop(second);
+
+    for _ in 0..100 {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        if harness.console.execute("maps").await.unwrap() == "map_0000 map_0000 0" {
+            return;
+        }
+    }
+
+    panic!("instance was not closed");
+}
+
+#[tokio::test(start_paused = true)]
+async fn unknown_templates_send_users_back() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(1))]).await;
+
+    let (mut client, _) = harness.enter(&user_id(0)).await;
+
+    harness
+        .console
+        .execute(&format!("instance map_9999 {}", user_id(0)))
+        .await
+        .unwrap();
+
+    let hello = client
+        .recv_matching(|packet| matches!(packet, Outgoing::Hello { .. }))
+        .await;
+
+    assert!(matches!(hello, Outgoing::Hello { map_id, .. } if map_id == "map_0000"));
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use std::collections::HashMap;
+
+use east_online_core::model::Vector3;
+use east_online_server::{
+    db::InventoryStore,
+    item,
+    net::packet::{Incoming, Outgoing},
+};
+
+use common::{flat_tiles, user_id, Harness, Options};
+
+#[tokio::test(start_paused = true)]
+async fn full_floor_stacks_overflow_into_new_ones() {
+    let definition = item::Definition {
+        id: String::from("item_0000"),
+        name: String::from("Stone"),
+        max_stack: u16::MAX,
+        consumable: false,
+    };
+
+    let mut tiles = flat_tiles(1);
+
+    let origin = Vector3 { x: 0, y: 0, z: 0 };
+
+    tiles
+        .get_mut(&origin)
+        .unwrap()
+        .items
+        .push(item::Stack::new(String::from("item_0000"), u16::MAX - 10));
+
+    let options = Options {
+        items: HashMap::from([(definition.id.to_owned(), definition)]),
+        ..Default::default()
+    };
+
+    let harness = Harness::start_with(vec![("map_0000", tiles)], options).await;
+
+    let inventory =
+        item::Inventory::from_slots(vec![(0, item::Stack::new(String::from("item_0000"), 30))]);
+
+    harness
+        .storage
+        .save_inventory(&user_id(0), &inventory)
+        .unwrap();
+
+    let (mut client, _) = harness.enter(&user_id(0)).await;
+
+    client
+        .send(Incoming::DropItem {
+            slot: 0,
+            quantity: 30,
+        })
+        .await;
+
+    let items = client
+        .recv_matching(
+            |packet| matches!(packet, Outgoing::TileItems { items, .. } if items.len() > 1),
+        )
+        .await;
+
+    assert_eq!(
+        items,
+        Outgoing::TileItems {
+            position: origin,
+            items: vec![
+                item::Stack::new(String::from("item_0000"), u16::MAX),
+                item::Stack::new(String::from("item_0000"), 20),
+            ],
+        }
+    );
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use east_online_server::{
+    limit::{Action, Limits, Rate},
+    net::packet::{Incoming, Outgoing},
+};
+use tokio::time;
+
+use common::{flat_tiles, user_id, Client, Harness, Options};
+
+#[tokio::test(start_paused = true)]
+async fn flooding_disconnects_when_configured() {
+    let mut limits = Limits {
+        action: Action::Disconnect,
+        ..Default::default()
+    };
+
+    limits.kinds.insert("inventory", Rate::new(1.0, 3.0));
+
+    let options = Options {
+        limits,
+        ..Default::default()
+    };
+
+    let harness = Harness::start_with(vec![("map_0000", flat_tiles(1))], options).await;
+
+    let (mut client, _) = harness.enter(&user_id(0)).await;
+
+    for _ in 0..4 {
+        client.send(Incoming::Inventory).await;
+    }
+
+    let mut inventories = 0;
+
+    while let Some(packet) = client.try_recv().await {
+        if matches!(packet, Outgoing::Inventory { .. }) {
+            inventories += 1;
+        }
+    }
+
+    assert_eq!(inventories, 4);
+}
+
+#[tokio::test(start_paused = true)]
+async fn throttled_packets_arrive_late() {
+    let mut limits = Limits {
+        action: Action::Throttle,
+        ..Default::default()
+    };
+
+    limits.kinds.insert("inventory", Rate::new(1.0, 1.0));
+
+    let options = Options {
+        limits,
+        ..Default::default()
+    };
+
+    let harness = Harness::start_with(vec![("map_0000", flat_tiles(1))], options).await;
+
+    let (mut client, _) = harness.enter(&user_id(0)).await;
+
+    let is_inventory = |packet: &Outgoing| matches!(packet, Outgoing::Inventory { .. });
+
+    client.recv_matching(is_inventory).await;
+
+    client.send(Incoming::Inventory).await;
+
+    client.send(Incoming::Inventory).await;
+
+    assert!(client.recv_now_matching(is_inventory).await.is_some());
+
+    assert!(client.recv_now_matching(is_inventory).await.is_none());
+
+    time::advance(time::Duration::from_secs(1)).await;
+
+    assert!(client.recv_now_matching(is_inventory).await.is_some());
+}
+
+#[tokio::test(start_paused = true)]
+async fn gate_limits_login_attempts_per_ip() {
+    let limits = Limits {
+        logins_per_ip: Rate::new(0.01, 1.0),
+        ..Default::default()
+    };
+
+    let options = Options {
+        limits,
+        ..Default::default()
+    };
+
+    let harness = Harness::start_with(vec![("map_0000", flat_tiles(1))], options).await;
+
+    let (_first, _) = harness.enter(&user_id(0)).await;
+
+    harness.storage.add_user(&user_id(1));
+
+    let mut second = Client::connect(harness.address).await;
+
+    second.hello(&user_id(1)).await;
+
+    assert!(second.try_recv().await.is_none());
+}
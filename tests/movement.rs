@@ -0,0 +1,132 @@
+mod common;
+
+use east_online_core::model::{Direction, Vector3};
+use east_online_server::{
+    map::Profile,
+    net::packet::{ActorSnapshot, Outgoing},
+};
+use tokio::time;
+
+use common::{flat_tiles, user_id, Harness};
+
+const STEP_DURATION: time::Duration = time::Duration::from_millis(300);
+
+const TIMER_RESOLUTION: time::Duration = time::Duration::from_millis(1);
+
+fn is_move(packet: &Outgoing) -> bool {
+    matches!(packet, Outgoing::Move { .. })
+}
+
+#[tokio::test(start_paused = true)]
+async fn hello_greets_the_entering_user() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(2))]).await;
+
+    let (_, hello) = harness.enter(&user_id(0)).await;
+
+    match hello {
+        Outgoing::Hello { id, actors, .. } => {
+            assert_eq!(id, user_id(0));
+
+            assert_eq!(
+                actors,
+                vec![ActorSnapshot {
+                    id: user_id(0),
+                    position: Vector3 { x: 0, y: 0, z: 0 },
+                    facing: Direction::Down,
+                    profile: Profile::default(),
+                }]
+            );
+        }
+        packet => panic!("unexpected packet, {packet:?}"),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn walking_steps_once_per_duration() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(4))]).await;
+
+    let (mut client, _) = harness.enter(&user_id(0)).await;
+
+    time::sleep(STEP_DURATION).await;
+
+    client.walk(Direction::Up).await;
+
+    let first = client.recv_now_matching(is_move).await.unwrap();
+
+    assert_eq!(
+        first,
+        Outgoing::Move {
+            id: user_id(0),
+            position: Vector3 { x: 0, y: 0, z: 1 },
+            duration: STEP_DURATION,
+        }
+    );
+
+    time::advance(STEP_DURATION - TIMER_RESOLUTION).await;
+
+    assert!(client.recv_now_matching(is_move).await.is_none());
+
+    time::advance(TIMER_RESOLUTION * 2).await;
+
+    let second = client.recv_now_matching(is_move).await.unwrap();
+
+    assert!(matches!(
+        second,
+        Outgoing::Move {
+            position: Vector3 { x: 0, y: 0, z: 2 },
+            ..
+        }
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+async fn walking_stops_at_the_end_of_the_map() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(1))]).await;
+
+    let (mut client, _) = harness.enter(&user_id(0)).await;
+
+    time::sleep(STEP_DURATION).await;
+
+    client.walk(Direction::Up).await;
+
+    let stop = client
+        .recv_matching(|packet| matches!(packet, Outgoing::Stop { .. }))
+        .await;
+
+    assert_eq!(
+        stop,
+        Outgoing::Stop {
+            id: user_id(0),
+            position: Vector3 { x: 0, y: 0, z: 1 },
+            facing: Direction::Up,
+        }
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn facing_outlasts_the_walk() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(0))]).await;
+
+    let (mut first, _) = harness.enter(&user_id(0)).await;
+
+    time::sleep(STEP_DURATION).await;
+
+    first.walk(Direction::Left).await;
+
+    first
+        .recv_matching(|packet| matches!(packet, Outgoing::Stop { .. }))
+        .await;
+
+    first.walk(Direction::Idle).await;
+
+    let (_, hello) = harness.enter(&user_id(1)).await;
+
+    match hello {
+        Outgoing::Hello { actors, .. } => {
+            let first = actors.iter().find(|actor| actor.id == user_id(0)).unwrap();
+
+            assert_eq!(first.facing, Direction::Left);
+        }
+        packet => panic!("unexpected packet, {packet:?}"),
+    }
+}
@@ -0,0 +1,218 @@
+mod common;
+
+use std::{collections::HashMap, sync::Arc};
+
+use east_online_core::model::{Direction, Vector3};
+use east_online_server::{
+    db::{self, ObjectStore},
+    item,
+    map::{self, ObjectDefinition, ObjectKind},
+    net::packet::{Incoming, Outgoing},
+};
+use tokio::{sync::mpsc, time};
+
+use common::{flat_tiles, user_id, Client, Harness, Options};
+
+const STEP_DURATION: time::Duration = time::Duration::from_millis(300);
+
+fn object(id: &str, kind: ObjectKind, position: Vector3) -> ObjectDefinition {
+    ObjectDefinition {
+        id: String::from(id),
+        kind,
+        position,
+        open: false,
+        loot: vec![],
+        targets: vec![],
+        destination: None,
+        persistent: false,
+    }
+}
+
+async fn recv_opened(client: &mut Client, id: &str) -> Outgoing {
+    client
+        .recv_matching(|packet| {
+            matches!(packet, Outgoing::Object { id: object_id, open: true, .. } if object_id == id)
+        })
+        .await
+}
+
+#[tokio::test(start_paused = true)]
+async fn switches_open_doors_in_the_way() {
+    let door = ObjectDefinition {
+        persistent: true,
+        ..object("door_0000", ObjectKind::Door, Vector3 { x: -1, y: 0, z: 0 })
+    };
+
+    let switch = ObjectDefinition {
+        targets: vec![String::from("door_0000")],
+        ..object(
+            "switch_0000",
+            ObjectKind::Switch,
+            Vector3 { x: 0, y: 0, z: -1 },
+        )
+    };
+
+    let options = Options {
+        objects: vec![("map_0000", door), ("map_0000", switch)],
+        ..Default::default()
+    };
+
+    let harness = Harness::start_with(vec![("map_0000", flat_tiles(1))], options).await;
+
+    let (mut client, _) = harness.enter(&user_id(0)).await;
+
+    time::sleep(STEP_DURATION).await;
+
+    client.walk(Direction::Right).await;
+
+    let stop = client
+        .recv_matching(|packet| matches!(packet, Outgoing::Stop { .. }))
+        .await;
+
+    assert_eq!(
+        stop,
+        Outgoing::Stop {
+            id: user_id(0),
+            position: Vector3 { x: 0, y: 0, z: 0 },
+            facing: Direction::Right,
+        }
+    );
+
+    client.walk(Direction::Down).await;
+
+    client.walk(Direction::Idle).await;
+
+    client.send(Incoming::Interact).await;
+
+    recv_opened(&mut client, "switch_0000").await;
+
+    recv_opened(&mut client, "door_0000").await;
+
+    let states = harness.storage.find_object_states("map_0000").unwrap();
+
+    assert_eq!(states, HashMap::from([(String::from("door_0000"), true)]));
+
+    client.walk(Direction::Right).await;
+
+    let moved = client
+        .recv_matching(|packet| matches!(packet, Outgoing::Move { .. }))
+        .await;
+
+    assert!(matches!(moved, Outgoing::Move { position, .. } if position.x == -1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn chests_are_looted_once() {
+    let definition = item::Definition {
+        id: String::from("item_0000"),
+        name: String::from("Stone"),
+        max_stack: 10,
+        consumable: false,
+    };
+
+    let chest = ObjectDefinition {
+        loot: vec![item::Stack::new(String::from("item_0000"), 2)],
+        ..object(
+            "chest_0000",
+            ObjectKind::Chest,
+            Vector3 { x: 0, y: 0, z: -1 },
+        )
+    };
+
+    let options = Options {
+        items: HashMap::from([(definition.id.to_owned(), definition)]),
+        objects: vec![("map_0000", chest)],
+        ..Default::default()
+    };
+
+    let harness = Harness::start_with(vec![("map_0000", flat_tiles(1))], options).await;
+
+    let (mut client, _) = harness.enter(&user_id(0)).await;
+
+    client.send(Incoming::Interact).await;
+
+    recv_opened(&mut client, "chest_0000").await;
+
+    let inventory = client
+        .recv_matching(
+            |packet| matches!(packet, Outgoing::Inventory { slots } if !slots.is_empty()),
+        )
+        .await;
+
+    assert_eq!(
+        inventory,
+        Outgoing::Inventory {
+            slots: vec![(0, item::Stack::new(String::from("item_0000"), 2))],
+        }
+    );
+
+    client.send(Incoming::Interact).await;
+
+    let again = client
+        .recv_now_matching(|packet| {
+            matches!(packet, Outgoing::Object { .. } | Outgoing::Inventory { .. })
+        })
+        .await;
+
+    assert_eq!(again, None);
+}
+
+#[test]
+fn switches_cannot_target_chests() {
+    let chest = object(
+        "chest_0000",
+        ObjectKind::Chest,
+        Vector3 { x: 0, y: 0, z: -1 },
+    );
+
+    let switch = ObjectDefinition {
+        targets: vec![String::from("chest_0000")],
+        ..object(
+            "switch_0000",
+            ObjectKind::Switch,
+            Vector3 { x: 0, y: 0, z: 1 },
+        )
+    };
+
+    let (_, enter_rx) = mpsc::channel(16);
+
+    let (exit_tx, _) = mpsc::unbounded_channel();
+
+    let mut worker = map::Worker::new(
+        String::from("map_0000"),
+        String::from("map_0000"),
+        flat_tiles(1),
+        Arc::new(HashMap::new()),
+        Arc::new(db::Memory::new()),
+        (exit_tx, enter_rx),
+    );
+
+    assert!(worker.add_objects(vec![chest, switch]).is_err());
+}
+
+#[test]
+fn chests_cannot_hold_unknown_items() {
+    let chest = ObjectDefinition {
+        loot: vec![item::Stack::new(String::from("item_9999"), 1)],
+        ..object(
+            "chest_0000",
+            ObjectKind::Chest,
+            Vector3 { x: 0, y: 0, z: -1 },
+        )
+    };
+
+    let (_, enter_rx) = mpsc::channel(16);
+
+    let (exit_tx, _) = mpsc::unbounded_channel();
+
+    let mut worker = map::Worker::new(
+        String::from("map_0000"),
+        String::from("map_0000"),
+        flat_tiles(1),
+        Arc::new(HashMap::new()),
+        Arc::new(db::Memory::new()),
+        (exit_tx, enter_rx),
+    );
+
+    assert!(worker.add_objects(vec![chest]).is_err());
+}
@@ -0,0 +1,241 @@
+mod common;
+
+use east_online_core::model::{Direction, Vector3};
+use east_online_server::{
+    map::{ObjectDefinition, ObjectKind},
+    net::packet::{Incoming, Outgoing},
+};
+
+use common::{flat_tiles, user_id, Client, Harness, Options};
+
+/**
+ * Start two maps with a portal from the first to the second, right below
+ * where players enter.
+ */
+async fn start_with_portal() -> Harness {
+    let portal = ObjectDefinition {
+        id: String::from("portal_0000"),
+        kind: ObjectKind::Portal,
+        position: Vector3 { x: 0, y: 0, z: -1 },
+        open: false,
+        loot: vec![],
+        targets: vec![],
+        destination: Some(String::from("map_0001")),
+        persistent: false,
+    };
+
+    let options = Options {
+        objects: vec![("map_0000", portal)],
+        ..Default::default()
+    };
+
+    Harness::start_with(
+        vec![("map_0000", flat_tiles(1)), ("map_0001", flat_tiles(1))],
+        options,
+    )
+    .await
+}
+
+/**
+ * Invite the second player to the party of the first and accept it.
+ */
+async fn form_party(leader: &mut Client, member: &mut Client) {
+    leader
+        .send(Incoming::PartyInvite {
+            user_id: user_id(1),
+        })
+        .await;
+
+    let invite = member
+        .recv_matching(|packet| matches!(packet, Outgoing::PartyInvite { .. }))
+        .await;
+
+    assert_eq!(invite, Outgoing::PartyInvite { id: user_id(0) });
+
+    member
+        .send(Incoming::PartyAccept {
+            user_id: user_id(0),
+        })
+        .await;
+
+    for client in [leader, member] {
+        let party = client
+            .recv_matching(|packet| matches!(packet, Outgoing::Party { .. }))
+            .await;
+
+        assert_eq!(
+            party,
+            Outgoing::Party {
+                members: vec![user_id(0), user_id(1)],
+            }
+        );
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn parties_travel_together_and_disband() {
+    let harness = Harness::start(vec![
+        ("map_0000", flat_tiles(1)),
+        ("map_0001", flat_tiles(1)),
+    ])
+    .await;
+
+    let (mut leader, _) = harness.enter(&user_id(0)).await;
+
+    let (mut member, _) = harness.enter(&user_id(1)).await;
+
+    form_party(&mut leader, &mut member).await;
+
+    let output = harness
+        .console
+        .execute(&format!("transfer map_0001 {}", user_id(0)))
+        .await
+        .unwrap();
+
+    assert_eq!(output, "sent 2 users from map_0000 to map_0001");
+
+    for client in [&mut leader, &mut member] {
+        let hello = client
+            .recv_matching(|packet| matches!(packet, Outgoing::Hello { .. }))
+            .await;
+
+        assert!(matches!(hello, Outgoing::Hello { map_id, .. } if map_id == "map_0001"));
+
+        let party = client
+            .recv_matching(|packet| matches!(packet, Outgoing::Party { .. }))
+            .await;
+
+        assert_eq!(
+            party,
+            Outgoing::Party {
+                members: vec![user_id(0), user_id(1)],
+            }
+        );
+    }
+
+    leader
+        .send(Incoming::PartyKick {
+            user_id: user_id(1),
+        })
+        .await;
+
+    for client in [&mut leader, &mut member] {
+        let party = client
+            .recv_matching(|packet| matches!(packet, Outgoing::Party { .. }))
+            .await;
+
+        assert_eq!(party, Outgoing::Party { members: vec![] });
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn portals_send_parties_together() {
+    let harness = start_with_portal().await;
+
+    let (mut leader, _) = harness.enter(&user_id(0)).await;
+
+    let (mut member, _) = harness.enter(&user_id(1)).await;
+
+    form_party(&mut leader, &mut member).await;
+
+    leader.send(Incoming::Interact).await;
+
+    for client in [&mut leader, &mut member] {
+        let hello = client
+            .recv_matching(|packet| matches!(packet, Outgoing::Hello { .. }))
+            .await;
+
+        assert!(matches!(hello, Outgoing::Hello { map_id, .. } if map_id == "map_0001"));
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn portals_refuse_parties_split_across_maps() {
+    let harness = start_with_portal().await;
+
+    harness.storage.set_map_id(&user_id(1), "map_0001");
+
+    let (mut leader, _) = harness.enter(&user_id(0)).await;
+
+    let (mut member, _) = harness.enter(&user_id(1)).await;
+
+    form_party(&mut leader, &mut member).await;
+
+    leader.send(Incoming::Interact).await;
+
+    let error = leader
+        .recv_matching(|packet| matches!(packet, Outgoing::Error { .. }))
+        .await;
+
+    assert!(matches!(error, Outgoing::Error { code: 2, .. }));
+
+    let output = harness.console.execute("users").await.unwrap();
+
+    assert!(output.contains(&format!("{} map_0000", user_id(0))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn members_see_each_other_across_maps() {
+    let harness = Harness::start(vec![
+        ("map_0000", flat_tiles(1)),
+        ("map_0001", flat_tiles(1)),
+    ])
+    .await;
+
+    harness.storage.set_map_id(&user_id(1), "map_0001");
+
+    let (mut leader, _) = harness.enter(&user_id(0)).await;
+
+    let (mut member, _) = harness.enter(&user_id(1)).await;
+
+    form_party(&mut leader, &mut member).await;
+
+    let shown = leader
+        .recv_matching(|packet| matches!(packet, Outgoing::PartyMember { .. }))
+        .await;
+
+    assert_eq!(
+        shown,
+        Outgoing::PartyMember {
+            id: user_id(1),
+            map_id: String::from("map_0001"),
+            position: Vector3 { x: 0, y: 0, z: 0 },
+        }
+    );
+
+    member.walk(Direction::Right).await;
+
+    let moved = leader
+        .recv_matching(
+            |packet| matches!(packet, Outgoing::PartyMember { position, .. } if position.x == -1),
+        )
+        .await;
+
+    assert_eq!(
+        moved,
+        Outgoing::PartyMember {
+            id: user_id(1),
+            map_id: String::from("map_0001"),
+            position: Vector3 { x: -1, y: 0, z: 0 },
+        }
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn invites_need_a_connected_player() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(1))]).await;
+
+    let (mut leader, _) = harness.enter(&user_id(0)).await;
+
+    leader
+        .send(Incoming::PartyInvite {
+            user_id: user_id(9),
+        })
+        .await;
+
+    let error = leader
+        .recv_matching(|packet| matches!(packet, Outgoing::Error { .. }))
+        .await;
+
+    assert!(matches!(error, Outgoing::Error { code: 2, .. }));
+}
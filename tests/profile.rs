@@ -0,0 +1,74 @@
+mod common;
+
+use east_online_server::{db::ProfileStore, map::Profile, net::packet::Outgoing};
+
+use common::{flat_tiles, user_id, Harness};
+
+fn is_profile(packet: &Outgoing) -> bool {
+    matches!(packet, Outgoing::Profile { .. })
+}
+
+#[tokio::test(start_paused = true)]
+async fn entering_players_see_stored_profiles() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(1))]).await;
+
+    let profile = Profile::new(String::from("Mina"), String::from("robe"), 7);
+
+    harness.storage.set_profile(&user_id(0), profile.clone());
+
+    let (_first, _) = harness.enter(&user_id(0)).await;
+
+    let (_, hello) = harness.enter(&user_id(1)).await;
+
+    match hello {
+        Outgoing::Hello { actors, .. } => {
+            let first = actors.iter().find(|actor| actor.id == user_id(0)).unwrap();
+
+            assert_eq!(first.profile, profile);
+
+            let second = actors.iter().find(|actor| actor.id == user_id(1)).unwrap();
+
+            assert_eq!(second.profile, Profile::default());
+        }
+        packet => panic!("unexpected packet, {packet:?}"),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn profile_changes_reach_the_whole_map() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(1))]).await;
+
+    let (_first, _) = harness.enter(&user_id(0)).await;
+
+    let (mut second, _) = harness.enter(&user_id(1)).await;
+
+    harness
+        .console
+        .execute(&format!("profile {} level 5", user_id(0)))
+        .await
+        .unwrap();
+
+    let expected = Profile::new(String::new(), String::new(), 5);
+
+    let profile = second
+        .recv_matching(|packet| matches!(packet, Outgoing::Profile { id, .. } if *id == user_id(0)))
+        .await;
+
+    assert_eq!(
+        profile,
+        Outgoing::Profile {
+            id: user_id(0),
+            profile: expected.clone(),
+        }
+    );
+
+    assert_eq!(harness.storage.find_profile(&user_id(0)).unwrap(), expected);
+
+    assert!(harness
+        .console
+        .execute(&format!("profile {} level 0", user_id(0)))
+        .await
+        .is_err());
+
+    assert!(second.recv_now_matching(is_profile).await.is_none());
+}
@@ -0,0 +1,332 @@
+use std::time::Duration;
+
+use east_online_core::model::{Direction, Vector3};
+use east_online_server::{
+    item,
+    map::{self, Profile},
+    net::{
+        client::{get_packet_buf, take_packet, take_packet_for},
+        io::{get_packet_buf as get_outgoing_packet_buf, get_packet_buf_for},
+        packet::{ActorSnapshot, Incoming, Outgoing, TradeSide},
+    },
+    shop,
+};
+
+fn id(index: usize) -> String {
+    format!("00000000-0000-4000-8000-{:012}", index)
+}
+
+#[test]
+fn incoming_packets_survive_a_round_trip() {
+    let packets = vec![
+        Incoming::Hello {
+            version: 1,
+            features: Vec::new(),
+            token: String::from("token"),
+        },
+        Incoming::Hello {
+            version: 2,
+            features: vec![String::from("inventory"), String::from("combat")],
+            token: String::from("token"),
+        },
+        Incoming::Move {
+            direction: Direction::Left,
+        },
+        Incoming::Inventory,
+        Incoming::MoveItem { from: 1, to: 2 },
+        Incoming::UseItem { slot: 3 },
+        Incoming::DropItem {
+            slot: 4,
+            quantity: 500,
+        },
+        Incoming::Attack,
+        Incoming::PartyInvite { user_id: id(1) },
+        Incoming::PartyAccept { user_id: id(2) },
+        Incoming::PartyLeave,
+        Incoming::PartyKick { user_id: id(3) },
+        Incoming::FriendRequest { user_id: id(4) },
+        Incoming::FriendAccept { user_id: id(5) },
+        Incoming::TradeRequest { user_id: id(6) },
+        Incoming::TradeAccept { user_id: id(7) },
+        Incoming::TradeOffer {
+            slots: vec![(0, 2), (5, 1)],
+            coins: 300,
+        },
+        Incoming::TradeLock,
+        Incoming::TradeConfirm,
+        Incoming::TradeCancel,
+        Incoming::ShopOpen { shop_id: id(8) },
+        Incoming::ShopBuy {
+            shop_id: id(8),
+            item_id: String::from("item_0000"),
+            quantity: 3,
+        },
+        Incoming::ShopSell {
+            shop_id: id(8),
+            slot: 4,
+            quantity: 1,
+        },
+        Incoming::Talk { npc_id: id(9) },
+        Incoming::Interact,
+        Incoming::Emote { emote: 2 },
+    ];
+
+    for packet in packets {
+        let buf = get_packet_buf(&packet).unwrap();
+
+        assert_eq!(
+            usize::from(u16::from_le_bytes([buf[0], buf[1]])),
+            buf.len() - 2
+        );
+
+        assert_eq!(Incoming::deserialize(&buf[2..]).unwrap(), packet);
+    }
+}
+
+#[test]
+fn outgoing_packets_survive_a_round_trip() {
+    let position = Vector3 { x: -1, y: 2, z: 3 };
+
+    let packets = vec![
+        Outgoing::Hello {
+            id: id(0),
+            map_id: String::from("map_0000"),
+            actors: vec![
+                ActorSnapshot {
+                    id: id(0),
+                    position,
+                    facing: Direction::Down,
+                    profile: Profile::default(),
+                },
+                ActorSnapshot {
+                    id: id(1),
+                    position,
+                    facing: Direction::Left,
+                    profile: Profile::new(String::from("Mina"), String::from("robe"), 7),
+                },
+            ],
+        },
+        Outgoing::Move {
+            id: id(0),
+            position,
+            duration: Duration::from_millis(300),
+        },
+        Outgoing::Stop {
+            id: id(0),
+            position,
+            facing: Direction::Up,
+        },
+        Outgoing::Inventory {
+            slots: vec![(0, item::Stack::new(String::from("item_0000"), 3))],
+        },
+        Outgoing::TileItems {
+            position,
+            items: vec![item::Stack::new(String::from("item_0001"), 1)],
+        },
+        Outgoing::UseItem {
+            id: id(0),
+            item_id: String::from("item_0000"),
+        },
+        Outgoing::Damage {
+            id: id(0),
+            target: id(1),
+            amount: 10,
+            health: 90,
+        },
+        Outgoing::Die { id: id(1) },
+        Outgoing::Respawn {
+            id: id(1),
+            position,
+            health: 100,
+        },
+        Outgoing::Error {
+            code: 1,
+            message: String::from("protocol version 9 is not supported"),
+        },
+        Outgoing::Capabilities {
+            version: 2,
+            features: vec![String::from("combat")],
+        },
+        Outgoing::Queue { position: 3 },
+        Outgoing::Notice {
+            message: String::from("server restarts in 5 minutes"),
+        },
+        Outgoing::MapUpdate {
+            tiles: vec![
+                (
+                    Vector3 { x: 4, y: 1, z: -2 },
+                    Some((String::from("stairs_0000"), 2)),
+                ),
+                (Vector3 { x: 0, y: 0, z: 0 }, None),
+            ],
+        },
+        Outgoing::PartyInvite { id: id(1) },
+        Outgoing::Party {
+            members: vec![id(1), id(2)],
+        },
+        Outgoing::Party { members: vec![] },
+        Outgoing::PartyMember {
+            id: id(2),
+            map_id: String::from("map_0001"),
+            position: Vector3 { x: 5, y: 0, z: -5 },
+        },
+        Outgoing::FriendRequest { id: id(3) },
+        Outgoing::Presence {
+            id: id(3),
+            map_id: Some(String::from("map_0001#2")),
+        },
+        Outgoing::Presence {
+            id: id(3),
+            map_id: None,
+        },
+        Outgoing::TradeRequest { id: id(4) },
+        Outgoing::Trade {
+            sides: vec![
+                TradeSide {
+                    id: id(4),
+                    items: vec![item::Stack::new(String::from("item_0000"), 2)],
+                    coins: 0,
+                    locked: true,
+                    confirmed: false,
+                },
+                TradeSide {
+                    id: id(5),
+                    items: vec![],
+                    coins: 30,
+                    locked: true,
+                    confirmed: true,
+                },
+            ],
+        },
+        Outgoing::TradeClosed {
+            message: String::from("completed"),
+        },
+        Outgoing::Wallet { coins: 1 << 40 },
+        Outgoing::Shop {
+            id: id(6),
+            goods: vec![
+                shop::Goods {
+                    item_id: String::from("item_0000"),
+                    price: 10,
+                    sell_price: Some(4),
+                    stock: None,
+                },
+                shop::Goods {
+                    item_id: String::from("item_0001"),
+                    price: 250,
+                    sell_price: None,
+                    stock: Some(2),
+                },
+            ],
+        },
+        Outgoing::Quest {
+            id: String::from("quest_0000"),
+            counts: vec![1, 0, 12],
+            completed: false,
+        },
+        Outgoing::Object {
+            id: String::from("door_0000"),
+            position: Vector3 { x: 1, y: 0, z: -2 },
+            kind: map::ObjectKind::Door,
+            open: true,
+        },
+        Outgoing::Emote {
+            id: id(0),
+            emote: 2,
+        },
+    ];
+
+    let mut buf = Vec::new();
+
+    for packet in &packets {
+        buf.extend(get_outgoing_packet_buf(packet.clone()).unwrap());
+    }
+
+    for packet in packets {
+        assert_eq!(take_packet(&mut buf).unwrap(), Some(packet));
+    }
+
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn older_clients_get_actors_in_their_layout() {
+    let position = Vector3 { x: -1, y: 2, z: 3 };
+
+    let actor = ActorSnapshot {
+        id: id(1),
+        position,
+        facing: Direction::Left,
+        profile: Profile::new(String::from("Mina"), String::from("robe"), 7),
+    };
+
+    let cases = [
+        (
+            2,
+            ActorSnapshot {
+                facing: Direction::Down,
+                profile: Profile::default(),
+                ..actor.clone()
+            },
+        ),
+        (
+            3,
+            ActorSnapshot {
+                profile: Profile::default(),
+                ..actor.clone()
+            },
+        ),
+    ];
+
+    for (version, expected) in cases {
+        let packets = [
+            (
+                Outgoing::Hello {
+                    id: id(0),
+                    map_id: String::from("map_0000"),
+                    actors: vec![actor.clone()],
+                },
+                Outgoing::Hello {
+                    id: id(0),
+                    map_id: String::from("map_0000"),
+                    actors: vec![expected.clone()],
+                },
+            ),
+            (
+                Outgoing::Stop {
+                    id: id(1),
+                    position,
+                    facing: actor.facing,
+                },
+                Outgoing::Stop {
+                    id: id(1),
+                    position,
+                    facing: expected.facing,
+                },
+            ),
+        ];
+
+        for (packet, expected) in packets {
+            let mut buf = get_packet_buf_for(packet, version).unwrap();
+
+            assert_eq!(take_packet_for(&mut buf, version).unwrap(), Some(expected));
+
+            assert!(buf.is_empty());
+        }
+    }
+}
+
+#[test]
+fn partial_frames_wait_for_the_rest() {
+    let packet = Outgoing::Die { id: id(0) };
+
+    let frame = get_outgoing_packet_buf(packet.clone()).unwrap();
+
+    let mut buf = frame[..frame.len() - 1].to_vec();
+
+    assert_eq!(take_packet(&mut buf).unwrap(), None);
+
+    buf.push(frame[frame.len() - 1]);
+
+    assert_eq!(take_packet(&mut buf).unwrap(), Some(packet));
+}
@@ -0,0 +1,246 @@
+mod common;
+
+use std::collections::HashMap;
+
+use east_online_core::model::{Direction, Vector3};
+use east_online_server::{
+    db::QuestStore,
+    item::{self, Inventory},
+    map::{NpcDefinition, NpcManifest},
+    net::packet::{Incoming, Outgoing},
+    quest::{self, Journal, Objective, Trigger},
+    shop,
+};
+use tokio::time;
+
+use common::{flat_tiles, user_id, Client, Harness, Options};
+
+const STEP_DURATION: time::Duration = time::Duration::from_millis(300);
+
+fn registry(definitions: Vec<quest::Definition>) -> quest::Registry {
+    definitions
+        .into_iter()
+        .map(|definition| (definition.id.to_owned(), definition))
+        .collect()
+}
+
+fn quest_packet(id: &str, counts: Vec<u16>, completed: bool) -> Outgoing {
+    Outgoing::Quest {
+        id: String::from(id),
+        counts,
+        completed,
+    }
+}
+
+async fn recv_quest(client: &mut Client) -> Outgoing {
+    client
+        .recv_matching(|packet| matches!(packet, Outgoing::Quest { .. }))
+        .await
+}
+
+#[tokio::test(start_paused = true)]
+async fn quests_follow_moves_and_items_across_logins() {
+    let definition = quest::Definition {
+        id: String::from("quest_0000"),
+        name: String::from("Quarry"),
+        giver: None,
+        objectives: vec![
+            Objective::Reach {
+                map_id: String::from("map_0000"),
+                position: Vector3 { x: -1, y: 0, z: 0 },
+            },
+            Objective::Collect {
+                item_id: String::from("item_0000"),
+                quantity: 2,
+            },
+        ],
+    };
+
+    let options = Options {
+        quests: registry(vec![definition]),
+        ..Default::default()
+    };
+
+    let harness = Harness::start_with(vec![("map_0000", flat_tiles(1))], options).await;
+
+    let stones = item::Stack::new(String::from("item_0000"), 5);
+
+    harness
+        .storage
+        .set_inventory(&user_id(0), Inventory::from_slots(vec![(0, stones)]));
+
+    let (mut client, _) = harness.enter(&user_id(0)).await;
+
+    assert_eq!(
+        recv_quest(&mut client).await,
+        quest_packet("quest_0000", vec![0, 2], false)
+    );
+
+    time::sleep(STEP_DURATION).await;
+
+    client.walk(Direction::Right).await;
+
+    assert_eq!(
+        recv_quest(&mut client).await,
+        quest_packet("quest_0000", vec![1, 2], true)
+    );
+
+    drop(client);
+
+    let (mut client, _) = harness.enter(&user_id(0)).await;
+
+    assert_eq!(
+        recv_quest(&mut client).await,
+        quest_packet("quest_0000", vec![1, 2], true)
+    );
+
+    let journal = harness.storage.find_journal(&user_id(0)).unwrap();
+
+    assert_eq!(journal.get("quest_0000").unwrap().counts, vec![1, 2]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn givers_start_quests_when_talked_to() {
+    let npc_id = user_id(900);
+
+    let definition = quest::Definition {
+        id: String::from("quest_0001"),
+        name: String::from("Errand"),
+        giver: Some(npc_id.to_owned()),
+        objectives: vec![Objective::Talk {
+            npc_id: npc_id.to_owned(),
+        }],
+    };
+
+    let npc = shop::Definition {
+        id: npc_id.to_owned(),
+        name: String::from("Mason"),
+        position: Vector3 { x: 0, y: 0, z: 1 },
+        goods: vec![],
+    };
+
+    let options = Options {
+        quests: registry(vec![definition]),
+        shops: vec![("map_0000", npc)],
+        ..Default::default()
+    };
+
+    let harness = Harness::start_with(vec![("map_0000", flat_tiles(1))], options).await;
+
+    let (mut client, _) = harness.enter(&user_id(0)).await;
+
+    client.send(Incoming::Talk { npc_id }).await;
+
+    assert_eq!(
+        recv_quest(&mut client).await,
+        quest_packet("quest_0001", vec![1], true)
+    );
+}
+
+#[test]
+fn defeats_count_up_to_the_goal() {
+    let definition = quest::Definition {
+        id: String::from("quest_0002"),
+        name: String::from("Hunt"),
+        giver: None,
+        objectives: vec![Objective::Defeat {
+            species: String::from("wolf"),
+            count: 2,
+        }],
+    };
+
+    let registry = registry(vec![definition]);
+
+    let mut journal = Journal::new();
+
+    let wolf = Trigger::Defeat {
+        species: String::from("wolf"),
+    };
+
+    let boar = Trigger::Defeat {
+        species: String::from("boar"),
+    };
+
+    assert!(journal.record(&registry, &wolf).is_empty());
+
+    journal.start("quest_0002");
+
+    assert!(journal.record(&registry, &boar).is_empty());
+
+    for _ in 0..3 {
+        journal.record(&registry, &wolf);
+    }
+
+    let progress = journal.get("quest_0002").unwrap();
+
+    assert_eq!(progress.counts, vec![2]);
+
+    assert!(progress.is_complete(&registry["quest_0002"]));
+
+    assert_eq!(
+        progress.to_string().parse::<quest::Progress>().unwrap(),
+        *progress
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn defeating_a_species_advances_quests() {
+    let definition = quest::Definition {
+        id: String::from("quest_0002"),
+        name: String::from("Hunt"),
+        giver: None,
+        objectives: vec![Objective::Defeat {
+            species: String::from("wolf"),
+            count: 1,
+        }],
+    };
+
+    let wolf = NpcDefinition {
+        id: user_id(901),
+        name: String::from("Wolf"),
+        position: Vector3 { x: 0, y: 0, z: -1 },
+        facing: Direction::Up,
+        health: 5,
+        defense: 0,
+        power: 0,
+        species: Some(String::from("wolf")),
+    };
+
+    let manifest = NpcManifest {
+        spawn: None,
+        npcs: vec![wolf],
+    };
+
+    let options = Options {
+        quests: registry(vec![definition]),
+        npcs: vec![("map_0000", manifest)],
+        ..Default::default()
+    };
+
+    let harness = Harness::start_with(vec![("map_0000", flat_tiles(1))], options).await;
+
+    let (mut client, _) = harness.enter(&user_id(0)).await;
+
+    assert_eq!(
+        recv_quest(&mut client).await,
+        quest_packet("quest_0002", vec![0], false)
+    );
+
+    client.send(Incoming::Attack).await;
+
+    assert_eq!(
+        recv_quest(&mut client).await,
+        quest_packet("quest_0002", vec![1], true)
+    );
+}
+
+#[test]
+fn unknown_quests_are_left_alone() {
+    let mut journal = Journal::from_quests(vec![(String::from("gone"), Default::default())]);
+
+    let trigger = Trigger::Talk {
+        npc_id: String::from("anyone"),
+    };
+
+    assert!(journal.record(&HashMap::new(), &trigger).is_empty());
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use east_online_server::{gate::Capacity, net::packet::Outgoing};
+
+use common::{flat_tiles, user_id, Client, Harness, Options};
+
+async fn start(players: usize, map_players: usize) -> Harness {
+    let options = Options {
+        capacity: Capacity {
+            players,
+            map_players,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    Harness::start_with(
+        vec![("map_0000", flat_tiles(1)), ("map_0001", flat_tiles(1))],
+        options,
+    )
+    .await
+}
+
+async fn wait_in_line(harness: &Harness, index: usize) -> Client {
+    harness.storage.add_user(&user_id(index));
+
+    let mut client = Client::connect(harness.address).await;
+
+    client.hello(&user_id(index)).await;
+
+    client
+}
+
+#[tokio::test(start_paused = true)]
+async fn full_servers_queue_players_in_order() {
+    let harness = start(1, 1).await;
+
+    let (first, _) = harness.enter(&user_id(0)).await;
+
+    let mut second = wait_in_line(&harness, 1).await;
+
+    let mut third = wait_in_line(&harness, 2).await;
+
+    let is_queue = |packet: &Outgoing| matches!(packet, Outgoing::Queue { .. });
+
+    assert_eq!(
+        second.recv_matching(is_queue).await,
+        Outgoing::Queue { position: 1 }
+    );
+
+    assert_eq!(
+        third.recv_matching(is_queue).await,
+        Outgoing::Queue { position: 2 }
+    );
+
+    drop(first);
+
+    let hello = second
+        .recv_matching(|packet| matches!(packet, Outgoing::Hello { .. }))
+        .await;
+
+    assert!(matches!(hello, Outgoing::Hello { id, .. } if id == user_id(1)));
+
+    assert_eq!(
+        third.recv_matching(is_queue).await,
+        Outgoing::Queue { position: 1 }
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn full_maps_do_not_hold_back_others() {
+    let harness = start(10, 1).await;
+
+    let (_first, _) = harness.enter(&user_id(0)).await;
+
+    let mut second = wait_in_line(&harness, 1).await;
+
+    assert_eq!(
+        second
+            .recv_matching(|packet| matches!(packet, Outgoing::Queue { .. }))
+            .await,
+        Outgoing::Queue { position: 1 }
+    );
+
+    harness.storage.set_map_id(&user_id(2), "map_0001");
+
+    let (_third, hello) = harness.enter(&user_id(2)).await;
+
+    assert!(matches!(hello, Outgoing::Hello { map_id, .. } if map_id == "map_0001"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn failed_entries_release_their_slot() {
+    let harness = start(1, 1).await;
+
+    harness.storage.break_wallet(&user_id(0));
+
+    let mut first = wait_in_line(&harness, 0).await;
+
+    while first.try_recv().await.is_some() {}
+
+    let (_second, hello) = harness.enter(&user_id(1)).await;
+
+    assert!(matches!(hello, Outgoing::Hello { id, .. } if id == user_id(1)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn transfers_keep_their_slot() {
+    let harness = start(1, 1).await;
+
+    let (mut first, _) = harness.enter(&user_id(0)).await;
+
+    let mut second = wait_in_line(&harness, 1).await;
+
+    let is_queue = |packet: &Outgoing| matches!(packet, Outgoing::Queue { .. });
+
+    assert_eq!(
+        second.recv_matching(is_queue).await,
+        Outgoing::Queue { position: 1 }
+    );
+
+    harness
+        .console
+        .execute(&format!("transfer map_0001 {}", user_id(0)))
+        .await
+        .unwrap();
+
+    let entered = first
+        .recv_matching(|packet| matches!(packet, Outgoing::Hello { .. } | Outgoing::Queue { .. }))
+        .await;
+
+    assert!(matches!(entered, Outgoing::Hello { map_id, .. } if map_id == "map_0001"));
+
+    drop(first);
+
+    let hello = second
+        .recv_matching(|packet| matches!(packet, Outgoing::Hello { .. }))
+        .await;
+
+    assert!(matches!(hello, Outgoing::Hello { id, .. } if id == user_id(1)));
+}
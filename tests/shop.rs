@@ -0,0 +1,142 @@
+mod common;
+
+use std::collections::HashMap;
+
+use east_online_core::model::Vector3;
+use east_online_server::{
+    db::WalletStore,
+    item::{self, Wallet},
+    net::packet::{Incoming, Outgoing},
+    shop,
+};
+
+use common::{flat_tiles, user_id, Client, Harness, Options};
+
+fn shop_id() -> String {
+    user_id(900)
+}
+
+fn options(position: Vector3) -> Options {
+    let definition = item::Definition {
+        id: String::from("item_0000"),
+        name: String::from("Stone"),
+        max_stack: 10,
+        consumable: false,
+    };
+
+    let shop = shop::Definition {
+        id: shop_id(),
+        name: String::from("Mason"),
+        position,
+        goods: vec![shop::Goods {
+            item_id: String::from("item_0000"),
+            price: 10,
+            sell_price: Some(4),
+            stock: Some(3),
+        }],
+    };
+
+    Options {
+        items: HashMap::from([(definition.id.to_owned(), definition)]),
+        shops: vec![("map_0000", shop)],
+        ..Default::default()
+    }
+}
+
+async fn recv_wallet(client: &mut Client) -> Outgoing {
+    client
+        .recv_matching(|packet| matches!(packet, Outgoing::Wallet { .. }))
+        .await
+}
+
+async fn recv_error(client: &mut Client) -> Outgoing {
+    client
+        .recv_matching(|packet| matches!(packet, Outgoing::Error { .. }))
+        .await
+}
+
+#[tokio::test(start_paused = true)]
+async fn players_buy_and_sell_from_stock() {
+    let options = options(Vector3 { x: 0, y: 0, z: 1 });
+
+    let harness = Harness::start_with(vec![("map_0000", flat_tiles(1))], options).await;
+
+    harness.storage.set_wallet(&user_id(0), Wallet::new(100));
+
+    let (mut client, _) = harness.enter(&user_id(0)).await;
+
+    assert_eq!(
+        recv_wallet(&mut client).await,
+        Outgoing::Wallet { coins: 100 }
+    );
+
+    client
+        .send(Incoming::ShopBuy {
+            shop_id: shop_id(),
+            item_id: String::from("item_0000"),
+            quantity: 2,
+        })
+        .await;
+
+    assert_eq!(
+        recv_wallet(&mut client).await,
+        Outgoing::Wallet { coins: 80 }
+    );
+
+    let shop = client
+        .recv_matching(|packet| matches!(packet, Outgoing::Shop { .. }))
+        .await;
+
+    match shop {
+        Outgoing::Shop { goods, .. } => assert_eq!(goods[0].stock, Some(1)),
+        _ => unreachable!(),
+    }
+
+    client
+        .send(Incoming::ShopBuy {
+            shop_id: shop_id(),
+            item_id: String::from("item_0000"),
+            quantity: 2,
+        })
+        .await;
+
+    assert!(matches!(
+        recv_error(&mut client).await,
+        Outgoing::Error { code: 5, .. }
+    ));
+
+    client
+        .send(Incoming::ShopSell {
+            shop_id: shop_id(),
+            slot: 0,
+            quantity: 1,
+        })
+        .await;
+
+    assert_eq!(
+        recv_wallet(&mut client).await,
+        Outgoing::Wallet { coins: 84 }
+    );
+
+    assert_eq!(harness.storage.find_wallet(&user_id(0)).unwrap().coins, 84);
+}
+
+#[tokio::test(start_paused = true)]
+async fn shops_out_of_reach_refuse_players() {
+    let options = options(Vector3 { x: 1, y: 0, z: 1 });
+
+    let harness = Harness::start_with(vec![("map_0000", flat_tiles(1))], options).await;
+
+    harness.storage.set_wallet(&user_id(0), Wallet::new(100));
+
+    let (mut client, _) = harness.enter(&user_id(0)).await;
+
+    client.send(Incoming::ShopOpen { shop_id: shop_id() }).await;
+
+    assert!(matches!(
+        recv_error(&mut client).await,
+        Outgoing::Error { code: 5, .. }
+    ));
+
+    assert_eq!(harness.storage.find_wallet(&user_id(0)).unwrap().coins, 100);
+}
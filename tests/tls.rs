@@ -0,0 +1,75 @@
+mod common;
+
+use std::sync::Arc;
+
+use east_online_server::net::{
+    client::Connection,
+    packet::{Incoming, Outgoing},
+    protocol, tls,
+};
+use tokio::net::TcpStream;
+use tokio_rustls::{
+    rustls::{Certificate, ClientConfig, RootCertStore, ServerName},
+    TlsConnector,
+};
+
+use common::{flat_tiles, user_id, Harness, Options};
+
+#[tokio::test]
+async fn tls_clients_enter_like_plain_ones() {
+    let cert = rcgen::generate_simple_self_signed(vec![String::from("localhost")]).unwrap();
+
+    let acceptor = tls::acceptor_from_pem(
+        cert.serialize_pem().unwrap().as_bytes(),
+        cert.serialize_private_key_pem().as_bytes(),
+    )
+    .unwrap();
+
+    let options = Options {
+        acceptor: Some(acceptor),
+        ..Default::default()
+    };
+
+    let harness = Harness::start_with(vec![("map_0000", flat_tiles(1))], options).await;
+
+    harness.storage.add_user(&user_id(0));
+
+    let mut roots = RootCertStore::empty();
+
+    roots
+        .add(&Certificate(cert.serialize_der().unwrap()))
+        .unwrap();
+
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+
+    let stream = TcpStream::connect(harness.address).await.unwrap();
+
+    let stream = TlsConnector::from(Arc::new(config))
+        .connect(ServerName::try_from("localhost").unwrap(), stream)
+        .await
+        .unwrap();
+
+    let mut connection = Connection::new(stream);
+
+    connection
+        .send(&Incoming::Hello {
+            version: protocol::PROTOCOL_VERSION,
+            features: Vec::new(),
+            token: east_online_server::auth::Stub::token(&user_id(0)),
+        })
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        connection.recv().await.unwrap(),
+        Outgoing::Capabilities { .. }
+    ));
+
+    assert!(matches!(
+        connection.recv().await.unwrap(),
+        Outgoing::Hello { id, .. } if id == user_id(0)
+    ));
+}
@@ -0,0 +1,270 @@
+mod common;
+
+use std::collections::HashMap;
+
+use east_online_core::model::Direction;
+use east_online_server::{
+    db::{InventoryStore, WalletStore},
+    item::{self, Inventory, Wallet},
+    net::packet::{Incoming, Outgoing},
+    trade::{self, Offer},
+};
+use tokio::time;
+
+use common::{flat_tiles, user_id, Client, Harness, Options};
+
+const STEP_DURATION: time::Duration = time::Duration::from_millis(300);
+
+fn items() -> item::Registry {
+    let definition = item::Definition {
+        id: String::from("item_0000"),
+        name: String::from("Stone"),
+        max_stack: 10,
+        consumable: false,
+    };
+
+    HashMap::from([(definition.id.to_owned(), definition)])
+}
+
+/**
+ * Enter two players standing next to each other.
+ */
+async fn enter_side_by_side(harness: &Harness) -> (Client, Client) {
+    let (mut first, _) = harness.enter(&user_id(0)).await;
+
+    let (mut second, _) = harness.enter(&user_id(1)).await;
+
+    time::sleep(STEP_DURATION).await;
+
+    second.walk(Direction::Right).await;
+
+    first
+        .recv_matching(|packet| matches!(packet, Outgoing::Stop { id, .. } if *id == user_id(1)))
+        .await;
+
+    (first, second)
+}
+
+async fn open_trade(first: &mut Client, second: &mut Client) {
+    first
+        .send(Incoming::TradeRequest {
+            user_id: user_id(1),
+        })
+        .await;
+
+    let request = second
+        .recv_matching(|packet| matches!(packet, Outgoing::TradeRequest { .. }))
+        .await;
+
+    assert_eq!(request, Outgoing::TradeRequest { id: user_id(0) });
+
+    second
+        .send(Incoming::TradeAccept {
+            user_id: user_id(0),
+        })
+        .await;
+
+    for client in [first, second] {
+        client
+            .recv_matching(|packet| matches!(packet, Outgoing::Trade { .. }))
+            .await;
+    }
+}
+
+async fn recv_closed(client: &mut Client) -> Outgoing {
+    client
+        .recv_matching(|packet| matches!(packet, Outgoing::TradeClosed { .. }))
+        .await
+}
+
+#[tokio::test(start_paused = true)]
+async fn confirmed_trades_swap_items_and_coins() {
+    let options = Options {
+        items: items(),
+        ..Default::default()
+    };
+
+    let harness = Harness::start_with(vec![("map_0000", flat_tiles(1))], options).await;
+
+    let stones = item::Stack::new(String::from("item_0000"), 3);
+
+    harness
+        .storage
+        .set_inventory(&user_id(0), Inventory::from_slots(vec![(0, stones)]));
+
+    harness.storage.set_wallet(&user_id(1), Wallet::new(50));
+
+    let (mut first, mut second) = enter_side_by_side(&harness).await;
+
+    open_trade(&mut first, &mut second).await;
+
+    first
+        .send(Incoming::TradeOffer {
+            slots: vec![(0, 2)],
+            coins: 0,
+        })
+        .await;
+
+    first.send(Incoming::TradeLock).await;
+
+    second
+        .send(Incoming::TradeOffer {
+            slots: vec![],
+            coins: 30,
+        })
+        .await;
+
+    second.send(Incoming::TradeLock).await;
+
+    first
+        .recv_matching(|packet| match packet {
+            Outgoing::Trade { sides } => sides.iter().all(|side| side.locked),
+            _ => false,
+        })
+        .await;
+
+    first.send(Incoming::TradeConfirm).await;
+
+    second.send(Incoming::TradeConfirm).await;
+
+    for client in [&mut first, &mut second] {
+        let closed = recv_closed(client).await;
+
+        assert_eq!(
+            closed,
+            Outgoing::TradeClosed {
+                message: String::from("completed"),
+            }
+        );
+    }
+
+    assert_eq!(harness.storage.find_wallet(&user_id(0)).unwrap().coins, 30);
+
+    assert_eq!(harness.storage.find_wallet(&user_id(1)).unwrap().coins, 20);
+
+    let inventory = harness.storage.find_inventory(&user_id(1)).unwrap();
+
+    assert_eq!(
+        inventory.get(0),
+        Some(&item::Stack::new(String::from("item_0000"), 2))
+    );
+
+    let inventory = harness.storage.find_inventory(&user_id(0)).unwrap();
+
+    assert_eq!(inventory.get(0).map(|stack| stack.quantity), Some(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn offered_items_stay_put_during_a_trade() {
+    let options = Options {
+        items: items(),
+        ..Default::default()
+    };
+
+    let harness = Harness::start_with(vec![("map_0000", flat_tiles(1))], options).await;
+
+    let stones = item::Stack::new(String::from("item_0000"), 3);
+
+    harness
+        .storage
+        .set_inventory(&user_id(0), Inventory::from_slots(vec![(0, stones)]));
+
+    let (mut first, mut second) = enter_side_by_side(&harness).await;
+
+    open_trade(&mut first, &mut second).await;
+
+    first
+        .send(Incoming::TradeOffer {
+            slots: vec![(0, 2)],
+            coins: 0,
+        })
+        .await;
+
+    first.send(Incoming::MoveItem { from: 0, to: 1 }).await;
+
+    let error = first
+        .recv_matching(|packet| matches!(packet, Outgoing::Error { .. }))
+        .await;
+
+    assert!(matches!(error, Outgoing::Error { code: 4, .. }));
+
+    let inventory = harness.storage.find_inventory(&user_id(0)).unwrap();
+
+    assert_eq!(inventory.get(0).map(|stack| stack.quantity), Some(3));
+}
+
+#[test]
+fn exchanges_refuse_slots_holding_other_items() {
+    let mut registry = items();
+
+    registry.insert(
+        String::from("item_0001"),
+        item::Definition {
+            id: String::from("item_0001"),
+            name: String::from("Pebble"),
+            max_stack: 10,
+            consumable: false,
+        },
+    );
+
+    let pebbles = item::Stack::new(String::from("item_0001"), 3);
+
+    let inventory = Inventory::from_slots(vec![(0, pebbles)]);
+
+    let offer = Offer {
+        slots: vec![(0, item::Stack::new(String::from("item_0000"), 2))],
+        coins: 0,
+    };
+
+    let wallet = Wallet::new(0);
+
+    let result = trade::exchange(
+        [
+            (&inventory, &wallet, &offer),
+            (&Inventory::new(), &wallet, &Offer::default()),
+        ],
+        &registry,
+    );
+
+    assert!(result.is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn trades_need_adjacent_players_and_end_on_disconnect() {
+    let harness = Harness::start(vec![("map_0000", flat_tiles(1))]).await;
+
+    let (mut first, _) = harness.enter(&user_id(0)).await;
+
+    let (mut second, _) = harness.enter(&user_id(1)).await;
+
+    first
+        .send(Incoming::TradeRequest {
+            user_id: user_id(1),
+        })
+        .await;
+
+    let error = first
+        .recv_matching(|packet| matches!(packet, Outgoing::Error { .. }))
+        .await;
+
+    assert!(matches!(error, Outgoing::Error { code: 4, .. }));
+
+    time::sleep(STEP_DURATION).await;
+
+    second.walk(Direction::Right).await;
+
+    first
+        .recv_matching(|packet| matches!(packet, Outgoing::Stop { id, .. } if *id == user_id(1)))
+        .await;
+
+    open_trade(&mut first, &mut second).await;
+
+    drop(second);
+
+    assert_eq!(
+        recv_closed(&mut first).await,
+        Outgoing::TradeClosed {
+            message: String::from("cancelled"),
+        }
+    );
+}
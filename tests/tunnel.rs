@@ -0,0 +1,76 @@
+use std::{io, net::SocketAddr};
+
+use east_online_server::net::Tunnel;
+use tokio::{io::AsyncWriteExt, time};
+
+const FLOOD_SIZE: usize = 1024 * 1024;
+
+/**
+ * Take every byte a tunnel holds, until it has no more for now or was
+ * closed.
+ *
+ * Return how many bytes were taken and whether the tunnel was closed.
+ */
+fn drain(tunnel: &Tunnel) -> (usize, bool) {
+    let mut taken = 0;
+
+    loop {
+        let mut buf = Vec::with_capacity(4096);
+
+        match tunnel.try_read_buf(&mut buf) {
+            Ok(0) => return (taken, true),
+            Ok(size) => taken += size,
+            Err(_) => return (taken, false),
+        }
+    }
+}
+
+#[tokio::test]
+async fn tunnels_stop_reading_until_bytes_are_taken() {
+    let (mut peer, stream) = tokio::io::duplex(4096);
+
+    let address: SocketAddr = "127.0.0.1:0".parse().unwrap();
+
+    let tunnel = Tunnel::spawn(stream, address);
+
+    let flood = tokio::spawn(async move {
+        peer.write_all(&vec![0; FLOOD_SIZE]).await.unwrap();
+    });
+
+    time::sleep(time::Duration::from_millis(100)).await;
+
+    let (mut taken, closed) = drain(&tunnel);
+
+    assert!(taken < FLOOD_SIZE);
+
+    assert!(!closed);
+
+    assert!(!flood.is_finished());
+
+    let rest = time::timeout(time::Duration::from_secs(5), async {
+        while taken < FLOOD_SIZE {
+            tunnel.readable().await.unwrap();
+
+            taken += drain(&tunnel).0;
+        }
+    });
+
+    rest.await.unwrap();
+
+    flood.await.unwrap();
+}
+
+#[tokio::test]
+async fn tunnels_refuse_writes_once_the_peer_stops_reading() {
+    let (_peer, stream) = tokio::io::duplex(4096);
+
+    let address: SocketAddr = "127.0.0.1:0".parse().unwrap();
+
+    let tunnel = Tunnel::spawn(stream, address);
+
+    let blocked = (0..FLOOD_SIZE / 1024).any(
+        |_| matches!(tunnel.try_write(&[0; 1024]), Err(e) if e.kind() == io::ErrorKind::WouldBlock),
+    );
+
+    assert!(blocked);
+}
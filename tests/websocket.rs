@@ -0,0 +1,75 @@
+mod common;
+
+use east_online_server::{
+    auth,
+    net::{
+        client::{get_packet_buf, take_packet},
+        packet::{Incoming, Outgoing},
+        protocol,
+    },
+};
+use futures::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+
+use common::{flat_tiles, user_id, Harness};
+
+#[tokio::test]
+async fn websocket_players_share_maps_with_tcp_players() {
+    let harness = Harness::start(vec![
+        ("map_0000", flat_tiles(1)),
+        ("map_0001", flat_tiles(1)),
+    ])
+    .await;
+
+    harness.storage.set_map_id(&user_id(0), "map_0001");
+
+    let (_tcp_client, _) = harness.enter(&user_id(0)).await;
+
+    harness.storage.add_user(&user_id(1));
+
+    harness.storage.set_map_id(&user_id(1), "map_0001");
+
+    let stream = TcpStream::connect(harness.websocket_address).await.unwrap();
+
+    let (mut websocket, _) = tokio_tungstenite::client_async("ws://localhost/", stream)
+        .await
+        .unwrap();
+
+    let hello = Incoming::Hello {
+        version: protocol::PROTOCOL_VERSION,
+        features: Vec::new(),
+        token: auth::Stub::token(&user_id(1)),
+    };
+
+    websocket
+        .send(Message::Binary(get_packet_buf(&hello).unwrap()))
+        .await
+        .unwrap();
+
+    let mut buf = Vec::new();
+
+    let hello = loop {
+        match take_packet(&mut buf).unwrap() {
+            Some(packet @ Outgoing::Hello { .. }) => break packet,
+            Some(_) => continue,
+            None => {}
+        }
+
+        match websocket.next().await.unwrap().unwrap() {
+            Message::Binary(frame) => buf.extend(frame),
+            message => panic!("unexpected message, {message:?}"),
+        }
+    };
+
+    match hello {
+        Outgoing::Hello { id, map_id, actors } => {
+            assert_eq!(id, user_id(1));
+
+            assert_eq!(map_id, "map_0001");
+
+            assert!(actors.iter().any(|actor| actor.id == user_id(0)));
+        }
+        _ => unreachable!(),
+    }
+}